@@ -141,6 +141,7 @@ pub mod git;
 pub mod paths;
 pub mod publish;
 pub mod registry;
+pub mod registry_server;
 
 /*
  *
@@ -1643,6 +1644,8 @@ fn substitute_macros(input: &str) -> String {
         ("[IGNORED]", "     Ignored"),
         ("[INSTALLED]", "   Installed"),
         ("[REPLACED]", "    Replaced"),
+        ("[MERGING]", "     Merging"),
+        ("[SELECTED]", "    Selected"),
     ];
     let mut result = input.to_owned();
     for &(pat, subst) in &macros {
@@ -2,6 +2,7 @@ use crate::git::repo;
 use crate::paths;
 use cargo::sources::CRATES_IO_INDEX;
 use cargo::util::Sha256;
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use std::collections::HashMap;
@@ -146,6 +147,7 @@ pub struct Package {
     invalid_json: bool,
     proc_macro: bool,
     links: Option<String>,
+    signed: bool,
 }
 
 #[derive(Clone)]
@@ -247,6 +249,7 @@ impl Package {
             invalid_json: false,
             proc_macro: false,
             links: None,
+            signed: false,
         }
     }
 
@@ -350,6 +353,12 @@ impl Package {
         self
     }
 
+    /// Signs the entry in the index with the key from `signing_key`.
+    pub fn signed(&mut self, signed: bool) -> &mut Package {
+        self.signed = signed;
+        self
+    }
+
     /// Specifies whether or not this is a proc macro.
     pub fn proc_macro(&mut self, proc_macro: bool) -> &mut Package {
         self.proc_macro = proc_macro;
@@ -420,7 +429,7 @@ impl Package {
         } else {
             serde_json::json!(self.name)
         };
-        let line = serde_json::json!({
+        let mut line = serde_json::json!({
             "name": name,
             "vers": self.vers,
             "deps": deps,
@@ -428,15 +437,17 @@ impl Package {
             "features": self.features,
             "yanked": self.yanked,
             "links": self.links,
-        })
-        .to_string();
-
-        let file = match self.name.len() {
-            1 => format!("1/{}", self.name),
-            2 => format!("2/{}", self.name),
-            3 => format!("3/{}/{}", &self.name[..1], self.name),
-            _ => format!("{}/{}/{}", &self.name[0..2], &self.name[2..4], self.name),
-        };
+        });
+        if self.signed {
+            // Without the `preserve_order` feature of `serde_json` the keys
+            // are sorted, which is the form Cargo verifies.
+            let message = line.to_string();
+            let sig = signing_keypair().sign(message.as_bytes());
+            line["sig"] = serde_json::json!(hex::encode(sig.to_bytes().as_ref()));
+        }
+        let line = line.to_string();
+
+        let file = index_file(&self.name);
 
         let registry_path = if self.alternative {
             alt_registry_path()
@@ -560,17 +571,50 @@ impl Package {
     pub fn archive_dst(&self) -> PathBuf {
         if self.local {
             registry_path().join(format!("{}-{}.crate", self.name, self.vers))
-        } else if self.alternative {
-            alt_dl_path()
-                .join(&self.name)
-                .join(&self.vers)
-                .join(&format!("{}-{}.crate", self.name, self.vers))
         } else {
-            dl_path().join(&self.name).join(&self.vers).join("download")
+            let dl = if self.alternative {
+                alt_dl_path()
+            } else {
+                dl_path()
+            };
+            crate_file(&dl, self.alternative, &self.name, &self.vers)
         }
     }
 }
 
+/// The path of a package's file in the index, relative to the index root.
+pub(crate) fn index_file(name: &str) -> String {
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[0..2], &name[2..4], name),
+    }
+}
+
+/// The path where the `.crate` file of a package is downloaded from, within
+/// the `dl` directory of its registry.
+pub(crate) fn crate_file(dl: &Path, alternative: bool, name: &str, vers: &str) -> PathBuf {
+    let dir = dl.join(name).join(vers);
+    if alternative {
+        dir.join(&format!("{}-{}.crate", name, vers))
+    } else {
+        dir.join("download")
+    }
+}
+
+fn signing_keypair() -> Keypair {
+    let secret = SecretKey::from_bytes(&[1; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+/// The hex-encoded public key that `Package::signed` entries are signed with,
+/// to use as the `signing-key` of a registry.
+pub fn signing_key() -> String {
+    hex::encode(signing_keypair().public.as_bytes())
+}
+
 pub fn cksum(s: &[u8]) -> String {
     Sha256::new().update(s).finish_hex()
 }
@@ -0,0 +1,553 @@
+//! An in-process HTTP server implementing the registry web API.
+//!
+//! `registry::init` points the web API of the test registries at the
+//! filesystem, where tests can only check what Cargo sent. A
+//! `RegistryServer` instead takes over the `api` and `dl` of one of those
+//! registries and behaves like crates.io does: published packages are added
+//! to the registry's git index, so they can be depended on afterwards, and
+//! yanks, owners and searches work against what has been published.
+//!
+//! The raw body of the last `cargo publish` is still written to `api_path`,
+//! so `publish::validate_upload` works as well.
+
+use crate::git;
+use crate::registry::{self, crate_file, index_file};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// The login of the owner of packages published to a `RegistryServer`.
+pub const PUBLISHER: &str = "publisher";
+
+/// A running registry server, which is shut down when dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// let server = RegistryServer::start();
+/// p.cargo("publish --no-verify").run();
+/// // The published `foo` can now be depended on.
+/// ```
+pub struct RegistryServer {
+    addr: SocketAddr,
+    done: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RegistryServer {
+    /// Serves the web API of the registry pretending to be crates.io.
+    pub fn start() -> RegistryServer {
+        RegistryServer::_start(false)
+    }
+
+    /// Serves the web API of the alternative registry.
+    pub fn start_alternative() -> RegistryServer {
+        RegistryServer::_start(true)
+    }
+
+    fn _start(alternative: bool) -> RegistryServer {
+        registry::init();
+        // The paths depend on the test, which `paths::root` only knows on
+        // the test's own thread, so they are resolved before the server
+        // thread is spawned.
+        let (index, api, dl) = if alternative {
+            (
+                registry::alt_registry_path(),
+                registry::alt_api_path(),
+                registry::alt_dl_path(),
+            )
+        } else {
+            (
+                registry::registry_path(),
+                registry::api_path(),
+                registry::dl_path(),
+            )
+        };
+
+        let listener = t!(TcpListener::bind("127.0.0.1:0"));
+        let addr = t!(listener.local_addr());
+        let repo = t!(git2::Repository::open(&index));
+        t!(fs::write(
+            index.join("config.json"),
+            format!(
+                r#"{{"dl":"http://{0}/api/v1/crates","api":"http://{0}"}}"#,
+                addr
+            ),
+        ));
+        git::add(&repo);
+        git::commit(&repo);
+
+        let done = Arc::new(AtomicBool::new(false));
+        let mut server = Server {
+            alternative,
+            index,
+            api,
+            dl,
+            owners: HashMap::new(),
+            descriptions: HashMap::new(),
+        };
+        let thread = {
+            let done = done.clone();
+            thread::spawn(move || {
+                for conn in listener.incoming() {
+                    if done.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(conn) = conn {
+                        server.handle(conn);
+                    }
+                }
+            })
+        };
+        RegistryServer {
+            addr,
+            done,
+            thread: Some(thread),
+        }
+    }
+
+    /// The URL of the server, which is the `api` of the registry.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for RegistryServer {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        // Wake up the server so that it notices it's done.
+        let _ = TcpStream::connect(self.addr);
+        let _ = self.thread.take().unwrap().join();
+    }
+}
+
+/// The status code and body of a response.
+type Response = (u32, Vec<u8>);
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Server {
+    alternative: bool,
+    index: PathBuf,
+    api: PathBuf,
+    dl: PathBuf,
+    /// Owners of the packages whose owners have been asked for or changed.
+    /// Packages are otherwise owned by `PUBLISHER` alone.
+    owners: HashMap<String, Vec<String>>,
+    /// The descriptions of published packages, which the index doesn't have.
+    descriptions: HashMap<String, String>,
+}
+
+impl Server {
+    fn handle(&mut self, conn: TcpStream) {
+        let mut conn = BufReader::new(conn);
+        let req = match read_request(&mut conn) {
+            Some(req) => req,
+            None => return,
+        };
+        let (status, body) = self.respond(&req);
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            _ => "Error",
+        };
+        let conn = conn.get_mut();
+        let _ = write!(
+            conn,
+            "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            reason,
+            body.len()
+        );
+        let _ = conn.write_all(&body);
+    }
+
+    fn respond(&mut self, req: &Request) -> Response {
+        if let Some(file) = req.path.strip_prefix("/index/") {
+            return self.index_contents(file);
+        }
+        let path = match req.path.strip_prefix("/api/v1/crates") {
+            Some(path) => path,
+            None => return error(404, "not found"),
+        };
+        let segments = path.split('/').skip(1).collect::<Vec<_>>();
+        let authorized = req.headers.contains_key("authorization");
+        match (req.method.as_str(), segments.as_slice()) {
+            ("GET", []) => self.search(&req.query),
+            ("GET", [name, vers, "download"]) => self.download(name, vers),
+            _ if !authorized => error(403, "must be logged in to perform that action"),
+            ("PUT", ["new"]) => self.publish(&req.body),
+            ("GET", [name, "owners"]) => self.list_owners(name),
+            ("PUT", [name, "owners"]) => self.add_owners(name, &req.body),
+            ("DELETE", [name, "owners"]) => self.remove_owners(name, &req.body),
+            ("DELETE", [name, vers, "yank"]) => self.set_yanked(name, vers, true),
+            ("PUT", [name, vers, "unyank"]) => self.set_yanked(name, vers, false),
+            _ => error(404, "not found"),
+        }
+    }
+
+    fn publish(&mut self, body: &[u8]) -> Response {
+        let upload = self.api.join("api/v1/crates/new");
+        t!(fs::create_dir_all(upload.parent().unwrap()));
+        t!(fs::write(&upload, body));
+
+        let (new, krate) = match split_upload(body) {
+            Some((json, krate)) => match serde_json::from_slice::<Value>(json) {
+                Ok(new) => (new, krate),
+                Err(e) => return error(400, &format!("invalid upload JSON: {}", e)),
+            },
+            None => return error(400, "invalid upload"),
+        };
+        let name = new["name"].as_str().unwrap_or_default();
+        let vers = new["vers"].as_str().unwrap_or_default();
+        let mut lines = self.read_index(name);
+        if lines.iter().any(|line| line["vers"] == vers) {
+            return error(
+                200,
+                &format!("crate version `{}` is already uploaded", vers),
+            );
+        }
+
+        // In the index, a renamed dependency is listed under the name it has
+        // in `Cargo.toml`, with the name of the package in `package`.
+        let deps = new["deps"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|dep| {
+                let (name, package) = match dep["explicit_name_in_toml"].as_str() {
+                    Some(explicit) => (json!(explicit), dep["name"].clone()),
+                    None => (dep["name"].clone(), Value::Null),
+                };
+                json!({
+                    "name": name,
+                    "req": dep["version_req"],
+                    "features": dep["features"],
+                    "optional": dep["optional"],
+                    "default_features": dep["default_features"],
+                    "target": dep["target"],
+                    "kind": dep["kind"],
+                    "registry": dep["registry"],
+                    "package": package,
+                })
+            })
+            .collect::<Vec<_>>();
+        lines.push(json!({
+            "name": name,
+            "vers": vers,
+            "deps": deps,
+            "cksum": registry::cksum(krate),
+            "features": new["features"],
+            "yanked": false,
+            "links": new["links"],
+        }));
+
+        let dst = crate_file(&self.dl, self.alternative, name, vers);
+        t!(fs::create_dir_all(dst.parent().unwrap()));
+        t!(fs::write(&dst, krate));
+        self.write_index(name, &lines);
+        if let Some(description) = new["description"].as_str() {
+            self.descriptions
+                .insert(name.to_string(), description.to_string());
+        }
+        ok(json!({
+            "warnings": {
+                "invalid_categories": [],
+                "invalid_badges": [],
+                "other": [],
+            }
+        }))
+    }
+
+    fn set_yanked(&mut self, name: &str, vers: &str, yanked: bool) -> Response {
+        let mut lines = self.read_index(name);
+        match lines.iter_mut().find(|line| line["vers"] == vers) {
+            Some(line) => line["yanked"] = json!(yanked),
+            None => {
+                return error(
+                    404,
+                    &format!("crate `{}` does not have a version `{}`", name, vers),
+                )
+            }
+        }
+        self.write_index(name, &lines);
+        ok(json!({ "ok": true }))
+    }
+
+    fn list_owners(&mut self, name: &str) -> Response {
+        let owners = match self.owners(name) {
+            Some(owners) => owners,
+            None => return error(404, &format!("crate `{}` does not exist", name)),
+        };
+        let users = owners
+            .iter()
+            .enumerate()
+            .map(|(i, login)| json!({ "id": i + 1, "login": login, "name": null }))
+            .collect::<Vec<_>>();
+        ok(json!({ "users": users }))
+    }
+
+    fn add_owners(&mut self, name: &str, body: &[u8]) -> Response {
+        let users = match requested_users(body) {
+            Some(users) => users,
+            None => return error(400, "invalid owners request"),
+        };
+        let owners = match self.owners(name) {
+            Some(owners) => owners,
+            None => return error(404, &format!("crate `{}` does not exist", name)),
+        };
+        for user in &users {
+            if !owners.contains(user) {
+                owners.push(user.clone());
+            }
+        }
+        let msg = format!(
+            "user {} has been invited to be an owner of crate {}",
+            users.join(", "),
+            name
+        );
+        ok(json!({ "ok": true, "msg": msg }))
+    }
+
+    fn remove_owners(&mut self, name: &str, body: &[u8]) -> Response {
+        let users = match requested_users(body) {
+            Some(users) => users,
+            None => return error(400, "invalid owners request"),
+        };
+        let owners = match self.owners(name) {
+            Some(owners) => owners,
+            None => return error(404, &format!("crate `{}` does not exist", name)),
+        };
+        if let Some(user) = users.iter().find(|user| !owners.contains(user)) {
+            return error(404, &format!("could not find owner with login `{}`", user));
+        }
+        if owners.iter().all(|owner| users.contains(owner)) {
+            return error(400, "cannot remove all individual owners of a crate");
+        }
+        owners.retain(|owner| !users.contains(owner));
+        ok(json!({ "ok": true, "msg": "owners successfully removed" }))
+    }
+
+    /// The owners of `name`, or `None` if it isn't in the index.
+    fn owners(&mut self, name: &str) -> Option<&mut Vec<String>> {
+        if self.read_index(name).is_empty() {
+            return None;
+        }
+        Some(
+            self.owners
+                .entry(name.to_string())
+                .or_insert_with(|| vec![PUBLISHER.to_string()]),
+        )
+    }
+
+    fn search(&self, query: &str) -> Response {
+        let mut q = String::new();
+        let mut per_page = 10;
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "q" => q = value.to_lowercase(),
+                "per_page" => per_page = value.parse().unwrap_or(per_page),
+                _ => {}
+            }
+        }
+
+        let mut files = Vec::new();
+        index_files(&self.index, &mut files);
+        let mut crates = files
+            .iter()
+            .filter_map(|file| {
+                let lines = read_lines(file);
+                let name = lines.first()?["name"].as_str()?.to_string();
+                let max_version = lines
+                    .iter()
+                    .filter(|line| line["yanked"] != true)
+                    .filter_map(|line| line["vers"].as_str()?.parse::<semver::Version>().ok())
+                    .max()?;
+                Some((name, max_version))
+            })
+            .filter(|(name, _)| name.to_lowercase().contains(&q))
+            .collect::<Vec<_>>();
+        crates.sort();
+        let total = crates.len();
+        let crates = crates
+            .into_iter()
+            .take(per_page)
+            .map(|(name, max_version)| {
+                json!({
+                    "name": name,
+                    "max_version": max_version.to_string(),
+                    "description": self.descriptions.get(&name),
+                })
+            })
+            .collect::<Vec<_>>();
+        ok(json!({ "crates": crates, "meta": { "total": total } }))
+    }
+
+    fn download(&self, name: &str, vers: &str) -> Response {
+        match fs::read(crate_file(&self.dl, self.alternative, name, vers)) {
+            Ok(krate) => (200, krate),
+            Err(_) => error(
+                404,
+                &format!("crate `{}` does not have a version `{}`", name, vers),
+            ),
+        }
+    }
+
+    fn index_contents(&self, file: &str) -> Response {
+        if file.split('/').any(|part| part == ".." || part == ".git") {
+            return error(404, "not found");
+        }
+        match fs::read(self.index.join(file)) {
+            Ok(contents) => (200, contents),
+            Err(_) => error(404, "not found"),
+        }
+    }
+
+    fn index_path(&self, name: &str) -> PathBuf {
+        self.index.join(index_file(&name.to_lowercase()))
+    }
+
+    fn read_index(&self, name: &str) -> Vec<Value> {
+        read_lines(&self.index_path(name))
+    }
+
+    /// Replaces the index entries of `name` with `lines`, and commits them.
+    fn write_index(&self, name: &str, lines: &[Value]) {
+        let path = self.index_path(name);
+        let contents = lines
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        t!(fs::create_dir_all(path.parent().unwrap()));
+        t!(fs::write(&path, contents));
+        let repo = t!(git2::Repository::open(&self.index));
+        git::add(&repo);
+        git::commit(&repo);
+    }
+}
+
+fn ok(body: Value) -> Response {
+    (200, body.to_string().into_bytes())
+}
+
+fn error(status: u32, detail: &str) -> Response {
+    let body = json!({ "errors": [{ "detail": detail }] });
+    (status, body.to_string().into_bytes())
+}
+
+fn read_request(conn: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    conn.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = match target.find('?') {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, ""),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        conn.read_line(&mut line).ok()?;
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        let colon = line.find(':')?;
+        headers.insert(
+            line[..colon].to_lowercase(),
+            line[colon + 1..].trim().to_string(),
+        );
+    }
+
+    // curl waits for the go-ahead before sending larger bodies.
+    if let Some(expect) = headers.get("expect") {
+        if expect.eq_ignore_ascii_case("100-continue") {
+            conn.get_mut()
+                .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+                .ok()?;
+        }
+    }
+    let len = headers
+        .get("content-length")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; len];
+    conn.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    })
+}
+
+/// Splits the body of a publish request into its JSON and `.crate` file.
+fn split_upload(body: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (json, rest) = split_prefixed(body)?;
+    let (krate, _) = split_prefixed(rest)?;
+    Some((json, krate))
+}
+
+/// Splits off data prefixed with its length as a 32-bit little-endian integer.
+fn split_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 4 {
+        return None;
+    }
+    let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let data = &data[4..];
+    if data.len() < len {
+        return None;
+    }
+    Some(data.split_at(len))
+}
+
+fn requested_users(body: &[u8]) -> Option<Vec<String>> {
+    let req = serde_json::from_slice::<Value>(body).ok()?;
+    req["users"]
+        .as_array()?
+        .iter()
+        .map(|user| user.as_str().map(|user| user.to_string()))
+        .collect()
+}
+
+fn read_lines(path: &Path) -> Vec<Value> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Collects the files of the packages in the index at `dir`.
+fn index_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in t!(fs::read_dir(dir)) {
+        let path = t!(entry).path();
+        let name = path.file_name().unwrap();
+        if name == ".git" || name == "config.json" {
+            continue;
+        }
+        if path.is_dir() {
+            index_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
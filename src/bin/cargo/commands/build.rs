@@ -41,6 +41,7 @@ pub fn cli() -> App {
         .arg_manifest_path()
         .arg_message_format()
         .arg_build_plan()
+        .arg_keep_going()
         .arg_unit_graph()
         .after_help("Run `cargo help build` for more detailed information.\n")
 }
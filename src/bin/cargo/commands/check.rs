@@ -33,6 +33,7 @@ pub fn cli() -> App {
         .arg_target_dir()
         .arg_manifest_path()
         .arg_message_format()
+        .arg_keep_going()
         .arg_unit_graph()
         .after_help("Run `cargo help check` for more detailed information.\n")
 }
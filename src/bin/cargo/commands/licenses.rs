@@ -0,0 +1,50 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, LicensesOptions};
+
+pub fn cli() -> App {
+    subcommand("licenses")
+        .about("Report and check the licenses of the dependencies of a build")
+        .arg(opt("quiet", "No output printed to stdout").short("q"))
+        .arg_package_spec(
+            "Package(s) to check the dependencies of",
+            "Check the dependencies of all packages in the workspace",
+            "Exclude packages from the check",
+        )
+        .arg_targets_bins_examples(
+            "Only the specified binary",
+            "All binaries",
+            "Only the specified example",
+            "All examples",
+        )
+        .arg(opt("lib", "Only this package's library"))
+        .arg_release("Use the dependencies of the release profile")
+        .arg_profile("Use the dependencies of the specified profile")
+        .arg_features()
+        .arg_target_triple("Check the dependencies for the target triple")
+        .arg_manifest_path()
+        .arg(
+            opt(
+                "notices",
+                "Write the license texts of all dependencies to FILE",
+            )
+            .value_name("FILE"),
+        )
+        .after_help("Run `cargo help licenses` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    let ws = args.workspace(config)?;
+    let compile_opts = args.compile_options(
+        config,
+        CompileMode::Build,
+        Some(&ws),
+        ProfileChecking::Checked,
+    )?;
+    let opts = LicensesOptions {
+        compile_opts,
+        notices: args.value_of_path("notices", config),
+    };
+    ops::licenses(&ws, &opts)?;
+    Ok(())
+}
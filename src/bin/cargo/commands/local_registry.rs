@@ -0,0 +1,54 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, LocalRegistryOptions};
+use std::path::PathBuf;
+
+pub fn cli() -> App {
+    subcommand("local-registry")
+        .about("Add the dependencies of a project to a local registry")
+        .arg(opt("quiet", "No output printed to stdout").short("q"))
+        .arg_manifest_path()
+        .arg(
+            Arg::with_name("path")
+                .help("Root of the local registry")
+                .required(true),
+        )
+        .arg(multi_opt(
+            "platform",
+            "TRIPLE",
+            "Add the `.crate` files of packages used on the given target triple",
+        ))
+        .arg(
+            opt(
+                "all-platforms",
+                "Add the `.crate` files of packages used on any platform",
+            )
+            .conflicts_with("platform"),
+        )
+        .arg(
+            Arg::with_name("respect-source-config")
+                .long("respect-source-config")
+                .help("Respect `[source]` config in `.cargo/config`"),
+        )
+        .after_help("Run `cargo help local-registry` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    // Like `cargo vendor`, go straight to crates.io rather than to any source
+    // replacing it, which may well be the local registry itself.
+    if !args.is_present("respect-source-config") {
+        config.values_mut()?.remove("source");
+    }
+
+    let ws = args.workspace(config)?;
+    let path = PathBuf::from(args.value_of_os("path").unwrap());
+    ops::local_registry(
+        &ws,
+        &LocalRegistryOptions {
+            destination: &path,
+            platforms: args._values_of("platform"),
+            all_platforms: args.is_present("all-platforms"),
+        },
+    )?;
+    Ok(())
+}
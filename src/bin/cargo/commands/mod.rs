@@ -13,6 +13,8 @@ pub fn builtin() -> Vec<App> {
         git_checkout::cli(),
         init::cli(),
         install::cli(),
+        licenses::cli(),
+        local_registry::cli(),
         locate_project::cli(),
         login::cli(),
         metadata::cli(),
@@ -25,12 +27,14 @@ pub fn builtin() -> Vec<App> {
         run::cli(),
         rustc::cli(),
         rustdoc::cli(),
+        sbom::cli(),
         search::cli(),
         test::cli(),
         tree::cli(),
         uninstall::cli(),
         update::cli(),
         vendor::cli(),
+        verify_lock::cli(),
         verify_project::cli(),
         version::cli(),
         yank::cli(),
@@ -50,6 +54,8 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> Cli
         "git-checkout" => git_checkout::exec,
         "init" => init::exec,
         "install" => install::exec,
+        "licenses" => licenses::exec,
+        "local-registry" => local_registry::exec,
         "locate-project" => locate_project::exec,
         "login" => login::exec,
         "metadata" => metadata::exec,
@@ -62,12 +68,14 @@ pub fn builtin_exec(cmd: &str) -> Option<fn(&mut Config, &ArgMatches<'_>) -> Cli
         "run" => run::exec,
         "rustc" => rustc::exec,
         "rustdoc" => rustdoc::exec,
+        "sbom" => sbom::exec,
         "search" => search::exec,
         "test" => test::exec,
         "tree" => tree::exec,
         "uninstall" => uninstall::exec,
         "update" => update::exec,
         "vendor" => vendor::exec,
+        "verify-lock" => verify_lock::exec,
         "verify-project" => verify_project::exec,
         "version" => version::exec,
         "yank" => yank::exec,
@@ -88,6 +96,8 @@ pub mod git_checkout;
 pub mod help;
 pub mod init;
 pub mod install;
+pub mod licenses;
+pub mod local_registry;
 pub mod locate_project;
 pub mod login;
 pub mod metadata;
@@ -100,12 +110,14 @@ pub mod read_manifest;
 pub mod run;
 pub mod rustc;
 pub mod rustdoc;
+pub mod sbom;
 pub mod search;
 pub mod test;
 pub mod tree;
 pub mod uninstall;
 pub mod update;
 pub mod vendor;
+pub mod verify_lock;
 pub mod verify_project;
 pub mod version;
 pub mod yank;
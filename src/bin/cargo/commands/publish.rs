@@ -16,6 +16,11 @@ pub fn cli() -> App {
             "allow-dirty",
             "Allow dirty working directories to be packaged",
         ))
+        .arg_package_spec_no_all(
+            "Package(s) to publish",
+            "Publish all packages in the workspace",
+            "Don't publish the specified packages",
+        )
         .arg_target_triple("Build for the target triple")
         .arg_target_dir()
         .arg_manifest_path()
@@ -37,6 +42,7 @@ pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
         &ws,
         &PublishOpts {
             config,
+            to_publish: args.packages_from_flags()?,
             token: args.value_of("token").map(|s| s.to_string()),
             index,
             verify: !args.is_present("no-verify"),
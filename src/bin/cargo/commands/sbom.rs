@@ -0,0 +1,55 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, SbomFormat, SbomOptions};
+
+pub fn cli() -> App {
+    subcommand("sbom")
+        .about("Generate a Software Bill of Materials for the artifacts of a build")
+        .arg(opt("quiet", "No output printed to stdout").short("q"))
+        .arg_package_spec(
+            "Package(s) to generate an SBOM for",
+            "Generate an SBOM for all packages in the workspace",
+            "Exclude packages from the SBOM",
+        )
+        .arg_targets_bins_examples(
+            "Only the specified binary",
+            "All binaries",
+            "Only the specified example",
+            "All examples",
+        )
+        .arg(opt("lib", "Only this package's library"))
+        .arg_release("Use the dependencies of the release profile")
+        .arg_profile("Use the dependencies of the specified profile")
+        .arg_features()
+        .arg_target_triple("Generate the SBOM for the target triple")
+        .arg_manifest_path()
+        .arg(
+            opt("format", "Format of the SBOM documents")
+                .value_name("FMT")
+                .possible_values(&["cyclonedx", "spdx"])
+                .default_value("cyclonedx"),
+        )
+        .arg(opt("out-dir", "Write one file per artifact into this directory").value_name("PATH"))
+        .after_help("Run `cargo help sbom` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    let ws = args.workspace(config)?;
+    let compile_opts = args.compile_options(
+        config,
+        CompileMode::Build,
+        Some(&ws),
+        ProfileChecking::Checked,
+    )?;
+    let format = match args.value_of("format") {
+        Some("spdx") => SbomFormat::Spdx,
+        _ => SbomFormat::CycloneDx,
+    };
+    let opts = SbomOptions {
+        compile_opts,
+        format,
+        out_dir: args.value_of_path("out-dir", config),
+    };
+    ops::sbom(&ws, &opts)?;
+    Ok(())
+}
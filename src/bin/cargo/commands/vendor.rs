@@ -32,6 +32,15 @@ pub fn cli() -> App {
                 .long("versioned-dirs")
                 .help("Always include version in subdir name"),
         )
+        .arg(multi_opt(
+            "platform",
+            "TRIPLE",
+            "Only vendor packages used on the given target triple",
+        ))
+        .arg(opt(
+            "check",
+            "Check that the vendor directory is up to date, without changing it",
+        ))
         .arg(
             Arg::with_name("no-merge-sources")
                 .long("no-merge-sources")
@@ -108,6 +117,8 @@ https://github.com/rust-lang/cargo/issues/new
                 .unwrap_or_default()
                 .map(|s| PathBuf::from(s.to_os_string()))
                 .collect(),
+            platforms: args._values_of("platform"),
+            check: args.is_present("check"),
         },
     )?;
     Ok(())
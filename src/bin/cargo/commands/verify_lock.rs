@@ -0,0 +1,49 @@
+use crate::command_prelude::*;
+
+use cargo::ops::{self, VerifyLockOptions};
+
+pub fn cli() -> App {
+    subcommand("verify-lock")
+        .about("Check the lock file against the configured lock file policies")
+        .arg(opt("quiet", "No output printed to stdout").short("q"))
+        .arg_manifest_path()
+        .arg(opt(
+            "deny-yanked",
+            "Fail on packages yanked from their registry",
+        ))
+        .arg(opt("deny-git", "Fail on packages from git repositories"))
+        .arg(opt(
+            "deny-external-paths",
+            "Fail on path packages outside of the workspace",
+        ))
+        .arg(opt(
+            "require-checksums",
+            "Fail on registry packages without a checksum",
+        ))
+        .arg(multi_opt(
+            "allow-registry",
+            "REGISTRY",
+            "Only allow packages from the given registries",
+        ))
+        .arg(
+            opt("message-format", "Output representation")
+                .value_name("FMT")
+                .possible_values(&["human", "json"])
+                .default_value("human"),
+        )
+        .after_help("Run `cargo help verify-lock` for more detailed information.\n")
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    let ws = args.workspace(config)?;
+    let opts = VerifyLockOptions {
+        deny_yanked: args.is_present("deny-yanked"),
+        deny_git: args.is_present("deny-git"),
+        deny_external_paths: args.is_present("deny-external-paths"),
+        require_checksums: args.is_present("require-checksums"),
+        allowed_registries: values(args, "allow-registry"),
+        json: args.value_of("message-format") == Some("json"),
+    };
+    ops::verify_lock(&ws, &opts)?;
+    Ok(())
+}
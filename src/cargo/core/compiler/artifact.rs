@@ -0,0 +1,53 @@
+//! Environment variables describing the artifacts of artifact dependencies.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::unit_graph::UnitDep;
+use super::{Context, CrateType, FileFlavor};
+use crate::util::errors::CargoResult;
+
+/// Returns the environment variables pointing to the artifacts built for the
+/// artifact dependencies in `deps`.
+///
+/// For each binary `<name>` of a dependency `<dep>` this sets
+/// `CARGO_BIN_FILE_<DEP>_<name>`, along with `CARGO_BIN_FILE_<DEP>` if the
+/// binary has the same name as the dependency, and `CARGO_BIN_DIR_<DEP>`.
+/// C libraries are available as `CARGO_CDYLIB_FILE_<DEP>` and
+/// `CARGO_STATICLIB_FILE_<DEP>`.
+pub fn get_env(cx: &Context<'_, '_>, deps: &[UnitDep]) -> CargoResult<HashMap<String, PathBuf>> {
+    let mut env = HashMap::new();
+    for dep in deps {
+        let dep_name = match dep.artifact {
+            Some(name) => name,
+            None => continue,
+        };
+        let env_name = super::envify(&dep_name);
+        for output in cx.outputs(&dep.unit)?.iter() {
+            if output.flavor == FileFlavor::DebugInfo || output.flavor == FileFlavor::Rmeta {
+                continue;
+            }
+            let path = output.path.clone();
+            match output.crate_type {
+                Some(CrateType::Bin) => {
+                    let bin_name = dep.unit.target.name();
+                    if let Some(dir) = path.parent() {
+                        env.insert(format!("CARGO_BIN_DIR_{}", env_name), dir.to_path_buf());
+                    }
+                    if bin_name == dep_name.as_str() {
+                        env.insert(format!("CARGO_BIN_FILE_{}", env_name), path.clone());
+                    }
+                    env.insert(format!("CARGO_BIN_FILE_{}_{}", env_name, bin_name), path);
+                }
+                Some(CrateType::Cdylib) => {
+                    env.insert(format!("CARGO_CDYLIB_FILE_{}", env_name), path);
+                }
+                Some(CrateType::Staticlib) => {
+                    env.insert(format!("CARGO_STATICLIB_FILE_{}", env_name), path);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(env)
+}
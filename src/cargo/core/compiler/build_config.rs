@@ -26,6 +26,8 @@ pub struct BuildConfig {
     pub build_plan: bool,
     /// Output the unit graph to stdout instead of actually compiling.
     pub unit_graph: bool,
+    /// Keep building units that don't depend on a failed unit.
+    pub keep_going: bool,
     /// An optional override of the rustc process for primary units
     pub primary_unit_rustc: Option<ProcessBuilder>,
     /// A thread used by `cargo fix` to receive messages on a socket regarding
@@ -37,6 +39,9 @@ pub struct BuildConfig {
     // Note that, although the cmd-line flag name is `out-dir`, in code we use
     // `export_dir`, to avoid confusion with out dir at `target/debug/deps`.
     pub export_dir: Option<PathBuf>,
+    /// Whether to display warnings from the build scripts of non-local
+    /// packages, even if not very verbose.
+    pub dependency_script_warnings: bool,
 }
 
 impl BuildConfig {
@@ -45,6 +50,7 @@ impl BuildConfig {
     ///
     /// * `build.jobs`
     /// * `build.target`
+    /// * `build.dependency-script-warnings`
     /// * `target.$target.ar`
     /// * `target.$target.linker`
     /// * `target.$target.libfoo.metadata`
@@ -77,9 +83,11 @@ impl BuildConfig {
             force_rebuild: false,
             build_plan: false,
             unit_graph: false,
+            keep_going: false,
             primary_unit_rustc: None,
             rustfix_diagnostic_server: RefCell::new(None),
             export_dir: None,
+            dependency_script_warnings: cfg.dependency_script_warnings.unwrap_or(false),
         })
     }
 
@@ -93,10 +101,28 @@ impl BuildConfig {
         self.mode == CompileMode::Test || self.mode == CompileMode::Bench
     }
 
-    pub fn single_requested_kind(&self) -> CargoResult<CompileKind> {
-        match self.requested_kinds.len() {
-            1 => Ok(self.requested_kinds[0]),
-            _ => bail!("only one `--target` argument is supported"),
+    /// Returns the only requested kind, or an error explaining that `what`
+    /// (such as "`cargo run`") can't be used when building for several
+    /// targets at once.
+    pub fn single_requested_kind(&self, what: &str) -> CargoResult<CompileKind> {
+        match self.requested_kinds.as_slice() {
+            [kind] => Ok(*kind),
+            kinds => {
+                let names: Vec<_> = kinds
+                    .iter()
+                    .map(|kind| match kind {
+                        CompileKind::Host => "host",
+                        CompileKind::Target(target) => target.short_name(),
+                    })
+                    .collect();
+                bail!(
+                    "{} only supports a single target, but {} targets were requested: {}\n\
+                     Pass a single `--target` flag to select one of them.",
+                    what,
+                    kinds.len(),
+                    names.join(", ")
+                )
+            }
         }
     }
 }
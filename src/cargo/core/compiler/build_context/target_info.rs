@@ -6,7 +6,8 @@ use cargo_platform::{Cfg, CfgExpr};
 use std::cell::RefCell;
 use std::collections::hash_map::{Entry, HashMap};
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::{self, FromStr};
 
 /// Information about the platform target gleaned from querying rustc.
@@ -40,6 +41,9 @@ pub struct TargetInfo {
     pub rustflags: Vec<String>,
     /// Extra flags to pass to `rustdoc`, see `env_args`.
     pub rustdocflags: Vec<String>,
+    /// Names of the codegen backends `rustc` can load, see
+    /// `discover_codegen_backends`.
+    codegen_backends: Vec<String>,
 }
 
 /// Kind of each file generated by a Unit, part of `FileType`.
@@ -54,7 +58,7 @@ pub enum FileFlavor {
     Linkable,
     /// An `.rmeta` Rust metadata file.
     Rmeta,
-    /// Piece of external debug information (e.g., `.dSYM`/`.pdb` file).
+    /// Piece of external debug information (e.g., `.dSYM`/`.pdb`/`.dwp` file).
     DebugInfo,
 }
 
@@ -206,9 +210,12 @@ impl TargetInfo {
                 )
             })?;
 
+        let codegen_backends = discover_codegen_backends(&sysroot, &rustc.host);
+
         Ok(TargetInfo {
             crate_type_process,
             crate_types: RefCell::new(map),
+            codegen_backends,
             sysroot,
             sysroot_host_libdir,
             sysroot_target_libdir,
@@ -251,6 +258,11 @@ impl TargetInfo {
         &self.cfg
     }
 
+    /// The names of the codegen backends available to `rustc`.
+    pub fn codegen_backends(&self) -> &[String] {
+        &self.codegen_backends
+    }
+
     /// Returns the list of file types generated by the given crate type.
     ///
     /// Returns `None` if the target does not support the given crate type.
@@ -388,6 +400,18 @@ impl TargetInfo {
                     // preserved.
                     should_replace_hyphens: true,
                 })
+            } else if !target_triple.contains("-windows-") && !target_triple.starts_with("wasm") {
+                // The DWARF package produced with `split-debuginfo = "packed"`.
+                // Debuggers look for it next to the executable, with the
+                // same name plus a `.dwp` extension.
+                let should_replace_hyphens = crate_type != CrateType::Bin;
+                ret.push(FileType {
+                    suffix: ".dwp".to_string(),
+                    prefix: prefix.clone(),
+                    flavor: FileFlavor::DebugInfo,
+                    crate_type: Some(crate_type),
+                    should_replace_hyphens,
+                })
             }
         }
 
@@ -474,6 +498,44 @@ impl TargetInfo {
     }
 }
 
+/// Returns the codegen backends that `rustc` can load with
+/// `-Zcodegen-backend`: the built-in LLVM backend and the ones installed in the
+/// `codegen-backends` directory of the sysroot, like the cranelift backend
+/// shipped as a rustup component.
+///
+/// The backends are compiler plugins, so they are looked up for the host even
+/// when cross compiling.
+fn discover_codegen_backends(sysroot: &Path, host: &str) -> Vec<String> {
+    let mut backends = vec!["llvm".to_string()];
+    let dir = sysroot
+        .join("lib")
+        .join("rustlib")
+        .join(host)
+        .join("codegen-backends");
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return backends,
+    };
+    // Backends are named like `librustc_codegen_cranelift-<release>.so`.
+    let prefix = format!("{}rustc_codegen_", env::consts::DLL_PREFIX);
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name();
+        let file_name = match file_name.to_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        if !file_name.starts_with(&prefix) || !file_name.ends_with(env::consts::DLL_SUFFIX) {
+            continue;
+        }
+        let name = &file_name[prefix.len()..file_name.len() - env::consts::DLL_SUFFIX.len()];
+        let name = name.split('-').next().unwrap();
+        if !name.is_empty() && !backends.iter().any(|b| b == name) {
+            backends.push(name.to_string());
+        }
+    }
+    backends
+}
+
 /// Takes rustc output (using specialized command line args), and calculates the file prefix and
 /// suffix for the given crate type, or returns `None` if the type is not supported. (e.g., for a
 /// Rust library like `libcargo.rlib`, we have prefix "lib" and suffix "rlib").
@@ -692,6 +754,23 @@ impl RustcTargetData {
         })
     }
 
+    /// Returns the host and every target that information was gathered for.
+    pub fn all_kinds(&self) -> impl Iterator<Item = CompileKind> + '_ {
+        std::iter::once(CompileKind::Host).chain(
+            self.target_info
+                .keys()
+                .map(|target| CompileKind::Target(*target)),
+        )
+    }
+
+    /// Returns whether information was gathered for the given kind.
+    pub fn has_kind(&self, kind: CompileKind) -> bool {
+        match kind {
+            CompileKind::Host => true,
+            CompileKind::Target(s) => self.target_info.contains_key(&s),
+        }
+    }
+
     /// Returns a "short" name for the given kind, suitable for keying off
     /// configuration in Cargo or presenting to users.
     pub fn short_name<'a>(&'a self, kind: &'a CompileKind) -> &'a str {
@@ -22,6 +22,9 @@ pub struct Doctest {
     pub unstable_opts: bool,
     /// The -Clinker value to use.
     pub linker: Option<PathBuf>,
+    /// The environment variables pointing to the artifacts of artifact
+    /// dependencies.
+    pub env: HashMap<String, PathBuf>,
 }
 
 /// A structure returning the result of a compilation.
@@ -58,6 +61,9 @@ pub struct Compilation<'cfg> {
     /// The path to libstd for each target
     sysroot_target_libdir: HashMap<CompileKind, PathBuf>,
 
+    /// The kinds for which the standard library is built from source.
+    std_kinds: HashSet<CompileKind>,
+
     /// Extra environment variables that were passed to compilations and should
     /// be passed to future invocations of programs.
     pub extra_env: HashMap<PackageId, Vec<(String, String)>>,
@@ -113,17 +119,21 @@ impl<'cfg> Compilation<'cfg> {
                 .sysroot_host_libdir
                 .clone(),
             sysroot_target_libdir: bcx
-                .build_config
-                .requested_kinds
-                .iter()
-                .chain(Some(&CompileKind::Host))
+                .target_data
+                .all_kinds()
                 .map(|kind| {
                     (
-                        *kind,
-                        bcx.target_data.info(*kind).sysroot_target_libdir.clone(),
+                        kind,
+                        bcx.target_data.info(kind).sysroot_target_libdir.clone(),
                     )
                 })
                 .collect(),
+            std_kinds: bcx
+                .unit_graph
+                .keys()
+                .filter(|unit| unit.is_std)
+                .map(|unit| unit.kind)
+                .collect(),
             tests: Vec::new(),
             binaries: Vec::new(),
             cdylibs: Vec::new(),
@@ -137,15 +147,21 @@ impl<'cfg> Compilation<'cfg> {
             rustc_workspace_wrapper_process,
             primary_rustc_process,
             target_runners: bcx
-                .build_config
-                .requested_kinds
-                .iter()
-                .chain(Some(&CompileKind::Host))
-                .map(|kind| Ok((*kind, target_runner(bcx, *kind)?)))
+                .target_data
+                .all_kinds()
+                .map(|kind| Ok((kind, target_runner(bcx, kind)?)))
                 .collect::<CargoResult<HashMap<_, _>>>()?,
         })
     }
 
+    /// Sorts the tests, binaries and cdylibs so that the outputs built for
+    /// each target are grouped together when building for several targets.
+    pub(super) fn group_by_kind(&mut self) {
+        for outputs in [&mut self.tests, &mut self.binaries, &mut self.cdylibs].iter_mut() {
+            outputs.sort_by(|(a, _), (b, _)| a.kind.cmp(&b.kind).then_with(|| a.cmp(b)));
+        }
+    }
+
     /// See `process`.
     pub fn rustc_process(
         &self,
@@ -239,7 +255,7 @@ impl<'cfg> Compilation<'cfg> {
             // libs from the sysroot that ships with rustc. This may not be
             // required (at least I cannot craft a situation where it
             // matters), but is here to be safe.
-            if self.config.cli_unstable().build_std.is_none() {
+            if !self.std_kinds.contains(&kind) {
                 search_path.push(self.sysroot_target_libdir[&kind].clone());
             }
         }
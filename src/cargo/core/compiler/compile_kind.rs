@@ -2,7 +2,6 @@ use crate::core::Target;
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::interning::InternedString;
 use crate::util::Config;
-use anyhow::bail;
 use serde::Serialize;
 use std::collections::BTreeSet;
 use std::path::Path;
@@ -50,33 +49,26 @@ impl CompileKind {
         config: &Config,
         targets: &[String],
     ) -> CargoResult<Vec<CompileKind>> {
-        if targets.len() > 1 && !config.cli_unstable().multitarget {
-            bail!("specifying multiple `--target` flags requires `-Zmultitarget`")
-        }
-        if !targets.is_empty() {
-            return Ok(targets
-                .iter()
-                .map(|value| Ok(CompileKind::Target(CompileTarget::new(value)?)))
-                // First collect into a set to deduplicate any `--target` passed
-                // more than once...
-                .collect::<CargoResult<BTreeSet<_>>>()?
-                // ... then generate a flat list for everything else to use.
-                .into_iter()
-                .collect());
-        }
-        let kind = match &config.build_config()?.target {
-            Some(val) => {
-                let value = if val.raw_value().ends_with(".json") {
-                    let path = val.clone().resolve_path(config);
-                    path.to_str().expect("must be utf-8 in toml").to_string()
-                } else {
-                    val.raw_value().to_string()
-                };
-                CompileKind::Target(CompileTarget::new(&value)?)
+        let targets = if !targets.is_empty() {
+            targets.to_vec()
+        } else {
+            match &config.build_config()?.target {
+                Some(val) => val.values(config),
+                None => Vec::new(),
             }
-            None => CompileKind::Host,
         };
-        Ok(vec![kind])
+        if targets.is_empty() {
+            return Ok(vec![CompileKind::Host]);
+        }
+        Ok(targets
+            .iter()
+            .map(|value| Ok(CompileKind::Target(CompileTarget::new(value)?)))
+            // First collect into a set to deduplicate any `--target` passed
+            // more than once...
+            .collect::<CargoResult<BTreeSet<_>>>()?
+            // ... then generate a flat list for everything else to use.
+            .into_iter()
+            .collect())
     }
 }
 
@@ -89,6 +89,9 @@ pub struct CompilationFiles<'a, 'cfg> {
     pub(super) target: HashMap<CompileTarget, Layout>,
     /// Additional directory to include a copy of the outputs.
     export_dir: Option<PathBuf>,
+    /// Whether outputs are exported into a subdirectory of `export_dir` named
+    /// after their target, because several targets were requested.
+    export_per_target: bool,
     /// The root targets requested by the user on the command line (does not
     /// include dependencies).
     roots: Vec<Unit>,
@@ -111,6 +114,8 @@ pub struct OutputFile {
     pub export_path: Option<PathBuf>,
     /// Type of the file (library / debug symbol / else).
     pub flavor: FileFlavor,
+    /// The crate-type that generates this file, if any.
+    pub crate_type: Option<CrateType>,
 }
 
 impl OutputFile {
@@ -143,6 +148,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
             host,
             target,
             export_dir: cx.bcx.build_config.export_dir.clone(),
+            export_per_target: cx.bcx.build_config.requested_kinds.len() > 1,
             roots: cx.bcx.roots.clone(),
             metas,
             outputs,
@@ -190,9 +196,18 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
         }
     }
 
-    /// Additional export directory from `--out-dir`.
-    pub fn export_dir(&self) -> Option<PathBuf> {
-        self.export_dir.clone()
+    /// Additional export directory from `--out-dir` for the given unit.
+    ///
+    /// When building for several targets the outputs of each target are
+    /// placed in a subdirectory named after the target.
+    pub fn export_dir(&self, unit: &Unit) -> Option<PathBuf> {
+        let export_dir = self.export_dir.as_ref()?;
+        match unit.kind {
+            CompileKind::Target(target) if self.export_per_target => {
+                Some(export_dir.join(target.short_name()))
+            }
+            _ => Some(export_dir.clone()),
+        }
     }
 
     /// Directory name to use for a package in the form `NAME-HASH`.
@@ -383,6 +398,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                     hardlink: None,
                     export_path: None,
                     flavor: FileFlavor::Normal,
+                    crate_type: None,
                 }]
             }
             CompileMode::RunCustomBuild => {
@@ -449,7 +465,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
             let export_path = if unit.target.is_custom_build() {
                 None
             } else {
-                self.export_dir.as_ref().and_then(|export_dir| {
+                self.export_dir(unit).and_then(|export_dir| {
                     hardlink
                         .as_ref()
                         .map(|hardlink| export_dir.join(hardlink.file_name().unwrap()))
@@ -460,6 +476,7 @@ impl<'a, 'cfg: 'a> CompilationFiles<'a, 'cfg> {
                 hardlink,
                 export_path,
                 flavor: file_type.flavor,
+                crate_type: file_type.crate_type,
             });
         }
         Ok(outputs)
@@ -123,7 +123,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     /// Starts compilation, waits for it to finish, and returns information
     /// about the result of compilation.
     pub fn compile(mut self, exec: &Arc<dyn Executor>) -> CargoResult<Compilation<'cfg>> {
-        let mut queue = JobQueue::new(self.bcx);
+        let mut queue = JobQueue::new(self.bcx)?;
         let mut plan = BuildPlan::new();
         let build_plan = self.bcx.build_config.build_plan;
         self.prepare_units()?;
@@ -216,6 +216,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                     args,
                     unstable_opts,
                     linker: self.bcx.linker(unit.kind),
+                    env: super::artifact::get_env(&self, &self.bcx.unit_graph[unit])?,
                 });
             }
 
@@ -262,6 +263,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 self.compilation.native_dirs.insert(dir.clone());
             }
         }
+        self.compilation.group_by_kind();
         Ok(self.compilation)
     }
 
@@ -286,8 +288,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         let dest = self.bcx.profiles.get_dir_name();
         let host_layout = Layout::new(self.bcx.ws, None, &dest)?;
         let mut targets = HashMap::new();
-        for kind in self.bcx.build_config.requested_kinds.iter() {
-            if let CompileKind::Target(target) = *kind {
+        for kind in self.bcx.target_data.all_kinds() {
+            if let CompileKind::Target(target) = kind {
                 let layout = Layout::new(self.bcx.ws, Some(target), &dest)?;
                 targets.insert(target, layout);
             }
@@ -318,13 +320,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         }
 
         let files = self.files.as_ref().unwrap();
-        for &kind in self
-            .bcx
-            .build_config
-            .requested_kinds
-            .iter()
-            .chain(Some(&CompileKind::Host))
-        {
+        for kind in self.bcx.target_data.all_kinds() {
             let layout = files.layout(kind);
             self.compilation
                 .root_output
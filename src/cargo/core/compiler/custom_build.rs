@@ -2,7 +2,7 @@ use super::job::{Freshness, Job, Work};
 use super::{fingerprint, Context, Unit};
 use crate::core::compiler::context::Metadata;
 use crate::core::compiler::job_queue::JobState;
-use crate::core::{profiles::ProfileRoot, PackageId};
+use crate::core::{profiles::ProfileRoot, PackageId, Target};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::machine_message::{self, Message};
 use crate::util::{self, internal, paths, profile};
@@ -22,8 +22,9 @@ pub struct BuildOutput {
     pub library_paths: Vec<PathBuf>,
     /// Names and link kinds of libraries, suitable for the `-l` flag.
     pub library_links: Vec<String>,
-    /// Linker arguments suitable to be passed to `-C link-arg=<args>`
-    pub linker_args: Vec<String>,
+    /// Linker arguments suitable to be passed to `-C link-arg=<args>`, along
+    /// with the kind of targets they apply to.
+    pub linker_args: Vec<(LinkType, String)>,
     /// Various `--cfg` flags to pass to the compiler.
     pub cfgs: Vec<String>,
     /// Additional environment variables to run the compiler with.
@@ -37,6 +38,43 @@ pub struct BuildOutput {
     pub rerun_if_env_changed: Vec<String>,
     /// Warnings generated by this build.
     pub warnings: Vec<String>,
+    /// Errors generated by this build. The build script fails if there are
+    /// any.
+    pub errors: Vec<String>,
+}
+
+/// The kinds of targets a linker argument from a build script applies to.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LinkType {
+    /// `cargo:rustc-link-arg`, every target which is linked.
+    All,
+    /// `cargo:rustc-cdylib-link-arg`
+    Cdylib,
+    /// `cargo:rustc-link-arg-bins`
+    Bin,
+    /// `cargo:rustc-link-arg-bin=NAME=ARG`
+    SingleBin(String),
+    /// `cargo:rustc-link-arg-tests`
+    Test,
+    /// `cargo:rustc-link-arg-benches`
+    Bench,
+    /// `cargo:rustc-link-arg-examples`
+    Example,
+}
+
+impl LinkType {
+    /// Returns whether arguments of this kind are passed when linking `target`.
+    pub fn applies_to(&self, target: &Target) -> bool {
+        match self {
+            LinkType::All => true,
+            LinkType::Cdylib => target.is_cdylib(),
+            LinkType::Bin => target.is_bin(),
+            LinkType::SingleBin(name) => target.is_bin() && target.name() == name,
+            LinkType::Test => target.is_test(),
+            LinkType::Bench => target.is_bench(),
+            LinkType::Example => target.is_exe_example(),
+        }
+    }
 }
 
 /// Map of packages to build script output.
@@ -178,7 +216,10 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
     // carried over.
     let to_exec = to_exec.into_os_string();
     let mut cmd = cx.compilation.host_process(to_exec, &unit.pkg)?;
-    let debug = unit.profile.debuginfo.unwrap_or(0) != 0;
+    let debug = unit
+        .profile
+        .debuginfo
+        .map_or(false, |debuginfo| debuginfo.is_turned_on());
     cmd.env("OUT_DIR", &script_out_dir)
         .env("CARGO_MANIFEST_DIR", unit.pkg.root())
         .env("NUM_JOBS", &bcx.jobs().to_string())
@@ -208,6 +249,12 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         cmd.env("CARGO_MANIFEST_LINKS", links);
     }
 
+    // Artifact dependencies are build dependencies of the build script
+    // itself, so they are found among the dependencies of its compilation.
+    for (var, path) in super::artifact::get_env(cx, cx.unit_deps(build_script_unit))? {
+        cmd.env(&var, path);
+    }
+
     // Be sure to pass along all enabled features for this package, this is the
     // last piece of statically known information that we have.
     for feat in &unit.features {
@@ -267,6 +314,8 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         })
         .collect::<Vec<_>>();
     let pkg_name = unit.pkg.to_string();
+    let targets: Vec<Target> = unit.pkg.targets().to_vec();
+    let targets_fresh = targets.clone();
     let build_script_outputs = Arc::clone(&cx.build_script_outputs);
     let id = unit.pkg.package_id();
     let output_file = script_run_dir.join("output");
@@ -392,16 +441,27 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         paths::set_file_time_no_err(output_file, timestamp);
         paths::write(&err_file, &output.stderr)?;
         paths::write(&root_output_file, util::path2bytes(&script_out_dir)?)?;
-        let parsed_output =
-            BuildOutput::parse(&output.stdout, &pkg_name, &script_out_dir, &script_out_dir)?;
+        let parsed_output = BuildOutput::parse(
+            &output.stdout,
+            &pkg_name,
+            &script_out_dir,
+            &script_out_dir,
+            &targets,
+        )?;
 
-        if json_messages {
+        // Errors are displayed by the job queue along with the warnings once
+        // this unit fails, so the output needs to be recorded first.
+        let has_errors = !parsed_output.errors.is_empty();
+        if json_messages && !has_errors {
             emit_build_output(state, &parsed_output, script_out_dir.as_path(), id);
         }
         build_script_outputs
             .lock()
             .unwrap()
             .insert(id, metadata_hash, parsed_output);
+        if has_errors {
+            anyhow::bail!("build script of `{}` reported errors", pkg_name);
+        }
         Ok(())
     });
 
@@ -417,6 +477,7 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
                 &pkg_name,
                 &prev_script_out_dir,
                 &script_out_dir,
+                &targets_fresh,
             )?,
         };
 
@@ -466,6 +527,7 @@ impl BuildOutput {
         pkg_name: &str,
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
+        targets: &[Target],
     ) -> CargoResult<BuildOutput> {
         let contents = paths::read_bytes(path)?;
         BuildOutput::parse(
@@ -473,16 +535,19 @@ impl BuildOutput {
             pkg_name,
             script_out_dir_when_generated,
             script_out_dir,
+            targets,
         )
     }
 
     // Parses the output of a script.
     // The `pkg_name` is used for error messages.
+    // The `targets` of the package are used to check `rustc-link-arg-bin`.
     pub fn parse(
         input: &[u8],
         pkg_name: &str,
         script_out_dir_when_generated: &Path,
         script_out_dir: &Path,
+        targets: &[Target],
     ) -> CargoResult<BuildOutput> {
         let mut library_paths = Vec::new();
         let mut library_links = Vec::new();
@@ -493,6 +558,7 @@ impl BuildOutput {
         let mut rerun_if_changed = Vec::new();
         let mut rerun_if_env_changed = Vec::new();
         let mut warnings = Vec::new();
+        let mut errors = Vec::new();
         let whence = format!("build script of `{}`", pkg_name);
 
         for line in input.split(|b| *b == b'\n') {
@@ -535,10 +601,43 @@ impl BuildOutput {
                 }
                 "rustc-link-lib" => library_links.push(value.to_string()),
                 "rustc-link-search" => library_paths.push(PathBuf::from(value)),
-                "rustc-cdylib-link-arg" => linker_args.push(value.to_string()),
+                "rustc-link-arg" => linker_args.push((LinkType::All, value)),
+                "rustc-cdylib-link-arg" => linker_args.push((LinkType::Cdylib, value)),
+                "rustc-link-arg-bins" => linker_args.push((LinkType::Bin, value)),
+                "rustc-link-arg-bin" => {
+                    let mut parts = value.splitn(2, '=');
+                    let (bin_name, arg) = match (parts.next(), parts.next()) {
+                        (Some(bin_name), Some(arg)) => (bin_name, arg),
+                        _ => anyhow::bail!(
+                            "invalid instruction `cargo:{}={}` from {}\n\
+                             The instruction should have the form cargo:{}=BIN=ARG",
+                            key,
+                            value,
+                            whence,
+                            key
+                        ),
+                    };
+                    if !targets
+                        .iter()
+                        .any(|target| target.is_bin() && target.name() == bin_name)
+                    {
+                        anyhow::bail!(
+                            "invalid instruction `cargo:{}` from {}\n\
+                             The package has no bin target named `{}`",
+                            key,
+                            whence,
+                            bin_name
+                        );
+                    }
+                    linker_args.push((LinkType::SingleBin(bin_name.to_string()), arg.to_string()));
+                }
+                "rustc-link-arg-tests" => linker_args.push((LinkType::Test, value)),
+                "rustc-link-arg-benches" => linker_args.push((LinkType::Bench, value)),
+                "rustc-link-arg-examples" => linker_args.push((LinkType::Example, value)),
                 "rustc-cfg" => cfgs.push(value.to_string()),
                 "rustc-env" => env.push(BuildOutput::parse_rustc_env(&value, &whence)?),
                 "warning" => warnings.push(value.to_string()),
+                "error" => errors.push(value.to_string()),
                 "rerun-if-changed" => rerun_if_changed.push(PathBuf::from(value)),
                 "rerun-if-env-changed" => rerun_if_env_changed.push(value.to_string()),
                 _ => metadata.push((key.to_string(), value.to_string())),
@@ -555,6 +654,7 @@ impl BuildOutput {
             rerun_if_changed,
             rerun_if_env_changed,
             warnings,
+            errors,
         })
     }
 
@@ -790,6 +890,7 @@ fn prev_build_output(cx: &mut Context<'_, '_>, unit: &Unit) -> (Option<BuildOutp
             &unit.pkg.to_string(),
             &prev_script_out_dir,
             &script_out_dir,
+            unit.pkg.targets(),
         )
         .ok(),
         prev_script_out_dir,
@@ -1245,13 +1245,14 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
     //
     // Skip fingerprints of binaries because they don't actually induce a
     // recompile, they're just dependencies in the sense that they need to be
-    // built.
+    // built. Binaries of artifact dependencies are kept, as their paths are
+    // baked into the compiled unit.
     //
     // Create Vec since mutable cx is needed in closure.
     let deps = Vec::from(cx.unit_deps(unit));
     let mut deps = deps
         .into_iter()
-        .filter(|dep| !dep.unit.target.is_bin())
+        .filter(|dep| !dep.unit.target.is_bin() || dep.artifact.is_some())
         .map(|dep| DepFingerprint::new(cx, unit, &dep))
         .collect::<CargoResult<Vec<_>>>()?;
     deps.sort_by(|a, b| a.pkg_id.cmp(&b.pkg_id));
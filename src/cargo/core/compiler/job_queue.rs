@@ -152,6 +152,13 @@ struct DrainState<'cfg> {
 
     // How many jobs we've finished
     finished: usize,
+
+    /// Whether to keep scheduling independent work after a job has failed
+    /// (`--keep-going`).
+    keep_going: bool,
+    /// Units whose job returned an error, used for the `--keep-going`
+    /// summary.
+    failed: Vec<Unit>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -269,12 +276,12 @@ impl<'a> JobState<'a> {
 }
 
 impl<'cfg> JobQueue<'cfg> {
-    pub fn new(bcx: &BuildContext<'_, 'cfg>) -> JobQueue<'cfg> {
-        JobQueue {
+    pub fn new(bcx: &BuildContext<'_, 'cfg>) -> CargoResult<JobQueue<'cfg>> {
+        Ok(JobQueue {
             queue: DependencyQueue::new(),
             counts: HashMap::new(),
-            timings: Timings::new(bcx, &bcx.roots),
-        }
+            timings: Timings::new(bcx, &bcx.roots)?,
+        })
     }
 
     pub fn enqueue(&mut self, cx: &Context<'_, 'cfg>, unit: &Unit, job: Job) -> CargoResult<()> {
@@ -284,21 +291,27 @@ impl<'cfg> JobQueue<'cfg> {
             .filter(|dep| {
                 // Binaries aren't actually needed to *compile* tests, just to run
                 // them, so we don't include this dependency edge in the job graph.
-                !dep.unit.target.is_test() && !dep.unit.target.is_bin()
+                // Artifact dependencies are the exception, as their files
+                // must exist before the dependent unit is compiled.
+                dep.artifact.is_some() || (!dep.unit.target.is_test() && !dep.unit.target.is_bin())
             })
-            .map(|dep| {
+            .fold(HashMap::new(), |mut queue_deps, dep| {
                 // Handle the case here where our `unit -> dep` dependency may
                 // only require the metadata, not the full compilation to
                 // finish. Use the tables in `cx` to figure out what kind
                 // of artifact is associated with this dependency.
-                let artifact = if cx.only_requires_rmeta(unit, &dep.unit) {
+                let artifact = if dep.artifact.is_none() && cx.only_requires_rmeta(unit, &dep.unit)
+                {
                     Artifact::Metadata
                 } else {
                     Artifact::All
                 };
-                (dep.unit.clone(), artifact)
-            })
-            .collect::<HashMap<_, _>>();
+                let entry = queue_deps.entry(dep.unit.clone()).or_insert(artifact);
+                if artifact == Artifact::All {
+                    *entry = Artifact::All;
+                }
+                queue_deps
+            });
 
         // This is somewhat tricky, but we may need to synthesize some
         // dependencies for this target if it requires full upstream
@@ -375,6 +388,8 @@ impl<'cfg> JobQueue<'cfg> {
             pending_queue: Vec::new(),
             print: DiagnosticPrinter::new(cx.bcx.config),
             finished: 0,
+            keep_going: cx.bcx.build_config.keep_going,
+            failed: Vec::new(),
         };
 
         // Create a helper thread for acquiring jobserver tokens
@@ -544,6 +559,7 @@ impl<'cfg> DrainState<'cfg> {
                     Err(e) => {
                         let msg = "The following warnings were emitted during compilation:";
                         self.emit_warnings(Some(msg), &unit, cx)?;
+                        self.failed.push(unit);
                         return Err(e);
                     }
                 }
@@ -643,13 +659,14 @@ impl<'cfg> DrainState<'cfg> {
         //
         // After a job has finished we update our internal state if it was
         // successful and otherwise wait for pending work to finish if it failed
-        // and then immediately return.
+        // and then immediately return. With `--keep-going` we instead continue
+        // to schedule everything that doesn't depend on a failed unit.
         let mut error = None;
         // CAUTION! Do not use `?` or break out of the loop early. Every error
         // must be handled in such a way that the loop is still allowed to
         // drain event messages.
         loop {
-            if error.is_none() {
+            if error.is_none() || self.keep_going {
                 if let Err(e) = self.spawn_work_if_possible(cx, jobserver_helper, scope) {
                     self.handle_error(&mut cx.bcx.config.shell(), &mut error, e);
                 }
@@ -678,6 +695,10 @@ impl<'cfg> DrainState<'cfg> {
         }
         self.progress.clear();
 
+        if self.keep_going && !self.failed.is_empty() {
+            self.print_keep_going_summary(&mut cx.bcx.config.shell());
+        }
+
         let profile_name = cx.bcx.build_config.requested_profile;
         // NOTE: this may be a bit inaccurate, since this may not display the
         // profile for what was actually built. Profile overrides can change
@@ -692,7 +713,10 @@ impl<'cfg> DrainState<'cfg> {
         } else {
             "optimized"
         });
-        if profile.debuginfo.unwrap_or(0) != 0 {
+        if profile
+            .debuginfo
+            .map_or(false, |debuginfo| debuginfo.is_turned_on())
+        {
             opt_type += " + debuginfo";
         }
 
@@ -742,7 +766,13 @@ impl<'cfg> DrainState<'cfg> {
         err_state: &mut Option<anyhow::Error>,
         new_err: anyhow::Error,
     ) {
-        if err_state.is_some() {
+        if self.keep_going {
+            // Every error is shown, since the build carries on regardless.
+            crate::display_error(&new_err, shell);
+            if err_state.is_none() {
+                *err_state = Some(anyhow::format_err!("build failed"));
+            }
+        } else if err_state.is_some() {
             // Already encountered one error.
             log::warn!("{:?}", new_err);
         } else {
@@ -756,6 +786,34 @@ impl<'cfg> DrainState<'cfg> {
         }
     }
 
+    /// Lists the units that failed and the units that were never started
+    /// because something they depend on failed.
+    fn print_keep_going_summary(&self, shell: &mut Shell) {
+        let mut failed = self
+            .failed
+            .iter()
+            .map(|u| self.name_for_progress(u))
+            .collect::<Vec<_>>();
+        failed.sort();
+        failed.dedup();
+        let mut skipped = self
+            .queue
+            .remaining()
+            .chain(self.pending_queue.iter().map(|(u, _)| u))
+            .map(|u| self.name_for_progress(u))
+            .collect::<Vec<_>>();
+        skipped.sort();
+        skipped.dedup();
+        // It doesn't really matter if these fail.
+        drop(shell.note(format!("failed to build: {}", failed.join(", "))));
+        if !skipped.is_empty() {
+            drop(shell.note(format!(
+                "skipped because a dependency failed to build: {}",
+                skipped.join(", ")
+            )));
+        }
+    }
+
     // This also records CPU usage and marks concurrency; we roughly want to do
     // this as often as we spin on the events receiver (at least every 500ms or
     // so).
@@ -899,6 +957,25 @@ impl<'cfg> DrainState<'cfg> {
         };
         let bcx = &mut cx.bcx;
         if let Some(output) = outputs.get(unit.pkg.package_id(), metadata) {
+            // Only report these for the build script's own unit, the other
+            // units of the package display the same output if they fail.
+            if bcx.build_config.emit_json() && unit.mode.is_run_custom_build() {
+                let messages = output
+                    .warnings
+                    .iter()
+                    .map(|m| ("warning", m))
+                    .chain(output.errors.iter().map(|m| ("error", m)));
+                for (level, message) in messages {
+                    let msg = machine_message::BuildScriptMessage {
+                        package_id: unit.pkg.package_id(),
+                        level,
+                        message,
+                    }
+                    .to_json_string();
+                    writeln!(bcx.config.shell().out(), "{}", msg)?;
+                }
+            }
+
             if !output.warnings.is_empty() {
                 if let Some(msg) = msg {
                     writeln!(bcx.config.shell().err(), "{}\n", msg)?;
@@ -913,6 +990,10 @@ impl<'cfg> DrainState<'cfg> {
                     writeln!(bcx.config.shell().err())?;
                 }
             }
+
+            for error in output.errors.iter() {
+                bcx.config.shell().error(error)?;
+            }
         }
 
         Ok(())
@@ -925,7 +1006,9 @@ impl<'cfg> DrainState<'cfg> {
         artifact: Artifact,
         cx: &mut Context<'_, '_>,
     ) -> CargoResult<()> {
-        if unit.mode.is_run_custom_build() && unit.show_warnings(cx.bcx.config) {
+        if unit.mode.is_run_custom_build()
+            && (unit.show_warnings(cx.bcx.config) || cx.bcx.build_config.dependency_script_warnings)
+        {
             self.emit_warnings(None, unit, cx)?;
         }
         let unlocked = self.queue.finish(unit, &artifact);
@@ -135,6 +135,14 @@ fn calculate(
             (_, false) | (Lto::OnlyObject, true) | (Lto::ObjectAndBitcode, true) => parent_lto,
         }
     };
+    // Some crates of the standard library, like `compiler_builtins`, never
+    // take part in LTO, so like the one shipped with rustc it always needs
+    // object code in addition to any bitcode.
+    let lto = if unit.is_std && lto == Lto::OnlyBitcode {
+        Lto::ObjectAndBitcode
+    } else {
+        lto
+    };
 
     // Merge the computed LTO. If this unit appears multiple times in the
     // graph, the merge may expand the requirements.
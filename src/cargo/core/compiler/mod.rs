@@ -1,3 +1,4 @@
+mod artifact;
 mod build_config;
 mod build_context;
 mod build_plan;
@@ -38,7 +39,7 @@ pub use self::compilation::{Compilation, Doctest};
 pub use self::compile_kind::{CompileKind, CompileTarget};
 pub use self::context::{Context, Metadata};
 pub use self::crate_type::CrateType;
-pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts};
+pub use self::custom_build::{BuildOutput, BuildScriptOutputs, BuildScripts, LinkType};
 pub use self::job::Freshness;
 use self::job::{Job, Work};
 use self::job_queue::{JobQueue, JobState};
@@ -196,7 +197,6 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     // If we are a binary and the package also contains a library, then we
     // don't pass the `-l` flags.
     let pass_l_flag = unit.target.is_lib() || !unit.pkg.targets().iter().any(|t| t.is_lib());
-    let pass_cdylib_link_args = unit.target.is_cdylib();
 
     let dep_info_name = match cx.files().metadata(unit) {
         Some(metadata) => format!("{}-{}.d", unit.target.crate_name(), metadata),
@@ -244,7 +244,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                     &script_outputs,
                     &build_scripts,
                     pass_l_flag,
-                    pass_cdylib_link_args,
+                    &target,
                     current_id,
                 )?;
                 add_plugin_deps(&mut rustc, &script_outputs, &build_scripts, &root_output)?;
@@ -326,7 +326,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
         build_script_outputs: &BuildScriptOutputs,
         build_scripts: &BuildScripts,
         pass_l_flag: bool,
-        pass_cdylib_link_args: bool,
+        target: &Target,
         current_id: PackageId,
     ) -> CargoResult<()> {
         for key in build_scripts.to_link.iter() {
@@ -348,10 +348,9 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                         rustc.arg("-l").arg(name);
                     }
                 }
-                if pass_cdylib_link_args {
-                    for arg in output.linker_args.iter() {
-                        let link_arg = format!("link-arg={}", arg);
-                        rustc.arg("-C").arg(link_arg);
+                for (lt, arg) in &output.linker_args {
+                    if lt.applies_to(target) {
+                        rustc.arg("-C").arg(format!("link-arg={}", arg));
                     }
                 }
             }
@@ -385,9 +384,9 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
 fn link_targets(cx: &mut Context<'_, '_>, unit: &Unit, fresh: bool) -> CargoResult<Work> {
     let bcx = cx.bcx;
     let outputs = cx.outputs(unit)?;
-    let export_dir = cx.files().export_dir();
+    let export_dir = cx.files().export_dir(unit);
     let package_id = unit.pkg.package_id();
-    let profile = unit.profile;
+    let profile = unit.profile.clone();
     let unit_mode = unit.mode;
     let features = unit.features.iter().map(|s| s.to_string()).collect();
     let json_messages = bcx.build_config.emit_json();
@@ -746,12 +745,15 @@ fn build_base_args(
         ref opt_level,
         codegen_units,
         debuginfo,
+        split_debuginfo,
         debug_assertions,
         overflow_checks,
         rpath,
         ref panic,
         incremental,
         strip,
+        codegen_backend,
+        ref rustflags,
         ..
     } = unit.profile;
     let test = unit.mode.is_any_test();
@@ -824,6 +826,12 @@ fn build_base_args(
 
     if let Some(debuginfo) = debuginfo {
         cmd.arg("-C").arg(format!("debuginfo={}", debuginfo));
+        // Splitting only makes sense when there is some debug information
+        // to split out.
+        if let Some(split_debuginfo) = split_debuginfo {
+            cmd.arg("-C")
+                .arg(format!("split-debuginfo={}", split_debuginfo));
+        }
     }
 
     if let Some(args) = cx.bcx.extra_args_for(unit) {
@@ -916,6 +924,12 @@ fn build_base_args(
         cmd.arg("-Z").arg(format!("strip={}", strip));
     }
 
+    if let Some(backend) = codegen_backend {
+        cmd.arg("-Z").arg(format!("codegen-backend={}", backend));
+    }
+
+    cmd.args(rustflags);
+
     if unit.is_std {
         // -Zforce-unstable-if-unmarked prevents the accidental use of
         // unstable crates within the sysroot (such as "extern crate libc" or
@@ -976,10 +990,9 @@ fn build_deps_args(
         .iter()
         .any(|dep| !dep.unit.mode.is_doc() && dep.unit.target.is_linkable())
     {
-        if let Some(dep) = deps
-            .iter()
-            .find(|dep| !dep.unit.mode.is_doc() && dep.unit.target.is_lib())
-        {
+        if let Some(dep) = deps.iter().find(|dep| {
+            !dep.unit.mode.is_doc() && dep.unit.target.is_lib() && dep.artifact.is_none()
+        }) {
             bcx.config.shell().warn(format!(
                 "The package `{}` \
                  provides no linkable target. The compiler might raise an error while compiling \
@@ -1000,6 +1013,10 @@ fn build_deps_args(
         }
     }
 
+    for (var, path) in artifact::get_env(cx, deps)? {
+        cmd.env(&var, path);
+    }
+
     for arg in extern_args(cx, unit, &mut unstable_opts)? {
         cmd.arg(arg);
     }
@@ -1077,7 +1094,7 @@ pub fn extern_args(
         };
 
     for dep in deps {
-        if dep.unit.target.is_linkable() && !dep.unit.mode.is_doc() {
+        if dep.unit.target.is_linkable() && !dep.unit.mode.is_doc() && dep.artifact.is_none() {
             link_to(dep, dep.extern_crate_name, dep.noprelude)?;
         }
     }
@@ -2,12 +2,14 @@
 
 use crate::core::compiler::UnitInterner;
 use crate::core::compiler::{CompileKind, CompileMode, RustcTargetData, Unit};
-use crate::core::profiles::{Profiles, UnitFor};
+use crate::core::profiles::{PanicStrategy, Profiles, UnitFor};
 use crate::core::resolver::features::{FeaturesFor, ResolvedFeatures};
 use crate::core::resolver::{HasDevUnits, ResolveOpts};
-use crate::core::{Dependency, PackageId, PackageSet, Resolve, SourceId, Workspace};
+use crate::core::{Dependency, PackageSet, Resolve, SourceId, Workspace};
 use crate::ops::{self, Packages};
+use crate::sources::SourceConfigMap;
 use crate::util::errors::CargoResult;
+use crate::util::Config;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::PathBuf;
@@ -17,7 +19,12 @@ pub fn parse_unstable_flag(value: Option<&str>) -> Vec<String> {
     // This is a temporary hack until there is a more principled way to
     // declare dependencies in Cargo.toml.
     let value = value.unwrap_or("std");
-    let mut crates: HashSet<&str> = value.split(',').collect();
+    expand_crates(value.split(','))
+}
+
+/// Adds the crates required by the given standard library crates.
+fn expand_crates<'a>(crates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut crates: HashSet<&str> = crates.into_iter().collect();
     if crates.contains("std") {
         crates.insert("core");
         crates.insert("alloc");
@@ -27,7 +34,70 @@ pub fn parse_unstable_flag(value: Option<&str>) -> Vec<String> {
     } else if crates.contains("core") {
         crates.insert("compiler_builtins");
     }
-    crates.into_iter().map(|s| s.to_string()).collect()
+    let mut crates: Vec<String> = crates.into_iter().map(|s| s.to_string()).collect();
+    crates.sort();
+    crates
+}
+
+/// Returns the standard library crates to build for each of the given kinds.
+///
+/// `-Zbuild-std` applies to every kind. Otherwise the crates come from the
+/// `target.<triple>.build-std` config, falling back to `build.build-std`.
+/// Kinds without any of these use the sysroot's standard library.
+pub fn std_crates(
+    config: &Config,
+    target_data: &RustcTargetData,
+    kinds: &[CompileKind],
+) -> CargoResult<HashMap<CompileKind, Vec<String>>> {
+    let build_config = config.build_config()?;
+    let mut ret = HashMap::new();
+    for &kind in kinds {
+        let (crates, origin) = if let Some(crates) = &config.cli_unstable().build_std {
+            (crates.clone(), "-Zbuild-std")
+        } else if let Some(list) = target_data
+            .target_config(kind)
+            .build_std
+            .as_ref()
+            .filter(|_| !kind.is_host())
+        {
+            let crates = list.val.as_slice().iter().map(String::as_str);
+            (expand_crates(crates), "target.<triple>.build-std")
+        } else if let Some(list) = &build_config.build_std {
+            let crates = list.as_slice().iter().map(String::as_str);
+            (expand_crates(crates), "build.build-std")
+        } else {
+            continue;
+        };
+        if kind.is_host() {
+            // TODO: This should eventually be fixed. Unfortunately it is not
+            // easy to get the host triple in BuildConfig. Consider changing
+            // requested_target to an enum, or some other approach.
+            anyhow::bail!("{} requires --target", origin);
+        }
+        ret.insert(kind, crates);
+    }
+    Ok(ret)
+}
+
+/// Returns the features to enable for the standard library.
+///
+/// `-Zbuild-std-features` takes precedence over the `build-std-features` key
+/// of the profile, which takes precedence over `build.build-std-features`.
+pub fn std_features(config: &Config, profiles: &Profiles) -> CargoResult<Vec<String>> {
+    if let Some(list) = &config.cli_unstable().build_std_features {
+        return Ok(list.clone());
+    }
+    if let Some(list) = profiles.build_std_features() {
+        return Ok(list);
+    }
+    if let Some(list) = &config.build_config()?.build_std_features {
+        return Ok(list.as_slice().to_vec());
+    }
+    Ok(vec![
+        "panic-unwind".to_string(),
+        "backtrace".to_string(),
+        "default".to_string(),
+    ])
 }
 
 /// Resolve the standard library dependencies.
@@ -36,6 +106,7 @@ pub fn resolve_std<'cfg>(
     target_data: &RustcTargetData,
     requested_targets: &[CompileKind],
     crates: &[String],
+    features: &[String],
 ) -> CargoResult<(PackageSet<'cfg>, Resolve, ResolvedFeatures)> {
     let src_path = detect_sysroot_src_path(target_data)?;
     let to_patch = [
@@ -86,30 +157,45 @@ pub fn resolve_std<'cfg>(
     // now. Perhaps in the future features will be decoupled from the resolver
     // and it will be easier to control feature selection.
     let current_manifest = src_path.join("library/test/Cargo.toml");
-    // TODO: Consider doing something to enforce --locked? Or to prevent the
-    // lock file from being written, such as setting ephemeral.
     let mut std_ws = Workspace::new_virtual(src_path, current_manifest, virtual_manifest, config)?;
     // Don't require optional dependencies in this workspace, aka std's own
     // `[dev-dependencies]`. No need for us to generate a `Resolve` which has
-    // those included because we'll never use them anyway.
+    // those included because we'll never use them anyway. This also means
+    // that the lock file shipped with `rust-src` is only read, never written.
     std_ws.set_require_optional_deps(false);
+    let lock = ops::load_pkg_lockfile(&std_ws)?;
+    // Resolve against the lock file offline: the registries it uses are
+    // marked as already updated, so only the local copy of their index is
+    // read. They are unmarked again after resolving the standard library.
+    let mut offline_ids = Vec::new();
+    if let Some(lock) = &lock {
+        let source_config = SourceConfigMap::new(config)?;
+        let registries: HashSet<SourceId> = lock
+            .iter()
+            .map(|id| id.source_id())
+            .filter(|id| id.is_registry())
+            .collect();
+        let mut ids = HashSet::new();
+        for id in registries {
+            ids.insert(id);
+            ids.insert(
+                source_config
+                    .load(id, &HashSet::new())?
+                    .replaced_source_id(),
+            );
+        }
+        let mut updated = config.updated_sources();
+        offline_ids = ids.into_iter().filter(|&id| updated.insert(id)).collect();
+    }
     // `test` is not in the default set because it is optional, but it needs
     // to be part of the resolve in case we do need it.
     let mut spec_pkgs = Vec::from(crates);
     spec_pkgs.push("test".to_string());
     let spec = Packages::Packages(spec_pkgs);
     let specs = spec.to_package_id_specs(&std_ws)?;
-    let features = match &config.cli_unstable().build_std_features {
-        Some(list) => list.clone(),
-        None => vec![
-            "panic-unwind".to_string(),
-            "backtrace".to_string(),
-            "default".to_string(),
-        ],
-    };
     // dev_deps setting shouldn't really matter here.
     let opts = ResolveOpts::new(
-        /*dev_deps*/ false, &features, /*all_features*/ false,
+        /*dev_deps*/ false, features, /*all_features*/ false,
         /*uses_default_features*/ false,
     );
     let resolve = ops::resolve_ws_with_opts(
@@ -120,7 +206,28 @@ pub fn resolve_std<'cfg>(
         &specs,
         HasDevUnits::No,
         crate::core::resolver::features::ForceAllTargets::No,
-    )?;
+    );
+    {
+        let mut updated = config.updated_sources();
+        for id in &offline_ids {
+            updated.remove(id);
+        }
+    }
+    let resolve = resolve?;
+    if let Some(lock) = lock {
+        if let Some(id) = resolve
+            .targeted_resolve
+            .iter()
+            .find(|id| !lock.contains(id))
+        {
+            anyhow::bail!(
+                "the lock file {} of the standard library needs to be updated \
+                 to build `{}`",
+                std_ws.root().join("Cargo.lock").display(),
+                id
+            );
+        }
+    }
     Ok((
         resolve.pkg_set,
         resolve.targeted_resolve,
@@ -130,21 +237,20 @@ pub fn resolve_std<'cfg>(
 
 /// Generate a list of root `Unit`s for the standard library.
 ///
-/// The given slice of crate names is the root set.
+/// The given map of crate names for each kind is the root set.
 pub fn generate_std_roots(
-    crates: &[String],
+    crates: &HashMap<CompileKind, Vec<String>>,
     std_resolve: &Resolve,
     std_features: &ResolvedFeatures,
-    kinds: &[CompileKind],
     package_set: &PackageSet<'_>,
     interner: &UnitInterner,
     profiles: &Profiles,
 ) -> CargoResult<HashMap<CompileKind, Vec<Unit>>> {
     // Generate the root Units for the standard library.
-    let std_ids = crates
-        .iter()
-        .map(|crate_name| std_resolve.query(crate_name))
-        .collect::<CargoResult<Vec<PackageId>>>()?;
+    let mut std_ids = HashSet::new();
+    for crate_name in crates.values().flatten() {
+        std_ids.insert(std_resolve.query(crate_name)?);
+    }
     // Convert PackageId to Package.
     let std_pkgs = package_set.get_many(std_ids)?;
     // Generate a map of Units for each kind requested.
@@ -160,21 +266,31 @@ pub fn generate_std_roots(
         // in time is minimal, and the difference in caching is
         // significant.
         let mode = CompileMode::Build;
-        let profile = profiles.get_profile(
+        let mut profile = profiles.get_profile(
             pkg.package_id(),
             /*is_member*/ false,
             /*is_local*/ false,
             unit_for,
             mode,
         );
+        // The standard library uses the `panic` strategy of the profile, so
+        // that `panic=abort` doesn't link in any unwinding code. The
+        // `panic_abort` runtime itself must always be built with
+        // `panic=abort`.
+        if pkg.name() == "panic_abort" {
+            profile.panic = PanicStrategy::Abort;
+        }
         let features = std_features.activated_features(pkg.package_id(), FeaturesFor::NormalOrDev);
 
-        for kind in kinds {
+        for (kind, kind_crates) in crates {
+            if !kind_crates.iter().any(|c| c == pkg.name().as_str()) {
+                continue;
+            }
             let list = ret.entry(*kind).or_insert_with(Vec::new);
             list.push(interner.intern(
                 pkg,
                 lib,
-                profile,
+                profile.clone(),
                 *kind,
                 mode,
                 features.clone(),
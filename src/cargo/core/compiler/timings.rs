@@ -11,8 +11,15 @@ use crate::util::machine_message::{self, Message};
 use crate::util::{paths, CargoResult, CargoResultExt, Config};
 use std::collections::HashMap;
 use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant, SystemTime};
 
+/// A unit is reported as a regression by `-Ztimings-compare` if it took at
+/// least this much longer (as a fraction of the previous duration)...
+const REGRESSION_THRESHOLD: f64 = 0.1;
+/// ...and at least this many more seconds than in the previous report.
+const REGRESSION_MIN_SECS: f64 = 0.2;
+
 pub struct Timings<'cfg> {
     config: &'cfg Config,
     /// Whether or not timings should be captured.
@@ -23,6 +30,10 @@ pub struct Timings<'cfg> {
     report_info: bool,
     /// If true, emits JSON information with timing information.
     report_json: bool,
+    /// If true, saves a JSON report with the same data as the HTML report.
+    report_json_file: bool,
+    /// A previous JSON report to compare the unit times against, and its path.
+    compare: Option<(PathBuf, PreviousReport)>,
     /// When Cargo started.
     start: Instant,
     /// A rendered string of when compilation started.
@@ -72,6 +83,37 @@ struct UnitTime {
     unlocked_rmeta_units: Vec<Unit>,
 }
 
+/// Per-unit data, used by both the HTML and the JSON report.
+#[derive(serde::Serialize)]
+struct UnitData {
+    i: usize,
+    name: String,
+    version: String,
+    mode: String,
+    target: String,
+    start: f64,
+    duration: f64,
+    rmeta_time: Option<f64>,
+    unlocked_units: Vec<usize>,
+    unlocked_rmeta_units: Vec<usize>,
+    features: Vec<String>,
+}
+
+/// The parts of a previous JSON report used by `-Ztimings-compare`.
+#[derive(serde::Deserialize)]
+struct PreviousReport {
+    duration: f64,
+    units: Vec<PreviousUnit>,
+}
+
+#[derive(serde::Deserialize)]
+struct PreviousUnit {
+    name: String,
+    version: String,
+    target: String,
+    duration: f64,
+}
+
 /// Periodic concurrency tracking information.
 #[derive(serde::Serialize)]
 struct Concurrency {
@@ -91,7 +133,7 @@ struct Concurrency {
 }
 
 impl<'cfg> Timings<'cfg> {
-    pub fn new(bcx: &BuildContext<'_, 'cfg>, root_units: &[Unit]) -> Timings<'cfg> {
+    pub fn new(bcx: &BuildContext<'_, 'cfg>, root_units: &[Unit]) -> CargoResult<Timings<'cfg>> {
         let has_report = |what| {
             bcx.config
                 .cli_unstable()
@@ -102,7 +144,23 @@ impl<'cfg> Timings<'cfg> {
         let report_html = has_report("html");
         let report_info = has_report("info");
         let report_json = has_report("json");
-        let enabled = report_html | report_info | report_json;
+        let report_json_file = has_report("json-report");
+        // Read the previous report before building, so that a bad path
+        // doesn't fail the build only after everything has been compiled.
+        let compare = match &bcx.config.cli_unstable().timings_compare {
+            Some(path) => {
+                let path = bcx.config.cwd().join(path);
+                let previous: PreviousReport = paths::read(&path)
+                    .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+                    .chain_err(|| {
+                        format!("failed to read previous timing report `{}`", path.display())
+                    })?;
+                Some((path, previous))
+            }
+            None => None,
+        };
+        let enabled =
+            report_html | report_info | report_json | report_json_file | compare.is_some();
 
         let mut root_map: HashMap<PackageId, Vec<String>> = HashMap::new();
         for unit in root_units {
@@ -133,12 +191,14 @@ impl<'cfg> Timings<'cfg> {
             None
         };
 
-        Timings {
+        Ok(Timings {
             config: bcx.config,
             enabled,
             report_html,
             report_info,
             report_json,
+            report_json_file,
+            compare,
             start: bcx.config.creation_time(),
             start_str,
             root_targets,
@@ -151,7 +211,7 @@ impl<'cfg> Timings<'cfg> {
             last_cpu_state,
             last_cpu_recording: Instant::now(),
             cpu_usage: Vec::new(),
-        }
+        })
     }
 
     /// Mark that a unit has started running.
@@ -325,6 +385,19 @@ impl<'cfg> Timings<'cfg> {
             self.report_html(bcx, error)
                 .chain_err(|| "failed to save timing report")?;
         }
+        if self.report_json_file {
+            self.report_json_file(bcx, error)
+                .chain_err(|| "failed to save timing report")?;
+        }
+        if let Some((path, previous)) = &self.compare {
+            if let Err(e) = self.report_comparison(path, previous) {
+                self.config.shell().warn(format!(
+                    "failed to compare with previous timing report `{}`: {}",
+                    path.display(),
+                    e
+                ))?;
+            }
+        }
         Ok(())
     }
 
@@ -379,6 +452,141 @@ impl<'cfg> Timings<'cfg> {
         Ok(())
     }
 
+    /// Save JSON report to disk.
+    fn report_json_file(
+        &self,
+        bcx: &BuildContext<'_, '_>,
+        error: &Option<anyhow::Error>,
+    ) -> CargoResult<()> {
+        #[derive(serde::Serialize)]
+        struct Root<'a> {
+            package: &'a str,
+            targets: &'a [String],
+        }
+        #[derive(serde::Serialize)]
+        struct Report<'a> {
+            version: u32,
+            start: &'a str,
+            duration: f64,
+            profile: &'a str,
+            roots: Vec<Root<'a>>,
+            fresh_units: u32,
+            dirty_units: u32,
+            jobs: u32,
+            ncpu: usize,
+            rustc: &'a str,
+            host: &'a str,
+            targets: Vec<&'a str>,
+            error: Option<String>,
+            units: Vec<UnitData>,
+            concurrency: &'a [Concurrency],
+            cpu_usage: &'a [(f64, f64)],
+            critical_path: Vec<usize>,
+        }
+        let units = self.unit_data();
+        let critical_path = critical_path(&units);
+        let report = Report {
+            version: 1,
+            start: &self.start_str,
+            duration: d_as_f64(self.start.elapsed()),
+            profile: &self.profile,
+            roots: self
+                .root_targets
+                .iter()
+                .map(|(package, targets)| Root { package, targets })
+                .collect(),
+            fresh_units: self.total_fresh,
+            dirty_units: self.total_dirty,
+            jobs: bcx.build_config.jobs,
+            ncpu: num_cpus::get(),
+            rustc: bcx
+                .rustc()
+                .verbose_version
+                .lines()
+                .next()
+                .expect("rustc version"),
+            host: &bcx.rustc().host,
+            targets: bcx
+                .build_config
+                .requested_kinds
+                .iter()
+                .map(|kind| bcx.target_data.short_name(kind))
+                .collect(),
+            error: error.as_ref().map(|e| e.to_string()),
+            units,
+            concurrency: &self.concurrency,
+            cpu_usage: &self.cpu_usage,
+            critical_path,
+        };
+        let timestamp = self.start_str.replace(&['-', ':'][..], "");
+        let filename = format!("cargo-timing-{}.json", timestamp);
+        paths::write(&filename, serde_json::to_string_pretty(&report)?)?;
+        let msg = format!(
+            "report saved to {}",
+            std::env::current_dir()
+                .unwrap_or_default()
+                .join(&filename)
+                .display()
+        );
+        paths::link_or_copy(&filename, "cargo-timing.json")?;
+        self.config
+            .shell()
+            .status_with_color("Timing", msg, termcolor::Color::Cyan)?;
+        Ok(())
+    }
+
+    /// Compares the unit times against a JSON report from a previous build,
+    /// and displays the units which got slower.
+    fn report_comparison(&self, path: &Path, previous: &PreviousReport) -> CargoResult<()> {
+        let previous_times: HashMap<String, f64> = previous
+            .units
+            .iter()
+            .map(|u| (format!("{} v{}{}", u.name, u.version, u.target), u.duration))
+            .collect();
+        let mut regressions: Vec<(String, f64, f64)> = self
+            .unit_times
+            .iter()
+            .filter_map(|ut| {
+                let name = format!("{}{}", ut.name_ver(), ut.target);
+                let before = *previous_times.get(&name)?;
+                let regressed = ut.duration - before >= REGRESSION_MIN_SECS
+                    && ut.duration > before * (1.0 + REGRESSION_THRESHOLD);
+                if regressed {
+                    Some((name, before, ut.duration))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        regressions.sort_by(|a, b| (b.2 - b.1).partial_cmp(&(a.2 - a.1)).unwrap());
+
+        let mut shell = self.config.shell();
+        for (name, before, after) in &regressions {
+            let pct = if *before > 0.0 {
+                format!(" (+{:.0}%)", (after - before) / before * 100.0)
+            } else {
+                String::new()
+            };
+            shell.status_with_color(
+                "Regressed",
+                format!("{} {:.1}s -> {:.1}s{}", name, before, after, pct),
+                termcolor::Color::Yellow,
+            )?;
+        }
+        shell.status_with_color(
+            "Timing",
+            format!(
+                "total {:.1}s, previously {:.1}s, {} unit(s) regressed compared to {}",
+                d_as_f64(self.start.elapsed()),
+                previous.duration,
+                regressions.len(),
+                path.display()
+            ),
+            termcolor::Color::Cyan,
+        )?;
+        Ok(())
+    }
+
     /// Render the summary table.
     fn write_summary_table(
         &self,
@@ -472,7 +680,8 @@ impl<'cfg> Timings<'cfg> {
         Ok(())
     }
 
-    fn write_js_data(&self, f: &mut impl Write) -> CargoResult<()> {
+    /// Returns the data for each unit, in the order they were started.
+    fn unit_data(&self) -> Vec<UnitData> {
         // Create a map to link indices of unlocked units.
         let unit_map: HashMap<Unit, usize> = self
             .unit_times
@@ -480,22 +689,8 @@ impl<'cfg> Timings<'cfg> {
             .enumerate()
             .map(|(i, ut)| (ut.unit.clone(), i))
             .collect();
-        #[derive(serde::Serialize)]
-        struct UnitData {
-            i: usize,
-            name: String,
-            version: String,
-            mode: String,
-            target: String,
-            start: f64,
-            duration: f64,
-            rmeta_time: Option<f64>,
-            unlocked_units: Vec<usize>,
-            unlocked_rmeta_units: Vec<usize>,
-        }
         let round = |x: f64| (x * 100.0).round() / 100.0;
-        let unit_data: Vec<UnitData> = self
-            .unit_times
+        self.unit_times
             .iter()
             .enumerate()
             .map(|(i, ut)| {
@@ -530,9 +725,14 @@ impl<'cfg> Timings<'cfg> {
                     rmeta_time: ut.rmeta_time.map(|t| round(t)),
                     unlocked_units,
                     unlocked_rmeta_units,
+                    features: ut.unit.features.iter().map(|f| f.to_string()).collect(),
                 }
             })
-            .collect();
+            .collect()
+    }
+
+    fn write_js_data(&self, f: &mut impl Write) -> CargoResult<()> {
+        let unit_data = self.unit_data();
         writeln!(
             f,
             "const UNIT_DATA = {};",
@@ -616,6 +816,33 @@ impl UnitTime {
     }
 }
 
+/// Returns the indices of the units on the critical path, in build order.
+///
+/// A unit is unlocked by the last of its dependencies to finish (or to
+/// produce its `.rmeta`), so the path is found by starting at the unit that
+/// finished last and walking back through whatever unlocked each unit.
+fn critical_path(unit_data: &[UnitData]) -> Vec<usize> {
+    let mut unlocked_by = HashMap::new();
+    for ud in unit_data {
+        for i in ud.unlocked_units.iter().chain(&ud.unlocked_rmeta_units) {
+            unlocked_by.insert(*i, ud.i);
+        }
+    }
+    let last = unit_data.iter().max_by(|a, b| {
+        (a.start + a.duration)
+            .partial_cmp(&(b.start + b.duration))
+            .unwrap()
+    });
+    let mut path = Vec::new();
+    let mut next = last.map(|ud| ud.i);
+    while let Some(i) = next {
+        path.push(i);
+        next = unlocked_by.get(&i).copied();
+    }
+    path.reverse();
+    path
+}
+
 // Replace with as_secs_f64 when 1.38 hits stable.
 fn d_as_f64(d: Duration) -> f64 {
     (d.as_secs() as f64) + f64::from(d.subsec_nanos()) / 1_000_000_000.0
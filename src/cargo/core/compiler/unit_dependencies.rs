@@ -18,11 +18,11 @@
 use crate::core::compiler::unit_graph::{UnitDep, UnitGraph};
 use crate::core::compiler::UnitInterner;
 use crate::core::compiler::{CompileKind, CompileMode, RustcTargetData, Unit};
-use crate::core::dependency::DepKind;
+use crate::core::dependency::{ArtifactKind, DepKind};
 use crate::core::profiles::{Profile, Profiles, UnitFor};
 use crate::core::resolver::features::{FeaturesFor, ResolvedFeatures};
 use crate::core::resolver::Resolve;
-use crate::core::{Package, PackageId, PackageSet, Target, Workspace};
+use crate::core::{Dependency, Package, PackageId, PackageSet, Target, Workspace};
 use crate::ops::resolve_all_features;
 use crate::util::interning::InternedString;
 use crate::util::Config;
@@ -130,15 +130,19 @@ fn attach_std_deps(
     std_unit_deps: UnitGraph,
 ) {
     // Attach the standard library as a dependency of every target unit.
+    // Artifacts built for targets that weren't requested use the sysroot's
+    // standard library instead.
     for (unit, deps) in state.unit_dependencies.iter_mut() {
         if !unit.kind.is_host() && !unit.mode.is_run_custom_build() {
-            deps.extend(std_roots[&unit.kind].iter().map(|unit| UnitDep {
+            let roots = std_roots.get(&unit.kind).into_iter().flatten();
+            deps.extend(roots.map(|unit| UnitDep {
                 unit: unit.clone(),
                 unit_for: UnitFor::new_normal(),
                 extern_crate_name: unit.pkg.name(),
                 // TODO: Does this `public` make sense?
                 public: true,
                 noprelude: true,
+                artifact: None,
             }));
         }
     }
@@ -217,9 +221,9 @@ fn compute_deps(
     }
 
     let id = unit.pkg.package_id();
-    let filtered_deps = state.resolve().deps(id).filter(|&(_id, deps)| {
+    let filtered_deps = state.resolve().deps(id).map(|(dep_id, deps)| {
         assert!(!deps.is_empty());
-        deps.iter().any(|dep| {
+        let deps = deps.iter().filter(|dep| {
             // If this target is a build command, then we only want build
             // dependencies, otherwise we want everything *other than* build
             // dependencies.
@@ -257,14 +261,25 @@ fn compute_deps(
             // If we've gotten past all that, then this dependency is
             // actually used!
             true
-        })
+        });
+        (dep_id, deps.collect::<Vec<_>>())
     });
     // Separate line to avoid rustfmt indentation. Must collect due to `state` capture.
-    let filtered_deps: Vec<_> = filtered_deps.collect();
+    let filtered_deps: Vec<_> = filtered_deps
+        .filter(|(_id, deps)| !deps.is_empty())
+        .collect();
 
     let mut ret = Vec::new();
-    for (id, _) in filtered_deps {
+    for (id, deps) in filtered_deps {
         let pkg = state.get(id);
+        ret.extend(artifact_deps(unit, unit_for, state, pkg, &deps)?);
+        // Artifact dependencies don't link the library unless `lib = true`.
+        if !deps
+            .iter()
+            .any(|dep| dep.artifact().map_or(true, |a| a.is_lib()))
+        {
+            continue;
+        }
         let lib = match pkg.targets().iter().find(|t| t.is_lib()) {
             Some(t) => t,
             None => continue,
@@ -408,19 +423,36 @@ fn compute_deps_doc(unit: &Unit, state: &mut State<'_, '_>) -> CargoResult<Vec<U
     let deps = state
         .resolve()
         .deps(unit.pkg.package_id())
-        .filter(|&(_id, deps)| {
-            deps.iter().any(|dep| match dep.kind() {
+        .map(|(id, deps)| {
+            let deps = deps.iter().filter(|dep| match dep.kind() {
                 DepKind::Normal => target_data.dep_platform_activated(dep, unit.kind),
                 _ => false,
-            })
-        });
+            });
+            (id, deps.collect::<Vec<_>>())
+        })
+        .filter(|(_id, deps)| !deps.is_empty())
+        .collect::<Vec<_>>();
 
     // To document a library, we depend on dependencies actually being
     // built. If we're documenting *all* libraries, then we also depend on
     // the documentation of the library being built.
     let mut ret = Vec::new();
-    for (id, _deps) in deps {
+    for (id, deps) in deps {
         let dep = state.get(id);
+        // Artifacts are built so that they can be found with `env!`.
+        ret.extend(artifact_deps(
+            unit,
+            UnitFor::new_normal(),
+            state,
+            dep,
+            &deps,
+        )?);
+        if !deps
+            .iter()
+            .any(|dep| dep.artifact().map_or(true, |a| a.is_lib()))
+        {
+            continue;
+        }
         let lib = match dep.targets().iter().find(|t| t.is_lib()) {
             Some(lib) => lib,
             None => continue,
@@ -466,6 +498,78 @@ fn compute_deps_doc(unit: &Unit, state: &mut State<'_, '_>) -> CargoResult<Vec<U
     Ok(ret)
 }
 
+/// Returns the units building the artifacts requested by the artifact
+/// dependencies in `deps`, all of which are dependencies of `unit` on `pkg`.
+///
+/// Artifacts are built for the target given in the dependency, or for the
+/// same target as `unit` (so for the host if `unit` is a build script).
+fn artifact_deps(
+    unit: &Unit,
+    unit_for: UnitFor,
+    state: &State<'_, '_>,
+    pkg: &Package,
+    deps: &[&Dependency],
+) -> CargoResult<Vec<UnitDep>> {
+    let mut ret = Vec::new();
+    for dep in deps {
+        let artifact = match dep.artifact() {
+            Some(artifact) => artifact,
+            None => continue,
+        };
+        let kind = artifact.target().map_or(unit.kind, CompileKind::Target);
+        if !state.target_data.has_kind(kind) {
+            anyhow::bail!(
+                "dependency `{}` in package `{}` sets `target = \"{}\"`, \
+                 which is only supported for dependencies of workspace members",
+                dep.name_in_toml(),
+                unit.pkg.name(),
+                state.target_data.short_name(&kind)
+            );
+        }
+        for artifact_kind in artifact.kinds() {
+            let targets: Vec<&Target> = pkg
+                .targets()
+                .iter()
+                .filter(|t| match artifact_kind {
+                    ArtifactKind::AllBinaries => t.is_bin(),
+                    ArtifactKind::SelectedBinary(name) => t.is_bin() && t.name() == name.as_str(),
+                    ArtifactKind::Cdylib => t.is_cdylib(),
+                    ArtifactKind::Staticlib => t.is_staticlib(),
+                })
+                .collect();
+            if targets.is_empty() {
+                anyhow::bail!(
+                    "dependency `{}` in package `{}` requires a `{}` artifact, \
+                     but package `{}` has no such target",
+                    dep.name_in_toml(),
+                    unit.pkg.name(),
+                    artifact_kind,
+                    pkg.name()
+                );
+            }
+            for target in targets {
+                let dep_unit_for = unit_for
+                    .with_for_host(target.for_host())
+                    .with_host_features(unit.target.is_custom_build());
+                let mut unit_dep = new_unit_dep(
+                    state,
+                    unit,
+                    pkg,
+                    target,
+                    dep_unit_for,
+                    kind,
+                    CompileMode::Build,
+                )?;
+                unit_dep.artifact = Some(dep.name_in_toml());
+                if !ret.contains(&unit_dep) {
+                    ret.push(unit_dep);
+                }
+            }
+        }
+    }
+    Ok(ret)
+}
+
 fn maybe_lib(
     unit: &Unit,
     state: &mut State<'_, '_>,
@@ -619,6 +723,7 @@ fn new_unit_dep_with_profile(
         extern_crate_name,
         public,
         noprelude: false,
+        artifact: None,
     })
 }
 
@@ -24,6 +24,10 @@ pub struct UnitDep {
     pub public: bool,
     /// If `true`, the dependency should not be added to Rust's prelude.
     pub noprelude: bool,
+    /// For an artifact dependency, the name of the dependency in the parent's
+    /// manifest. The artifact isn't linked, it is passed to the parent through
+    /// environment variables named after the dependency instead.
+    pub artifact: Option<InternedString>,
 }
 
 const VERSION: u32 = 1;
@@ -4,8 +4,10 @@ use semver::ReqParseError;
 use semver::VersionReq;
 use serde::ser;
 use serde::Serialize;
+use std::fmt;
 use std::rc::Rc;
 
+use crate::core::compiler::CompileTarget;
 use crate::core::{PackageId, SourceId, Summary};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::interning::InternedString;
@@ -44,6 +46,98 @@ struct Inner {
     // This dependency should be used only for this platform.
     // `None` means *all platforms*.
     platform: Option<Platform>,
+
+    /// For git dependencies, the directory of the package within the
+    /// repository as given by the `subdirectory` key, separated by `/`.
+    git_subdirectory: Option<String>,
+
+    /// The artifacts to build for this dependency, set with the `artifact`
+    /// key, instead of (or as well as) linking its library.
+    artifact: Option<Artifact>,
+}
+
+/// The artifacts requested by an artifact dependency.
+#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Debug)]
+pub struct Artifact {
+    kinds: Vec<ArtifactKind>,
+    lib: bool,
+    target: Option<CompileTarget>,
+}
+
+/// A kind of artifact in the `artifact` key of a dependency.
+#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Debug)]
+pub enum ArtifactKind {
+    /// `bin`, all the binaries of the package.
+    AllBinaries,
+    /// `bin:NAME`, the binary named `NAME`.
+    SelectedBinary(InternedString),
+    /// `cdylib`
+    Cdylib,
+    /// `staticlib`
+    Staticlib,
+}
+
+impl ArtifactKind {
+    fn parse(kind: &str) -> CargoResult<ArtifactKind> {
+        Ok(match kind {
+            "bin" => ArtifactKind::AllBinaries,
+            "cdylib" => ArtifactKind::Cdylib,
+            "staticlib" => ArtifactKind::Staticlib,
+            _ => match kind.strip_prefix("bin:") {
+                Some(name) if !name.is_empty() => ArtifactKind::SelectedBinary(name.into()),
+                _ => anyhow::bail!(
+                    "`{}` is not a valid artifact kind, \
+                     expected `bin`, `bin:<name>`, `cdylib` or `staticlib`",
+                    kind
+                ),
+            },
+        })
+    }
+}
+
+impl fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactKind::AllBinaries => f.write_str("bin"),
+            ArtifactKind::SelectedBinary(name) => write!(f, "bin:{}", name),
+            ArtifactKind::Cdylib => f.write_str("cdylib"),
+            ArtifactKind::Staticlib => f.write_str("staticlib"),
+        }
+    }
+}
+
+impl Artifact {
+    /// Parses the `artifact`, `lib` and `target` keys of a dependency.
+    pub fn parse(
+        kinds: &[impl AsRef<str>],
+        lib: bool,
+        target: Option<&str>,
+    ) -> CargoResult<Artifact> {
+        if kinds.is_empty() {
+            anyhow::bail!("the `artifact` key must not be empty");
+        }
+        let kinds = kinds
+            .iter()
+            .map(|kind| ArtifactKind::parse(kind.as_ref()))
+            .collect::<CargoResult<Vec<_>>>()?;
+        let target = target.map(CompileTarget::new).transpose()?;
+        Ok(Artifact { kinds, lib, target })
+    }
+
+    pub fn kinds(&self) -> &[ArtifactKind] {
+        &self.kinds
+    }
+
+    /// Whether the library of the dependency is linked as well.
+    pub fn is_lib(&self) -> bool {
+        self.lib
+    }
+
+    /// The target to build the artifacts for, if not the same as the
+    /// dependent's.
+    pub fn target(&self) -> Option<CompileTarget> {
+        self.target
+    }
 }
 
 #[derive(Serialize)]
@@ -217,6 +311,8 @@ impl Dependency {
                 specified_req: false,
                 platform: None,
                 explicit_name_in_toml: None,
+                git_subdirectory: None,
+                artifact: None,
             }),
         }
     }
@@ -312,6 +408,12 @@ impl Dependency {
         self.inner.platform.as_ref()
     }
 
+    /// For git dependencies, the directory within the repository that the
+    /// package is located in, if it was specified.
+    pub fn git_subdirectory(&self) -> Option<&str> {
+        self.inner.git_subdirectory.as_deref()
+    }
+
     /// The renamed name of this dependency, if any.
     ///
     /// If the `package` key is used in `Cargo.toml` then this returns the same
@@ -367,6 +469,22 @@ impl Dependency {
         self
     }
 
+    pub fn set_git_subdirectory(&mut self, dir: String) -> &mut Dependency {
+        Rc::make_mut(&mut self.inner).git_subdirectory = Some(dir);
+        self
+    }
+
+    /// The artifacts to build for this dependency, if it is an artifact
+    /// dependency.
+    pub fn artifact(&self) -> Option<&Artifact> {
+        self.inner.artifact.as_ref()
+    }
+
+    pub fn set_artifact(&mut self, artifact: Artifact) -> &mut Dependency {
+        Rc::make_mut(&mut self.inner).artifact = Some(artifact);
+        self
+    }
+
     pub fn set_explicit_name_in_toml(
         &mut self,
         name: impl Into<InternedString>,
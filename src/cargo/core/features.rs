@@ -217,6 +217,15 @@ features! {
 
         // Allow to specify whether binaries should be stripped.
         [unstable] strip: bool,
+
+        // Depend on the binaries and C libraries built by other packages.
+        [unstable] artifact_dependencies: bool,
+
+        // Allow to pass extra flags to rustc from profiles.
+        [unstable] profile_rustflags: bool,
+
+        // Allow to select the codegen backend from profiles.
+        [unstable] codegen_backend: bool,
     }
 }
 
@@ -352,12 +361,12 @@ pub struct CliUnstable {
     pub build_std: Option<Vec<String>>,
     pub build_std_features: Option<Vec<String>>,
     pub timings: Option<Vec<String>>,
+    pub timings_compare: Option<String>,
     pub doctest_xcompile: bool,
     pub panic_abort_tests: bool,
     pub jobserver_per_rustc: bool,
     pub features: Option<Vec<String>>,
     pub separate_nightlies: bool,
-    pub multitarget: bool,
     pub rustdoc_map: bool,
     pub terminal_width: Option<Option<usize>>,
 }
@@ -457,12 +466,20 @@ impl CliUnstable {
             }
             "build-std-features" => self.build_std_features = Some(parse_features(v)),
             "timings" => self.timings = Some(parse_timings(v)),
+            "timings-compare" => match v {
+                Some(path) => self.timings_compare = Some(path.to_string()),
+                None => bail!("flag -Z{} requires the path to a JSON timing report", k),
+            },
             "doctest-xcompile" => self.doctest_xcompile = parse_empty(k, v)?,
             "panic-abort-tests" => self.panic_abort_tests = parse_empty(k, v)?,
             "jobserver-per-rustc" => self.jobserver_per_rustc = parse_empty(k, v)?,
             "features" => self.features = Some(parse_features(v)),
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
-            "multitarget" => self.multitarget = parse_empty(k, v)?,
+            // Building for multiple targets is stable, the flag is still
+            // accepted so existing invocations keep working.
+            "multitarget" => {
+                parse_empty(k, v)?;
+            }
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
             "terminal-width" => self.terminal_width = Some(parse_usize_opt(v)?),
             _ => bail!("unknown `-Z` flag specified: {}", k),
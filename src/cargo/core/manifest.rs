@@ -459,6 +459,15 @@ impl Manifest {
     pub fn patch(&self) -> &HashMap<Url, Vec<Dependency>> {
         &self.patch
     }
+    /// Adds `[patch]` entries on top of those in `Cargo.toml`.
+    pub fn add_patch(&mut self, patch: &HashMap<Url, Vec<Dependency>>) {
+        for (url, deps) in patch {
+            self.patch
+                .entry(url.clone())
+                .or_default()
+                .extend(deps.iter().cloned());
+        }
+    }
     pub fn links(&self) -> Option<&str> {
         self.links.as_deref()
     }
@@ -791,6 +800,13 @@ impl Target {
         }
     }
 
+    pub fn is_staticlib(&self) -> bool {
+        match self.kind() {
+            TargetKind::Lib(libs) => libs.iter().any(|l| *l == CrateType::Staticlib),
+            _ => false,
+        }
+    }
+
     /// Returns whether this target produces an artifact which can be linked
     /// into a Rust crate.
     ///
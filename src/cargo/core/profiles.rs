@@ -3,7 +3,7 @@ use crate::core::resolver::features::FeaturesFor;
 use crate::core::{Feature, Features, PackageId, PackageIdSpec, Resolve, Shell};
 use crate::util::errors::CargoResultExt;
 use crate::util::interning::InternedString;
-use crate::util::toml::{ProfilePackageSpec, StringOrBool, TomlProfile, TomlProfiles, U32OrBool};
+use crate::util::toml::{ProfilePackageSpec, StringOrBool, TomlProfile, TomlProfiles};
 use crate::util::{closest_msg, config, CargoResult, Config};
 use anyhow::bail;
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -383,17 +383,26 @@ impl Profiles {
     /// `[Finished]` line. It is not entirely accurate, since it doesn't
     /// select for the package that was actually built.
     pub fn base_profile(&self) -> Profile {
-        let profile_name = if !self.named_profiles_enabled {
+        let maker = self.get_profile_maker(self.base_profile_name()).unwrap();
+        maker.get_profile(None, true, UnitFor::new_normal())
+    }
+
+    /// Returns the features of the standard library requested by the
+    /// `build-std-features` key of the base profile, if any.
+    pub fn build_std_features(&self) -> Option<Vec<String>> {
+        let maker = self.get_profile_maker(self.base_profile_name()).unwrap();
+        maker.toml.as_ref()?.build_std_features.clone()
+    }
+
+    fn base_profile_name(&self) -> InternedString {
+        if !self.named_profiles_enabled {
             match self.requested_profile.as_str() {
                 "release" | "bench" => self.requested_profile,
                 _ => InternedString::new("dev"),
             }
         } else {
             self.requested_profile
-        };
-
-        let maker = self.get_profile_maker(profile_name).unwrap();
-        maker.get_profile(None, true, UnitFor::new_normal())
+        }
     }
 
     /// Gets the directory name for a profile, like `debug` or `release`.
@@ -466,7 +475,7 @@ impl ProfileMaker {
         is_member: bool,
         unit_for: UnitFor,
     ) -> Profile {
-        let mut profile = self.default;
+        let mut profile = self.default.clone();
 
         // First apply profile-specific settings, things like
         // `[profile.release]`
@@ -563,9 +572,8 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
         profile.codegen_units = toml.codegen_units;
     }
     match toml.debug {
-        Some(U32OrBool::U32(debug)) => profile.debuginfo = Some(debug),
-        Some(U32OrBool::Bool(true)) => profile.debuginfo = Some(2),
-        Some(U32OrBool::Bool(false)) => profile.debuginfo = None,
+        Some(TomlDebugInfo::Level(debuginfo)) => profile.debuginfo = Some(debuginfo),
+        Some(TomlDebugInfo::False) => profile.debuginfo = None,
         None => {}
     }
     if let Some(debug_assertions) = toml.debug_assertions {
@@ -591,6 +599,15 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(strip) = toml.strip {
         profile.strip = strip;
     }
+    if let Some(split_debuginfo) = &toml.split_debuginfo {
+        profile.split_debuginfo = Some(InternedString::new(split_debuginfo));
+    }
+    if let Some(rustflags) = &toml.rustflags {
+        profile.rustflags = rustflags.clone();
+    }
+    if let Some(codegen_backend) = toml.codegen_backend {
+        profile.codegen_backend = Some(codegen_backend);
+    }
 }
 
 /// The root profile (dev/release).
@@ -606,7 +623,7 @@ pub enum ProfileRoot {
 
 /// Profile settings used to determine which compiler flags to use for a
 /// target.
-#[derive(Clone, Copy, Eq, PartialOrd, Ord, serde::Serialize)]
+#[derive(Clone, Eq, PartialOrd, Ord, serde::Serialize)]
 pub struct Profile {
     pub name: InternedString,
     pub opt_level: InternedString,
@@ -615,13 +632,22 @@ pub struct Profile {
     pub lto: Lto,
     // `None` means use rustc default.
     pub codegen_units: Option<u32>,
-    pub debuginfo: Option<u32>,
+    pub debuginfo: Option<DebugInfo>,
+    // `None` means use rustc default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_debuginfo: Option<InternedString>,
     pub debug_assertions: bool,
     pub overflow_checks: bool,
     pub rpath: bool,
     pub incremental: bool,
     pub panic: PanicStrategy,
     pub strip: Strip,
+    // `None` means use rustc default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codegen_backend: Option<InternedString>,
+    // Extra flags passed to rustc, after the flags from the profile settings.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rustflags: Vec<InternedString>,
 }
 
 impl Default for Profile {
@@ -633,12 +659,15 @@ impl Default for Profile {
             lto: Lto::Bool(false),
             codegen_units: None,
             debuginfo: None,
+            split_debuginfo: None,
             debug_assertions: false,
             overflow_checks: false,
             rpath: false,
             incremental: false,
             panic: PanicStrategy::Unwind,
             strip: Strip::None,
+            codegen_backend: None,
+            rustflags: Vec::new(),
         }
     }
 }
@@ -658,12 +687,15 @@ compact_debug! {
                 root
                 codegen_units
                 debuginfo
+                split_debuginfo
                 debug_assertions
                 overflow_checks
                 rpath
                 incremental
                 panic
                 strip
+                codegen_backend
+                rustflags
             )]
         }
     }
@@ -695,7 +727,7 @@ impl Profile {
         Profile {
             name: InternedString::new("dev"),
             root: ProfileRoot::Debug,
-            debuginfo: Some(2),
+            debuginfo: Some(DebugInfo::Full),
             debug_assertions: true,
             overflow_checks: true,
             incremental: true,
@@ -738,31 +770,38 @@ impl Profile {
     /// Compares all fields except `name`, which doesn't affect compilation.
     /// This is necessary for `Unit` deduplication for things like "test" and
     /// "dev" which are essentially the same.
+    ///
+    /// Tuples only implement `Hash` and `Eq` up to 12 elements, so related
+    /// settings are grouped into nested tuples.
     fn comparable(
         &self,
     ) -> (
         InternedString,
         Lto,
         Option<u32>,
-        Option<u32>,
+        (Option<DebugInfo>, Option<InternedString>),
         bool,
         bool,
         bool,
         bool,
         PanicStrategy,
         Strip,
+        Option<InternedString>,
+        &[InternedString],
     ) {
         (
             self.opt_level,
             self.lto,
             self.codegen_units,
-            self.debuginfo,
+            (self.debuginfo, self.split_debuginfo),
             self.debug_assertions,
             self.overflow_checks,
             self.rpath,
             self.incremental,
             self.panic,
             self.strip,
+            self.codegen_backend,
+            &self.rustflags,
         )
     }
 }
@@ -810,6 +849,154 @@ impl fmt::Display for PanicStrategy {
     }
 }
 
+/// The amount of debug information to generate, set with the `debug` key.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+pub enum DebugInfo {
+    /// No debug information, `debug = 0`.
+    None,
+    /// Only line info directives, `debug = "line-directives-only"`.
+    LineDirectivesOnly,
+    /// Only line tables, `debug = "line-tables-only"`.
+    LineTablesOnly,
+    /// Line tables and function-level information, `debug = 1`.
+    Limited,
+    /// Full debug information, `debug = true` or `debug = 2`.
+    Full,
+}
+
+impl DebugInfo {
+    /// Returns whether any debug information is generated.
+    pub fn is_turned_on(&self) -> bool {
+        *self != DebugInfo::None
+    }
+}
+
+impl fmt::Display for DebugInfo {
+    /// The value passed to `-C debuginfo`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DebugInfo::None => "0",
+            DebugInfo::LineDirectivesOnly => "line-directives-only",
+            DebugInfo::LineTablesOnly => "line-tables-only",
+            DebugInfo::Limited => "1",
+            DebugInfo::Full => "2",
+        }
+        .fmt(f)
+    }
+}
+
+impl serde::ser::Serialize for DebugInfo {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        match self {
+            DebugInfo::None => 0.serialize(s),
+            DebugInfo::LineDirectivesOnly => "line-directives-only".serialize(s),
+            DebugInfo::LineTablesOnly => "line-tables-only".serialize(s),
+            DebugInfo::Limited => 1.serialize(s),
+            DebugInfo::Full => 2.serialize(s),
+        }
+    }
+}
+
+/// The `debug` key of a profile as written in the manifest or config.
+///
+/// `debug = false` is kept apart from an explicit level: only an explicit
+/// level is passed to rustc, so that `debug = 0` can override a level set in
+/// `RUSTFLAGS`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TomlDebugInfo {
+    /// `debug = false`.
+    False,
+    /// `debug = true`, a number or one of the named levels.
+    Level(DebugInfo),
+}
+
+impl serde::ser::Serialize for TomlDebugInfo {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        match self {
+            TomlDebugInfo::False => false.serialize(s),
+            TomlDebugInfo::Level(level) => level.serialize(s),
+        }
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for TomlDebugInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = TomlDebugInfo;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str(
+                    "a boolean, 0, 1, 2, \"none\", \"limited\", \"full\", \
+                     \"line-tables-only\" or \"line-directives-only\"",
+                )
+            }
+
+            fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(if b {
+                    TomlDebugInfo::Level(DebugInfo::Full)
+                } else {
+                    TomlDebugInfo::False
+                })
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match v {
+                    0 => Ok(TomlDebugInfo::Level(DebugInfo::None)),
+                    1 => Ok(TomlDebugInfo::Level(DebugInfo::Limited)),
+                    2 => Ok(TomlDebugInfo::Level(DebugInfo::Full)),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Signed(v), &self)),
+                }
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match v {
+                    0 => Ok(TomlDebugInfo::Level(DebugInfo::None)),
+                    1 => Ok(TomlDebugInfo::Level(DebugInfo::Limited)),
+                    2 => Ok(TomlDebugInfo::Level(DebugInfo::Full)),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Unsigned(v), &self)),
+                }
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let level = match v {
+                    "none" => DebugInfo::None,
+                    "line-directives-only" => DebugInfo::LineDirectivesOnly,
+                    "line-tables-only" => DebugInfo::LineTablesOnly,
+                    "limited" => DebugInfo::Limited,
+                    "full" => DebugInfo::Full,
+                    _ => return Err(E::invalid_value(serde::de::Unexpected::Str(v), &self)),
+                };
+                Ok(TomlDebugInfo::Level(level))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
 /// The setting for choosing which symbols to strip
 #[derive(
     Clone, Copy, PartialEq, Eq, Debug, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
@@ -31,6 +31,12 @@ pub trait Registry {
 
     fn describe_source(&self, source: SourceId) -> String;
     fn is_replaced(&self, source: SourceId) -> bool;
+
+    /// Query if a package has already been downloaded, see
+    /// `Source::is_crate_downloaded`.
+    fn is_crate_downloaded(&self, _pkg: PackageId) -> bool {
+        false
+    }
 }
 
 /// This structure represents a registry of known packages. It internally
@@ -373,6 +379,16 @@ impl<'cfg> PackageRegistry<'cfg> {
             .collect()
     }
 
+    /// Queries the already loaded source of `dep` for every matching version,
+    /// see `Source::query_online`. Patches and overrides are not applied.
+    pub fn query_online(&mut self, dep: &Dependency) -> CargoResult<Vec<Summary>> {
+        let mut ret = Vec::new();
+        if let Some(source) = self.sources.get_mut(dep.source_id()) {
+            source.query_online(dep, &mut |s| ret.push(s))?;
+        }
+        Ok(ret)
+    }
+
     fn load(&mut self, source_id: SourceId, kind: Kind) -> CargoResult<()> {
         (|| {
             debug!("loading source {}", source_id);
@@ -631,6 +647,13 @@ impl<'cfg> Registry for PackageRegistry<'cfg> {
             None => false,
         }
     }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        match self.sources.get(pkg.source_id()) {
+            Some(src) => src.is_crate_downloaded(pkg),
+            None => false,
+        }
+    }
 }
 
 fn lock(
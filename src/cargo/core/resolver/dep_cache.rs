@@ -208,14 +208,36 @@ impl<'a> RegistryQueryer<'a> {
             }
         }
 
+        // With `net.prefer-offline` the versions which are already downloaded
+        // are tried first, and the others are only used if none of those
+        // work out.
+        let prefer_downloaded = match self.config {
+            Some(config) => config.net_config()?.prefer_offline == Some(true),
+            None => false,
+        };
+        let downloaded: HashSet<PackageId> = if prefer_downloaded {
+            ret.iter()
+                .map(|s| s.package_id())
+                .filter(|&id| self.registry.is_crate_downloaded(id))
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
         // When we attempt versions for a package we'll want to do so in a
         // sorted fashion to pick the "best candidates" first. Currently we try
-        // prioritized summaries (those in `try_to_use`) and failing that we
-        // list everything from the maximum version to the lowest version.
+        // prioritized summaries (those in `try_to_use`), then downloaded ones
+        // (see above), and failing that we list everything from the maximum
+        // version to the lowest version.
         ret.sort_unstable_by(|a, b| {
             let a_in_previous = self.try_to_use.contains(&a.package_id());
             let b_in_previous = self.try_to_use.contains(&b.package_id());
-            let previous_cmp = a_in_previous.cmp(&b_in_previous).reverse();
+            let a_downloaded = downloaded.contains(&a.package_id());
+            let b_downloaded = downloaded.contains(&b.package_id());
+            let previous_cmp = a_in_previous
+                .cmp(&b_in_previous)
+                .then(a_downloaded.cmp(&b_downloaded))
+                .reverse();
             match previous_cmp {
                 Ordering::Equal => {
                     let cmp = a.version().cmp(b.version());
@@ -688,8 +688,9 @@ pub fn encodable_package_id(
     if resolve_version <= ResolveVersion::V2 {
         if let Some(GitReference::Branch(b)) = id_to_encode.git_reference() {
             if b == "master" {
-                id_to_encode =
-                    SourceId::for_git(id_to_encode.url(), GitReference::DefaultBranch).unwrap();
+                id_to_encode = SourceId::for_git(id_to_encode.url(), GitReference::DefaultBranch)
+                    .unwrap()
+                    .with_git_submodules(id_to_encode.git_submodules().cloned());
             }
         }
     }
@@ -48,6 +48,10 @@ pub struct Resolve {
     /// `cargo::core::resolver::encode` for more.
     version: ResolveVersion,
     summaries: HashMap<PackageId, Summary>,
+    /// Whether this was created by combining both sides of a `Cargo.lock`
+    /// containing git merge conflict markers. Such a resolve is only used as
+    /// a set of preferred versions, nothing in it is locked.
+    merge_conflict: bool,
 }
 
 /// A version to indicate how a `Cargo.lock` should be serialized. Currently
@@ -112,6 +116,7 @@ impl Resolve {
             public_dependencies,
             version,
             summaries,
+            merge_conflict: false,
         }
     }
 
@@ -129,6 +134,74 @@ impl Resolve {
         }
     }
 
+    /// Combines the two sides of a conflicted `Cargo.lock` into one resolve.
+    ///
+    /// The result is the union of both graphs, which is not necessarily a
+    /// valid resolution (the same package may be locked at two versions). It
+    /// is marked so that it is only used as a preference set when resolving
+    /// again, see `is_merge_conflict`.
+    pub fn merge_conflict_sides(mut self, theirs: Resolve) -> CargoResult<Resolve> {
+        for (id, cksum) in theirs.checksums {
+            match self.checksums.get(&id) {
+                Some(Some(mine)) if cksum.as_ref().map_or(false, |c| c != mine) => {
+                    anyhow::bail!(
+                        "checksum for `{}` differs between the two sides of the \
+                         merge conflict",
+                        id
+                    );
+                }
+                Some(Some(_)) => {}
+                _ => {
+                    self.checksums.insert(id, cksum);
+                }
+            }
+        }
+        for node in theirs.graph.iter() {
+            self.graph.add(*node);
+            for (dep, edge) in theirs.graph.edges(node) {
+                self.graph.link(*node, *dep).extend(edge.iter().cloned());
+            }
+        }
+        for (id, features) in theirs.features {
+            self.features.entry(id).or_insert(features);
+        }
+        for (id, summary) in theirs.summaries {
+            self.summaries.entry(id).or_insert(summary);
+        }
+        for (key, value) in theirs.metadata {
+            self.metadata.entry(key).or_insert(value);
+        }
+        for id in theirs.unused_patches {
+            if !self.unused_patches.contains(&id) {
+                self.unused_patches.push(id);
+            }
+        }
+        let mut replacements = self.replacements.clone();
+        for (id, replacement) in theirs.replacements {
+            replacements.entry(id).or_insert(replacement);
+        }
+        let version = self.version.max(theirs.version);
+
+        let mut merged = Resolve::new(
+            self.graph,
+            replacements,
+            self.features,
+            self.checksums,
+            self.metadata,
+            self.unused_patches,
+            version,
+            self.summaries,
+        );
+        merged.merge_conflict = true;
+        Ok(merged)
+    }
+
+    /// Returns whether this resolve was created from a conflicted
+    /// `Cargo.lock` with `merge_conflict_sides`.
+    pub fn is_merge_conflict(&self) -> bool {
+        self.merge_conflict
+    }
+
     pub fn merge_from(&mut self, previous: &Resolve) -> CargoResult<()> {
         // Given a previous instance of resolve, it should be forbidden to ever
         // have a checksums which *differ*. If the same package ID has differing
@@ -378,7 +451,7 @@ impl PartialEq for Resolve {
             checksums metadata unused_patches public_dependencies summaries
             |
             // fields to ignore
-            version
+            version merge_conflict
         }
     }
 }
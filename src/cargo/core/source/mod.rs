@@ -7,7 +7,7 @@ use crate::util::{CargoResult, Config};
 
 mod source_id;
 
-pub use self::source_id::{GitReference, SourceId};
+pub use self::source_id::{GitReference, GitSubmodules, SourceId};
 
 /// Something that finds and downloads remote packages based on names and versions.
 pub trait Source {
@@ -36,6 +36,12 @@ pub trait Source {
     /// whereas an `Index` source may return dependencies that have the same canonicalization.
     fn fuzzy_query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()>;
 
+    /// Like `query`, but also finds the packages which are left out in
+    /// offline mode because they aren't downloaded.
+    fn query_online(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        self.query(dep, f)
+    }
+
     fn query_vec(&mut self, dep: &Dependency) -> CargoResult<Vec<Summary>> {
         let mut ret = Vec::new();
         self.query(dep, &mut |s| ret.push(s))?;
@@ -101,11 +107,23 @@ pub trait Source {
     /// Query if a package is yanked. Only registry sources can mark packages
     /// as yanked. This ignores the yanked whitelist.
     fn is_yanked(&mut self, _pkg: PackageId) -> CargoResult<bool>;
+
+    /// Query if a package has already been downloaded, so it can be used
+    /// without accessing the network. Only registry sources download
+    /// packages.
+    fn is_crate_downloaded(&self, _pkg: PackageId) -> bool {
+        false
+    }
 }
 
 pub enum MaybePackage {
     Ready(Package),
-    Download { url: String, descriptor: String },
+    Download {
+        url: String,
+        descriptor: String,
+        /// The value of the `Authorization` header to send, if any.
+        authorization: Option<String>,
+    },
 }
 
 impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
@@ -139,6 +157,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
         (**self).fuzzy_query(dep, f)
     }
 
+    fn query_online(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        (**self).query_online(dep, f)
+    }
+
     /// Forwards to `Source::update`.
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
@@ -178,6 +200,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
     fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
         (**self).is_yanked(pkg)
     }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        (**self).is_crate_downloaded(pkg)
+    }
 }
 
 impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
@@ -205,6 +231,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
         (**self).fuzzy_query(dep, f)
     }
 
+    fn query_online(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        (**self).query_online(dep, f)
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         (**self).update()
     }
@@ -240,6 +270,10 @@ impl<'a, T: Source + ?Sized + 'a> Source for &'a mut T {
     fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
         (**self).is_yanked(pkg)
     }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        (**self).is_crate_downloaded(pkg)
+    }
 }
 
 /// A `HashMap` of `SourceId` -> `Box<Source>`.
@@ -3,6 +3,7 @@ use crate::sources::DirectorySource;
 use crate::sources::{GitSource, PathSource, RegistrySource, CRATES_IO_INDEX};
 use crate::util::{CanonicalUrl, CargoResult, Config, IntoUrl};
 use log::trace;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::de;
 use serde::ser;
 use std::cmp::{self, Ordering};
@@ -14,6 +15,17 @@ use std::ptr;
 use std::sync::Mutex;
 use url::Url;
 
+/// The characters escaped in the paths of the `submodules` query parameter,
+/// so they can't be confused with the separators of the URL or the list.
+const SUBMODULE_PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b',')
+    .add(b'=');
+
 lazy_static::lazy_static! {
     static ref SOURCE_ID_CACHE: Mutex<HashSet<&'static SourceIdInner>> = Default::default();
 }
@@ -38,6 +50,9 @@ struct SourceIdInner {
     /// WARNING: this is not always set for alt-registries when the name is
     /// not known.
     name: Option<String>,
+    /// Which submodules of a git source are checked out, if set for the
+    /// dependency. Otherwise `net.git-submodules` decides.
+    git_submodules: Option<GitSubmodules>,
 }
 
 /// The possible kinds of code source. Along with `SourceIdInner`, this fully defines the
@@ -69,6 +84,18 @@ pub enum GitReference {
     DefaultBranch,
 }
 
+/// Which submodules of a git repository are checked out along with it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GitSubmodules {
+    /// Every submodule, recursively.
+    All,
+    /// No submodules at all.
+    None,
+    /// Only the submodules within these paths of the repository, along with
+    /// all of their nested submodules.
+    Paths(Vec<String>),
+}
+
 impl SourceId {
     /// Creates a `SourceId` object from the kind and URL.
     ///
@@ -80,6 +107,7 @@ impl SourceId {
             url,
             precise: None,
             name: None,
+            git_submodules: None,
         });
         Ok(source_id)
     }
@@ -115,6 +143,14 @@ impl SourceId {
             "git" => {
                 let mut url = url.into_url()?;
                 let mut reference = GitReference::DefaultBranch;
+                // The paths of the submodules are escaped individually, so
+                // the list is split before decoding it.
+                let submodules = url.query().and_then(|query| {
+                    query
+                        .split('&')
+                        .find(|pair| pair.starts_with("submodules="))
+                        .map(|pair| GitSubmodules::from_query(&pair["submodules=".len()..]))
+                });
                 for (k, v) in url.query_pairs() {
                     match &k[..] {
                         // Map older 'ref' to branch.
@@ -128,7 +164,9 @@ impl SourceId {
                 let precise = url.fragment().map(|s| s.to_owned());
                 url.set_fragment(None);
                 url.set_query(None);
-                Ok(SourceId::for_git(&url, reference)?.with_precise(precise))
+                Ok(SourceId::for_git(&url, reference)?
+                    .with_git_submodules(submodules)
+                    .with_precise(precise))
             }
             "registry" => {
                 let url = url.into_url()?;
@@ -200,6 +238,7 @@ impl SourceId {
             url,
             precise: None,
             name: Some(key.to_string()),
+            git_submodules: None,
         }))
     }
 
@@ -314,6 +353,23 @@ impl SourceId {
         }
     }
 
+    /// Gets the submodules set for this git source, if any.
+    pub fn git_submodules(self) -> Option<&'static GitSubmodules> {
+        self.inner.git_submodules.as_ref()
+    }
+
+    /// Creates a new `SourceId` from this git source with the given
+    /// submodules to check out.
+    ///
+    /// `None` leaves the choice to the `net.git-submodules` config, so an
+    /// explicit `GitSubmodules::All` is kept distinct from it.
+    pub fn with_git_submodules(self, v: Option<GitSubmodules>) -> SourceId {
+        SourceId::wrap(SourceIdInner {
+            git_submodules: v,
+            ..(*self.inner).clone()
+        })
+    }
+
     /// Creates a new `SourceId` from this source with the given `precise`.
     pub fn with_precise(self, v: Option<String>) -> SourceId {
         SourceId::wrap(SourceIdInner {
@@ -423,6 +479,10 @@ impl Ord for SourceId {
                 if ord != Ordering::Equal {
                     return ord;
                 }
+                let ord = self.inner.git_submodules.cmp(&other.inner.git_submodules);
+                if ord != Ordering::Equal {
+                    return ord;
+                }
             }
         }
 
@@ -482,6 +542,14 @@ impl fmt::Display for SourceId {
                 if let Some(pretty) = reference.pretty_ref() {
                     write!(f, "?{}", pretty)?;
                 }
+                if let Some(submodules) = &self.inner.git_submodules {
+                    let sep = if reference.pretty_ref().is_some() {
+                        '&'
+                    } else {
+                        '?'
+                    };
+                    write!(f, "{}submodules={}", sep, submodules)?;
+                }
 
                 if let Some(ref s) = self.inner.precise {
                     let len = cmp::min(s.len(), 8);
@@ -541,6 +609,10 @@ impl Hash for SourceId {
             SourceKind::Git(_) => self.inner.canonical_url.hash(into),
             _ => self.inner.url.as_str().hash(into),
         }
+        // Only hashed when set, so sources without it keep their old hash.
+        if let Some(submodules) = &self.inner.git_submodules {
+            submodules.hash(into);
+        }
     }
 }
 
@@ -561,12 +633,21 @@ impl<'a> fmt::Display for SourceIdAsUrl<'a> {
                 kind: SourceKind::Git(ref reference),
                 ref url,
                 ref precise,
+                ref git_submodules,
                 ..
             } => {
                 write!(f, "git+{}", url)?;
                 if let Some(pretty) = reference.pretty_ref() {
                     write!(f, "?{}", pretty)?;
                 }
+                if let Some(submodules) = git_submodules {
+                    let sep = if reference.pretty_ref().is_some() {
+                        '&'
+                    } else {
+                        '?'
+                    };
+                    write!(f, "{}submodules={}", sep, submodules)?;
+                }
                 if let Some(precise) = precise.as_ref() {
                     write!(f, "#{}", precise)?;
                 }
@@ -618,9 +699,48 @@ impl<'a> fmt::Display for PrettyRef<'a> {
     }
 }
 
+impl GitSubmodules {
+    /// Parses the raw value of the `submodules` query parameter of a source
+    /// URL, as written by the `Display` implementation.
+    fn from_query(s: &str) -> GitSubmodules {
+        match s {
+            "true" => GitSubmodules::All,
+            "false" => GitSubmodules::None,
+            _ => GitSubmodules::Paths(
+                s.split(',')
+                    .map(|p| percent_decode_str(p).decode_utf8_lossy().into_owned())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for GitSubmodules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitSubmodules::All => f.write_str("true"),
+            GitSubmodules::None => f.write_str("false"),
+            GitSubmodules::Paths(paths) => {
+                for (i, path) in paths.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    match path.as_str() {
+                        // Keep a lone path from reading as a boolean.
+                        "true" => f.write_str("%74rue")?,
+                        "false" => f.write_str("%66alse")?,
+                        _ => write!(f, "{}", utf8_percent_encode(path, SUBMODULE_PATH))?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{GitReference, SourceId, SourceKind};
+    use super::{GitReference, GitSubmodules, SourceId, SourceKind};
     use crate::util::IntoUrl;
 
     #[test]
@@ -638,4 +758,59 @@ mod tests {
         let s3 = SourceId::new(foo, loc).unwrap();
         assert_ne!(s1, s3);
     }
+
+    #[test]
+    fn git_submodules_round_trip() {
+        let loc = "https://github.com/foo/bar".into_url().unwrap();
+        let branch = SourceKind::Git(GitReference::Branch("foo".to_string()));
+        let s1 = SourceId::new(branch, loc).unwrap();
+        let s2 = s1.with_git_submodules(Some(GitSubmodules::Paths(vec![
+            "a".to_string(),
+            "b/c".to_string(),
+        ])));
+        assert_ne!(s1, s2);
+
+        let url = s2.as_url().to_string();
+        assert_eq!(
+            url,
+            "git+https://github.com/foo/bar?branch=foo&submodules=a,b/c"
+        );
+        let s3 = SourceId::from_url(&url).unwrap();
+        assert_eq!(s2, s3);
+        assert_eq!(s3.git_submodules(), s2.git_submodules());
+    }
+
+    #[test]
+    fn git_submodules_escaped_paths_round_trip() {
+        let loc = "https://github.com/foo/bar".into_url().unwrap();
+        let s1 = SourceId::new(SourceKind::Git(GitReference::DefaultBranch), loc).unwrap();
+        let s2 = s1.with_git_submodules(Some(GitSubmodules::Paths(vec![
+            "a,b".to_string(),
+            "c&d=e".to_string(),
+            "f#g %h+".to_string(),
+            "true".to_string(),
+        ])));
+
+        let url = s2.as_url().to_string();
+        assert_eq!(
+            url,
+            "git+https://github.com/foo/bar?submodules=a%2Cb,c%26d%3De,f%23g%20%25h%2B,%74rue"
+        );
+        let s3 = SourceId::from_url(&url).unwrap();
+        assert_eq!(s2, s3);
+    }
+
+    #[test]
+    fn git_submodules_all_is_not_default() {
+        let loc = "https://github.com/foo/bar".into_url().unwrap();
+        let s1 = SourceId::new(SourceKind::Git(GitReference::DefaultBranch), loc).unwrap();
+        let s2 = s1.with_git_submodules(Some(GitSubmodules::All));
+        assert_ne!(s1, s2);
+        assert_eq!(s2.git_submodules(), Some(&GitSubmodules::All));
+
+        let url = s2.as_url().to_string();
+        assert_eq!(url, "git+https://github.com/foo/bar?submodules=true");
+        let s3 = SourceId::from_url(&url).unwrap();
+        assert_eq!(s2, s3);
+    }
 }
@@ -168,6 +168,11 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
                         let unhashed_dep_info = dir.join(format!("{}.d", crate_name));
                         rm_rf_glob(&hashed_dep_info, config)?;
                         rm_rf(&unhashed_dep_info, config)?;
+                        // Remove the split debuginfo objects generated by rustc
+                        // with `split-debuginfo = "unpacked"`. They are not
+                        // tracked in file_types either.
+                        let dwo_files = dir.join(format!("{}-*.dwo", crate_name));
+                        rm_rf_glob(&dwo_files, config)?;
 
                         // Remove the uplifted copy.
                         if let Some(uplift_dir) = uplift_dir {
@@ -31,7 +31,7 @@ use crate::core::compiler::{standard_lib, unit_graph};
 use crate::core::compiler::{BuildConfig, BuildContext, Compilation, Context};
 use crate::core::compiler::{CompileKind, CompileMode, RustcTargetData, Unit};
 use crate::core::compiler::{DefaultExecutor, Executor, UnitInterner};
-use crate::core::profiles::{Profiles, UnitFor};
+use crate::core::profiles::{PanicStrategy, Profiles, UnitFor};
 use crate::core::resolver::features::{self, FeaturesFor};
 use crate::core::resolver::{HasDevUnits, Resolve, ResolveOpts};
 use crate::core::{FeatureValue, Package, PackageSet, Shell, Summary, Target};
@@ -310,7 +310,20 @@ pub fn create_bcx<'a, 'cfg>(
         }
     }
 
-    let target_data = RustcTargetData::new(ws, &build_config.requested_kinds)?;
+    // Artifact dependencies may be built for targets other than the requested
+    // ones, so information about those targets is needed as well.
+    let mut explicit_kinds = build_config.requested_kinds.clone();
+    for member in ws.members() {
+        for dep in member.dependencies() {
+            if let Some(target) = dep.artifact().and_then(|a| a.target()) {
+                let kind = CompileKind::Target(target);
+                if !explicit_kinds.contains(&kind) {
+                    explicit_kinds.push(kind);
+                }
+            }
+        }
+    }
+    let target_data = RustcTargetData::new(ws, &explicit_kinds)?;
 
     let specs = spec.to_package_id_specs(ws)?;
     let dev_deps = ws.require_optional_deps() || filter.need_dev_deps(build_config.mode);
@@ -323,7 +336,7 @@ pub fn create_bcx<'a, 'cfg>(
     let resolve = ops::resolve_ws_with_opts(
         ws,
         &target_data,
-        &build_config.requested_kinds,
+        &explicit_kinds,
         &opts,
         &specs,
         has_dev_units,
@@ -336,20 +349,28 @@ pub fn create_bcx<'a, 'cfg>(
         resolved_features,
     } = resolve;
 
-    let std_resolve_features = if let Some(crates) = &config.cli_unstable().build_std {
+    let profiles = Profiles::new(
+        ws.profiles(),
+        config,
+        build_config.requested_profile,
+        ws.features(),
+    )?;
+
+    let mut std_crates =
+        standard_lib::std_crates(config, &target_data, &build_config.requested_kinds)?;
+    let std_resolve_features = if !std_crates.is_empty() {
         if build_config.build_plan {
             config
                 .shell()
-                .warn("-Zbuild-std does not currently fully support --build-plan")?;
-        }
-        if build_config.requested_kinds[0].is_host() {
-            // TODO: This should eventually be fixed. Unfortunately it is not
-            // easy to get the host triple in BuildConfig. Consider changing
-            // requested_target to an enum, or some other approach.
-            anyhow::bail!("-Zbuild-std requires --target");
+                .warn("build-std does not currently fully support --build-plan")?;
         }
+        let std_kinds: Vec<CompileKind> = std_crates.keys().cloned().collect();
+        let mut crates: Vec<String> = std_crates.values().flatten().cloned().collect();
+        crates.sort();
+        crates.dedup();
+        let features = standard_lib::std_features(config, &profiles)?;
         let (std_package_set, std_resolve, std_features) =
-            standard_lib::resolve_std(ws, &target_data, &build_config.requested_kinds, crates)?;
+            standard_lib::resolve_std(ws, &target_data, &std_kinds, &crates, &features)?;
         pkg_set.add_set(std_package_set);
         Some((std_resolve, std_features))
     } else {
@@ -398,12 +419,6 @@ pub fn create_bcx<'a, 'cfg>(
         );
     }
 
-    let profiles = Profiles::new(
-        ws.profiles(),
-        config,
-        build_config.requested_profile,
-        ws.features(),
-    )?;
     profiles.validate_packages(
         ws.profiles(),
         &mut config.shell(),
@@ -424,25 +439,34 @@ pub fn create_bcx<'a, 'cfg>(
         interner,
     )?;
 
-    let std_roots = if let Some(crates) = &config.cli_unstable().build_std {
-        // Only build libtest if it looks like it is needed.
-        let mut crates = crates.clone();
-        if !crates.iter().any(|c| c == "test")
-            && units
-                .iter()
-                .any(|unit| unit.mode.is_rustc_test() && unit.target.harness())
-        {
-            // Only build libtest when libstd is built (libtest depends on libstd)
-            if crates.iter().any(|c| c == "std") {
+    let std_roots = if let Some((std_resolve, std_features)) = &std_resolve_features {
+        for (kind, crates) in std_crates.iter_mut() {
+            // Only build libtest and the `panic=abort` runtime when libstd is
+            // built (they depend on libstd), and if it looks like they are
+            // needed.
+            if !crates.iter().any(|c| c == "std") {
+                continue;
+            }
+            let kind_units = units.iter().filter(|unit| unit.kind == *kind);
+            if !crates.iter().any(|c| c == "test")
+                && kind_units
+                    .clone()
+                    .any(|unit| unit.mode.is_rustc_test() && unit.target.harness())
+            {
                 crates.push("test".to_string());
             }
+            if !crates.iter().any(|c| c == "panic_abort")
+                && kind_units
+                    .clone()
+                    .any(|unit| unit.profile.panic == PanicStrategy::Abort)
+            {
+                crates.push("panic_abort".to_string());
+            }
         }
-        let (std_resolve, std_features) = std_resolve_features.as_ref().unwrap();
         standard_lib::generate_std_roots(
-            &crates,
+            &std_crates,
             std_resolve,
             std_features,
-            &build_config.requested_kinds,
             &pkg_set,
             interner,
             &profiles,
@@ -499,6 +523,25 @@ pub fn create_bcx<'a, 'cfg>(
         interner,
     )?;
 
+    // `rustc` only notices a missing codegen backend once it starts compiling,
+    // check all of them upfront for a better error.
+    for unit in unit_graph.keys() {
+        if let Some(backend) = unit.profile.codegen_backend {
+            let backends = target_data.info(unit.kind).codegen_backends();
+            if !backends.iter().any(|b| *b == backend.as_str()) {
+                anyhow::bail!(
+                    "codegen backend `{}` selected by profile `{}` for package `{}` \
+                     is not available\n\
+                     available backends: {}",
+                    backend,
+                    unit.profile.name,
+                    unit.pkg.name(),
+                    backends.join(", ")
+                );
+            }
+        }
+    }
+
     let bcx = BuildContext::new(
         ws,
         pkg_set,
@@ -784,7 +827,7 @@ fn generate_targets(
                 let unit = interner.intern(
                     pkg,
                     target,
-                    profile,
+                    profile.clone(),
                     kind.for_target(target),
                     target_mode,
                     features.clone(),
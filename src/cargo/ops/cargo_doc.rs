@@ -18,6 +18,17 @@ pub struct DocOptions {
 
 /// Main method for `cargo doc`.
 pub fn doc(ws: &Workspace<'_>, options: &DocOptions) -> CargoResult<()> {
+    let open_kind = if options.open_result {
+        Some(
+            options
+                .compile_opts
+                .build_config
+                .single_requested_kind("`cargo doc --open`")?,
+        )
+    } else {
+        None
+    };
+
     let specs = options.compile_opts.spec.to_package_id_specs(ws)?;
     let opts = ResolveOpts::new(
         /*dev_deps*/ true,
@@ -69,12 +80,6 @@ pub fn doc(ws: &Workspace<'_>, options: &DocOptions) -> CargoResult<()> {
         }
     }
 
-    let open_kind = if options.open_result {
-        Some(options.compile_opts.build_config.single_requested_kind()?)
-    } else {
-        None
-    };
-
     let compilation = ops::compile(ws, &options.compile_opts)?;
 
     if let Some(kind) = open_kind {
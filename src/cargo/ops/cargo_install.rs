@@ -593,7 +593,7 @@ fn make_ws_rustc_target<'cfg>(
     ws.set_require_optional_deps(false);
 
     let rustc = config.load_global_rustc(Some(&ws))?;
-    let target = match &opts.build_config.single_requested_kind()? {
+    let target = match &opts.build_config.single_requested_kind("`cargo install`")? {
         CompileKind::Host => rustc.host.as_str().to_owned(),
         CompileKind::Target(target) => target.short_name().to_owned(),
     };
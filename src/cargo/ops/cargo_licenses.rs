@@ -0,0 +1,192 @@
+//! Implementation of `cargo licenses`.
+//!
+//! The `license` and `license-file` fields of every package compiled for the
+//! requested targets are collected from the unit graph, parsed as SPDX
+//! expressions, and checked against the `[licenses]` config table. Only
+//! packages outside of the workspace are subject to the checks.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::core::compiler::UnitInterner;
+use crate::core::{Package, PackageId, Workspace};
+use crate::ops::{self, CompileOptions};
+use crate::util::spdx::LicenseExpr;
+use crate::util::{paths, CargoResult};
+
+pub struct LicensesOptions {
+    pub compile_opts: CompileOptions,
+    /// Path of a third-party notices file to write with the license texts of
+    /// every dependency.
+    pub notices: Option<PathBuf>,
+}
+
+/// The `[licenses]` config table.
+#[derive(Debug, Default, Deserialize)]
+struct LicensesConfig {
+    allow: Option<Vec<String>>,
+    deny: Option<Vec<String>>,
+}
+
+/// Name of the group for packages which only specify `license-file`.
+const LICENSE_FILE_GROUP: &str = "(license file)";
+
+/// File name prefixes of license texts collected for the notices file.
+const LICENSE_FILE_PREFIXES: &[&str] = &["LICENSE", "LICENCE", "COPYING", "NOTICE"];
+
+pub fn licenses(ws: &Workspace<'_>, opts: &LicensesOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let cfg = config
+        .get::<Option<LicensesConfig>>("licenses")?
+        .unwrap_or_default();
+    let allow: BTreeSet<String> = cfg.allow.unwrap_or_default().into_iter().collect();
+    let deny: BTreeSet<String> = cfg.deny.unwrap_or_default().into_iter().collect();
+    let denied = |l: &str| deny.contains(l);
+    let allowed = |l: &str| allow.is_empty() || allow.contains(l);
+
+    let interner = UnitInterner::new();
+    let bcx = ops::create_bcx(ws, &opts.compile_opts, &interner)?;
+    let pkgs: BTreeMap<PackageId, &Package> = bcx
+        .unit_graph
+        .keys()
+        .map(|unit| (unit.pkg.package_id(), &unit.pkg))
+        .collect();
+    let members: BTreeSet<PackageId> = ws.members().map(|pkg| pkg.package_id()).collect();
+
+    let mut groups: BTreeMap<String, Vec<PackageId>> = BTreeMap::new();
+    let mut errors = Vec::new();
+    for (&id, pkg) in pkgs.iter() {
+        let is_member = members.contains(&id);
+        let metadata = pkg.manifest().metadata();
+        match (&metadata.license, &metadata.license_file) {
+            (Some(license), _) => match LicenseExpr::parse(license) {
+                Ok(expr) => {
+                    if !is_member && !expr.is_satisfied_by(&denied, &allowed) {
+                        errors.push(format!(
+                            "package `{}` has license `{}` which is not allowed",
+                            id, expr
+                        ));
+                    }
+                    groups.entry(expr.to_string()).or_default().push(id);
+                }
+                Err(e) => {
+                    if !is_member {
+                        errors.push(format!(
+                            "package `{}` has an invalid license expression: {}",
+                            id, e
+                        ));
+                    }
+                    groups.entry(license.clone()).or_default().push(id);
+                }
+            },
+            (None, Some(_)) => {
+                if !is_member && !allow.is_empty() {
+                    config.shell().warn(format!(
+                        "package `{}` only specifies a `license-file`, \
+                         which cannot be checked against the allowed licenses",
+                        id
+                    ))?;
+                }
+                groups
+                    .entry(LICENSE_FILE_GROUP.to_string())
+                    .or_default()
+                    .push(id);
+            }
+            (None, None) => {
+                if !is_member {
+                    errors.push(format!(
+                        "package `{}` does not specify a `license` or `license-file`",
+                        id
+                    ));
+                }
+            }
+        }
+    }
+
+    for (license, ids) in groups.iter() {
+        crate::drop_println!(config, "{} ({})", license, ids.len());
+        for id in ids {
+            crate::drop_println!(config, "    {}", id);
+        }
+    }
+
+    if let Some(path) = &opts.notices {
+        let third_party = pkgs
+            .values()
+            .filter(|pkg| !members.contains(&pkg.package_id()));
+        let mut out = String::new();
+        for pkg in third_party {
+            let files = license_files(pkg)?;
+            if files.is_empty() {
+                config.shell().warn(format!(
+                    "no license text found for package `{}`",
+                    pkg.package_id()
+                ))?;
+                continue;
+            }
+            let metadata = pkg.manifest().metadata();
+            writeln!(out, "{} {}", pkg.name(), pkg.version()).unwrap();
+            if let Some(license) = &metadata.license {
+                writeln!(out, "License: {}", license).unwrap();
+            }
+            for file in files {
+                let text = paths::read(&file)?;
+                let name = file.strip_prefix(pkg.root()).unwrap_or(&file);
+                writeln!(out, "\n--- {} ---\n", name.display()).unwrap();
+                out.push_str(text.trim_end());
+                out.push('\n');
+            }
+            out.push_str("\n================================================================\n\n");
+        }
+        if let Some(parent) = path.parent() {
+            paths::create_dir_all(parent)?;
+        }
+        paths::write(path, out)?;
+        config.shell().status("Writing", path.display())?;
+    }
+
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            config.shell().error(error)?;
+        }
+        anyhow::bail!(
+            "{} license problem{} found",
+            errors.len(),
+            if errors.len() == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Finds the license texts shipped with a package: its `license-file`, and
+/// any `LICENSE*`, `COPYING*` or `NOTICE*` file in the package root.
+fn license_files(pkg: &Package) -> CargoResult<Vec<PathBuf>> {
+    let root = pkg.root();
+    let mut files = BTreeSet::new();
+    if let Some(license_file) = &pkg.manifest().metadata().license_file {
+        let path = root.join(license_file);
+        if path.is_file() {
+            files.insert(path);
+        }
+    }
+    for entry in root.read_dir()? {
+        let path = entry?.path();
+        if path.is_file() && is_license_file(&path) {
+            files.insert(path);
+        }
+    }
+    Ok(files.into_iter().collect())
+}
+
+fn is_license_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name.to_uppercase(),
+        None => return false,
+    };
+    LICENSE_FILE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
@@ -0,0 +1,178 @@
+use crate::core::compiler::{CompileKind, CompileTarget};
+use crate::core::shell::Verbosity;
+use crate::core::{Dependency, PackageId, Workspace};
+use crate::ops;
+use crate::sources::registry::RegistryPackage;
+use crate::util::{internal, paths, CargoResult, CargoResultExt, Config, Sha256};
+use anyhow::bail;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+pub struct LocalRegistryOptions<'a> {
+    /// The root of the local registry, created if it doesn't exist.
+    pub destination: &'a Path,
+    /// Only add the `.crate` files of packages used on these target triples,
+    /// or on the host if there are none.
+    pub platforms: Vec<String>,
+    /// Add the `.crate` files of all packages, regardless of `platforms`.
+    pub all_platforms: bool,
+}
+
+/// Adds the crates.io packages of the workspace to the local registry at
+/// `opts.destination`.
+///
+/// Every package gets an entry in the index, so that resolution works the
+/// same as against crates.io, but only the `.crate` files of packages used
+/// on the requested platforms are added. Packages which are already in the
+/// registry are left alone.
+pub fn local_registry(ws: &Workspace<'_>, opts: &LocalRegistryOptions<'_>) -> CargoResult<()> {
+    let config = ws.config();
+    let (packages, resolve) = ops::resolve_ws(ws)?;
+
+    let mut ids = BTreeSet::new();
+    for id in resolve.iter() {
+        if id.source_id().is_default_registry() {
+            ids.insert(id);
+        } else if !id.source_id().is_path() {
+            config.shell().warn(format!(
+                "`{}` is not from crates.io and can't be added to a local registry",
+                id
+            ))?;
+        }
+    }
+
+    let used = if opts.all_platforms {
+        ids.clone()
+    } else {
+        let kinds = if opts.platforms.is_empty() {
+            vec![CompileKind::Host]
+        } else {
+            opts.platforms
+                .iter()
+                .map(|p| Ok(CompileKind::Target(CompileTarget::new(p)?)))
+                .collect::<CargoResult<Vec<_>>>()?
+        };
+        let used = super::vendor::used_on_platforms(ws, &resolve, &kinds)?;
+        ids.iter().cloned().filter(|id| used.contains(id)).collect()
+    };
+    packages
+        .get_many(used.iter().cloned())
+        .chain_err(|| "failed to download packages")?;
+
+    let root = opts.destination;
+    paths::create_dir_all(root.join("index"))?;
+    let _lock = config.acquire_package_cache_lock()?;
+    for id in ids {
+        let cksum = match resolve.checksums().get(&id) {
+            Some(Some(cksum)) => cksum,
+            _ => bail!("missing checksum for `{}` in the lock file", id),
+        };
+
+        if used.contains(&id) {
+            let dst = root.join(format!("{}-{}.crate", id.name(), id.version()));
+            if !dst.exists() || Sha256::new().update_path(&dst)?.finish_hex() != *cksum {
+                let src = downloaded_crate(config, id, cksum)?;
+                paths::copy(&src, &dst)?;
+            }
+        }
+
+        // The resolved summary has its requirements locked to exact versions,
+        // so take the one from the index instead.
+        let dep = Dependency::parse_no_deprecated(
+            id.name(),
+            Some(&format!("={}", id.version())),
+            id.source_id(),
+        )?;
+        let summary = packages
+            .sources_mut()
+            .get_mut(id.source_id())
+            .ok_or_else(|| internal(format!("no source for `{}`", id)))?
+            .query_vec(&dep)?
+            .into_iter()
+            .find(|s| s.package_id() == id)
+            .ok_or_else(|| internal(format!("`{}` is missing from the index", id)))?;
+        let entry = RegistryPackage::new(&summary, cksum);
+        if add_to_index(root, id, cksum, &entry)? {
+            config.shell().status("Adding", id)?;
+        }
+    }
+
+    if config.shell().verbosity() != Verbosity::Quiet {
+        crate::drop_eprint!(
+            config,
+            "To use the local registry, add this to your .cargo/config for this project:\n\n"
+        );
+        crate::drop_print!(
+            config,
+            "[source.crates-io]\n\
+             replace-with = \"local-registry\"\n\
+             \n\
+             [source.local-registry]\n\
+             local-registry = {}\n",
+            toml::Value::String(root.display().to_string())
+        );
+    }
+    Ok(())
+}
+
+/// Adds `entry` to the index of the local registry at `root`, returning
+/// whether it wasn't there already.
+fn add_to_index(
+    root: &Path,
+    id: PackageId,
+    cksum: &str,
+    entry: &RegistryPackage<'_>,
+) -> CargoResult<bool> {
+    let fs_name = id.name().to_lowercase();
+    let path = match fs_name.len() {
+        1 => format!("1/{}", fs_name),
+        2 => format!("2/{}", fs_name),
+        3 => format!("3/{}/{}", &fs_name[..1], fs_name),
+        _ => format!("{}/{}/{}", &fs_name[0..2], &fs_name[2..4], fs_name),
+    };
+    let path = root.join("index").join(path);
+    let line = serde_json::to_string(entry)?;
+
+    let mut contents = if path.exists() {
+        paths::read(&path)?
+    } else {
+        String::new()
+    };
+    for existing in contents.lines() {
+        let existing: serde_json::Value = match serde_json::from_str(existing) {
+            Ok(existing) => existing,
+            Err(_) => continue,
+        };
+        if existing["vers"].as_str() == Some(&id.version().to_string()) {
+            if existing["cksum"].as_str() != Some(cksum) {
+                bail!(
+                    "`{}` is already in the local registry at `{}` with a different checksum",
+                    id,
+                    root.display()
+                );
+            }
+            return Ok(false);
+        }
+    }
+
+    contents.push_str(&line);
+    contents.push('\n');
+    paths::create_dir_all(path.parent().unwrap())?;
+    paths::write(&path, contents)?;
+    Ok(true)
+}
+
+/// Finds the `.crate` file of `id` in the download cache, which may be in
+/// the cache of a source replacing crates.io, by its checksum.
+fn downloaded_crate(config: &Config, id: PackageId, cksum: &str) -> CargoResult<PathBuf> {
+    let cache = config.registry_cache_path();
+    let cache = config.assert_package_cache_locked(&cache);
+    let file_name = format!("{}-{}.crate", id.name(), id.version());
+    for entry in cache.read_dir()? {
+        let path = entry?.path().join(&file_name);
+        if path.is_file() && Sha256::new().update_path(&path)?.finish_hex() == cksum {
+            return Ok(path);
+        }
+    }
+    bail!("failed to find the downloaded `.crate` file of `{}`", id)
+}
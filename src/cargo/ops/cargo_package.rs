@@ -11,9 +11,10 @@ use flate2::read::GzDecoder;
 use flate2::{Compression, GzBuilder};
 use log::debug;
 use tar::{Archive, Builder, EntryType, Header};
+use url::Url;
 
 use crate::core::compiler::{BuildConfig, CompileMode, DefaultExecutor, Executor};
-use crate::core::{Feature, Shell, Verbosity, Workspace};
+use crate::core::{Dependency, Feature, Shell, Verbosity, Workspace};
 use crate::core::{Package, PackageId, PackageSet, Resolve, Source, SourceId};
 use crate::sources::PathSource;
 use crate::util::errors::{CargoResult, CargoResultExt};
@@ -64,6 +65,20 @@ enum GeneratedFile {
 }
 
 pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option<FileLock>> {
+    package_with_patch(ws, opts, &HashMap::new())
+}
+
+/// Like `package`, but generates `Cargo.lock` and verifies the package with
+/// `patch` added to its `[patch]` table.
+///
+/// `cargo publish --workspace` uses this to verify packages against the
+/// packaged versions of the members they depend on, which aren't published
+/// yet.
+pub(crate) fn package_with_patch(
+    ws: &Workspace<'_>,
+    opts: &PackageOpts<'_>,
+    patch: &HashMap<Url, Vec<Dependency>>,
+) -> CargoResult<Option<FileLock>> {
     if ws.root().join("Cargo.lock").exists() {
         // Make sure the Cargo.lock is up-to-date and valid.
         let _ = ops::resolve_ws(ws)?;
@@ -124,11 +139,11 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
         .shell()
         .status("Packaging", pkg.package_id().to_string())?;
     dst.file().set_len(0)?;
-    tar(ws, ar_files, dst.file(), &filename)
+    tar(ws, ar_files, dst.file(), &filename, patch)
         .chain_err(|| anyhow::format_err!("failed to prepare local package for uploading"))?;
     if opts.verify {
         dst.seek(SeekFrom::Start(0))?;
-        run_verify(ws, &dst, opts).chain_err(|| "failed to verify package tarball")?
+        run_verify(ws, &dst, opts, patch).chain_err(|| "failed to verify package tarball")?
     }
     dst.seek(SeekFrom::Start(0))?;
     {
@@ -264,7 +279,7 @@ fn build_ar_list(
 }
 
 /// Construct `Cargo.lock` for the package to be published.
-fn build_lock(ws: &Workspace<'_>) -> CargoResult<String> {
+fn build_lock(ws: &Workspace<'_>, patch: &HashMap<Url, Vec<Dependency>>) -> CargoResult<String> {
     let config = ws.config();
     let orig_resolve = ops::load_pkg_lockfile(ws)?;
 
@@ -280,7 +295,8 @@ fn build_lock(ws: &Workspace<'_>) -> CargoResult<String> {
     let source_id = orig_pkg.package_id().source_id();
     let (manifest, _nested_paths) =
         TomlManifest::to_real_manifest(&toml_manifest, source_id, package_root, config)?;
-    let new_pkg = Package::new(manifest, orig_pkg.manifest_path());
+    let mut new_pkg = Package::new(manifest, orig_pkg.manifest_path());
+    new_pkg.manifest_mut().add_patch(patch);
 
     // Regenerate Cargo.lock using the old one as a guide.
     let tmp_ws = Workspace::ephemeral(new_pkg, ws.config(), None, true)?;
@@ -477,6 +493,7 @@ fn tar(
     ar_files: Vec<ArchiveFile>,
     dst: &File,
     filename: &str,
+    patch: &HashMap<Url, Vec<Dependency>>,
 ) -> CargoResult<()> {
     // Prepare the encoder and its header.
     let filename = Path::new(filename);
@@ -520,7 +537,7 @@ fn tar(
             FileContents::Generated(generated_kind) => {
                 let contents = match generated_kind {
                     GeneratedFile::Manifest => pkg.to_registry_toml(ws)?,
-                    GeneratedFile::Lockfile => build_lock(ws)?,
+                    GeneratedFile::Lockfile => build_lock(ws, patch)?,
                     GeneratedFile::VcsInfo(s) => s,
                 };
                 header.set_entry_type(EntryType::file());
@@ -652,30 +669,25 @@ fn check_yanked(config: &Config, pkg_set: &PackageSet<'_>, resolve: &Resolve) ->
     Ok(())
 }
 
-fn run_verify(ws: &Workspace<'_>, tar: &FileLock, opts: &PackageOpts<'_>) -> CargoResult<()> {
+fn run_verify(
+    ws: &Workspace<'_>,
+    tar: &FileLock,
+    opts: &PackageOpts<'_>,
+    patch: &HashMap<Url, Vec<Dependency>>,
+) -> CargoResult<()> {
     let config = ws.config();
     let pkg = ws.current()?;
 
     config.shell().status("Verifying", pkg)?;
 
-    let f = GzDecoder::new(tar.file());
-    let dst = tar
-        .parent()
-        .join(&format!("{}-{}", pkg.name(), pkg.version()));
-    if dst.exists() {
-        paths::remove_dir_all(&dst)?;
-    }
-    let mut archive = Archive::new(f);
-    // We don't need to set the Modified Time, as it's not relevant to verification
-    // and it errors on filesystems that don't support setting a modified timestamp
-    archive.set_preserve_mtime(false);
-    archive.unpack(dst.parent().unwrap())?;
+    let dst = unpack(tar, pkg)?;
 
     // Manufacture an ephemeral workspace to ensure that even if the top-level
     // package has a workspace we can still build our new crate.
     let id = SourceId::for_path(&dst)?;
     let mut src = PathSource::new(&dst, id, ws.config());
-    let new_pkg = src.root_package()?;
+    let mut new_pkg = src.root_package()?;
+    new_pkg.manifest_mut().add_patch(patch);
     let pkg_fingerprint = hash_all(&dst)?;
     let ws = Workspace::ephemeral(new_pkg, config, None, true)?;
 
@@ -728,6 +740,26 @@ fn run_verify(ws: &Workspace<'_>, tar: &FileLock, opts: &PackageOpts<'_>) -> Car
     Ok(())
 }
 
+/// Unpacks the tarball `tar` of `pkg` next to it, returning the directory of
+/// the unpacked package. The tarball is left rewound for uploading.
+pub(crate) fn unpack(tar: &FileLock, pkg: &Package) -> CargoResult<PathBuf> {
+    let dst = tar
+        .parent()
+        .join(&format!("{}-{}", pkg.name(), pkg.version()));
+    if dst.exists() {
+        paths::remove_dir_all(&dst)?;
+    }
+    let mut file = tar.file();
+    file.seek(SeekFrom::Start(0))?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+    // We don't need to set the Modified Time, as it's not relevant to verification
+    // and it errors on filesystems that don't support setting a modified timestamp
+    archive.set_preserve_mtime(false);
+    archive.unpack(dst.parent().unwrap())?;
+    tar.file().seek(SeekFrom::Start(0))?;
+    Ok(dst)
+}
+
 fn hash_all(path: &Path) -> CargoResult<HashMap<PathBuf, u64>> {
     fn wrap(path: &Path) -> CargoResult<HashMap<PathBuf, u64>> {
         let mut result = HashMap::new();
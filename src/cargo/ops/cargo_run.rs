@@ -71,7 +71,7 @@ pub fn run(
     }
 
     // `cargo run` is only compatible with one `--target` flag at most
-    options.build_config.single_requested_kind()?;
+    options.build_config.single_requested_kind("`cargo run`")?;
 
     let compile = ops::compile(ws, options)?;
     assert_eq!(compile.binaries.len(), 1);
@@ -0,0 +1,503 @@
+//! Implementation of `cargo sbom`.
+//!
+//! A Software Bill of Materials is generated for every root unit of the build
+//! (each binary, library, example, etc. that would be compiled). The contents
+//! are derived from the unit graph rather than the `Resolve`, so they only
+//! contain the packages which are actually compiled for the requested
+//! platform, with the features that are actually activated.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::core::compiler::{BuildContext, CompileKind, Unit, UnitInterner};
+use crate::core::{Package, PackageId, Workspace};
+use crate::ops::{self, CompileOptions};
+use crate::util::interning::InternedString;
+use crate::util::{paths, CargoResult, Sha256};
+
+pub struct SbomOptions {
+    pub compile_opts: CompileOptions,
+    pub format: SbomFormat,
+    /// Directory to write one file per artifact to. If `None`, each document
+    /// is printed on its own line to stdout.
+    pub out_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.3 JSON.
+    CycloneDx,
+    /// SPDX 2.2 JSON.
+    Spdx,
+}
+
+impl SbomFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            SbomFormat::CycloneDx => "cdx.json",
+            SbomFormat::Spdx => "spdx.json",
+        }
+    }
+}
+
+pub fn sbom(ws: &Workspace<'_>, opts: &SbomOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let interner = UnitInterner::new();
+    let bcx = ops::create_bcx(ws, &opts.compile_opts, &interner)?;
+
+    let mut roots = bcx.roots.clone();
+    roots.sort();
+    for root in roots.iter() {
+        let components = collect_components(&bcx, root);
+        let doc = match opts.format {
+            SbomFormat::CycloneDx => serde_json::to_value(cyclonedx(root, &components))?,
+            SbomFormat::Spdx => serde_json::to_value(spdx(root, &components)?)?,
+        };
+        match &opts.out_dir {
+            Some(out_dir) => {
+                paths::create_dir_all(out_dir)?;
+                let path = out_dir.join(format!(
+                    "{}.{}",
+                    document_name(root),
+                    opts.format.extension()
+                ));
+                paths::write(&path, serde_json::to_vec_pretty(&doc)?)?;
+                config.shell().status("Writing", path.display())?;
+            }
+            None => crate::drop_println!(config, "{}", doc),
+        }
+    }
+    Ok(())
+}
+
+/// The name of the document for `root`, which is unique among the roots of a
+/// build since it includes the package, the target and the platform.
+fn document_name(root: &Unit) -> String {
+    let mut name = format!(
+        "{}-{}-{}-{}",
+        root.pkg.name(),
+        root.pkg.version(),
+        root.target.name(),
+        root.target.kind().description()
+    );
+    if let CompileKind::Target(target) = root.kind {
+        name.push('-');
+        name.push_str(&target.short_name());
+    }
+    name
+}
+
+/// A package which is compiled as part of an artifact.
+struct Component<'a> {
+    pkg: &'a Package,
+    /// Features activated across all units of this package.
+    features: BTreeSet<InternedString>,
+    /// Whether this package is only used at build time (by build scripts or
+    /// procedural macros) and is not linked into the artifact.
+    build_only: bool,
+    /// Packages this package directly depends on.
+    deps: BTreeSet<PackageId>,
+}
+
+/// Walks the unit graph starting at `root` and collects every package it
+/// reaches.
+fn collect_components<'a>(
+    bcx: &'a BuildContext<'_, '_>,
+    root: &'a Unit,
+) -> BTreeMap<PackageId, Component<'a>> {
+    let mut components: BTreeMap<PackageId, Component<'a>> = BTreeMap::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![(root, false)];
+    while let Some((unit, build_only)) = stack.pop() {
+        if !visited.insert((unit, build_only)) {
+            continue;
+        }
+        let pkg_id = unit.pkg.package_id();
+        let component = components.entry(pkg_id).or_insert_with(|| Component {
+            pkg: &unit.pkg,
+            features: BTreeSet::new(),
+            build_only,
+            deps: BTreeSet::new(),
+        });
+        component.features.extend(unit.features.iter().cloned());
+        component.build_only &= build_only;
+
+        for dep in bcx.unit_graph[unit].iter() {
+            let dep_id = dep.unit.pkg.package_id();
+            if dep_id != pkg_id {
+                component.deps.insert(dep_id);
+            }
+            let dep_build_only =
+                build_only || dep.unit.target.is_custom_build() || dep.unit.target.proc_macro();
+            stack.push((&dep.unit, dep_build_only));
+        }
+    }
+    components
+}
+
+/// Uses the same `name version (source)` form as `cargo metadata`.
+fn bom_ref(id: PackageId) -> String {
+    format!(
+        "{} {} ({})",
+        id.name(),
+        id.version(),
+        id.source_id().as_url()
+    )
+}
+
+fn purl(id: PackageId) -> Option<String> {
+    if id.source_id().is_registry() {
+        Some(format!("pkg:cargo/{}@{}", id.name(), id.version()))
+    } else {
+        None
+    }
+}
+
+fn features_string(features: &BTreeSet<InternedString>) -> String {
+    features
+        .iter()
+        .map(|f| f.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxBom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: CycloneDxMetadata,
+    components: Vec<CycloneDxComponent>,
+    dependencies: Vec<CycloneDxDependency>,
+}
+
+#[derive(Serialize)]
+struct CycloneDxMetadata {
+    timestamp: String,
+    tools: Vec<CycloneDxTool>,
+    component: CycloneDxComponent,
+}
+
+#[derive(Serialize)]
+struct CycloneDxTool {
+    vendor: &'static str,
+    name: &'static str,
+    version: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    scope: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<CycloneDxHash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CycloneDxLicense>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purl: Option<String>,
+    external_references: Vec<CycloneDxReference>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    properties: Vec<CycloneDxProperty>,
+}
+
+#[derive(Serialize)]
+struct CycloneDxHash {
+    alg: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct CycloneDxLicense {
+    expression: String,
+}
+
+#[derive(Serialize)]
+struct CycloneDxReference {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct CycloneDxProperty {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDependency {
+    #[serde(rename = "ref")]
+    bom_ref: String,
+    depends_on: Vec<String>,
+}
+
+fn cyclonedx(root: &Unit, components: &BTreeMap<PackageId, Component<'_>>) -> CycloneDxBom {
+    let component = |c: &Component<'_>, kind| {
+        let id = c.pkg.package_id();
+        let metadata = c.pkg.manifest().metadata();
+        let source_id = id.source_id();
+        let reference_kind = if source_id.is_git() {
+            "vcs"
+        } else {
+            "distribution"
+        };
+        let mut properties = Vec::new();
+        if !c.features.is_empty() {
+            properties.push(CycloneDxProperty {
+                name: "cargo:features",
+                value: features_string(&c.features),
+            });
+        }
+        CycloneDxComponent {
+            kind,
+            bom_ref: bom_ref(id),
+            name: id.name().to_string(),
+            version: id.version().to_string(),
+            description: metadata.description.clone(),
+            scope: if c.build_only { "excluded" } else { "required" },
+            hashes: c
+                .pkg
+                .summary()
+                .checksum()
+                .map(|cksum| CycloneDxHash {
+                    alg: "SHA-256",
+                    content: cksum.to_string(),
+                })
+                .into_iter()
+                .collect(),
+            licenses: metadata
+                .license
+                .iter()
+                .map(|license| CycloneDxLicense {
+                    expression: license.clone(),
+                })
+                .collect(),
+            purl: purl(id),
+            external_references: vec![CycloneDxReference {
+                kind: reference_kind,
+                url: source_id.as_url().to_string(),
+            }],
+            properties,
+        }
+    };
+
+    let root_id = root.pkg.package_id();
+    let root_kind = if root.target.is_lib() {
+        "library"
+    } else {
+        "application"
+    };
+    CycloneDxBom {
+        bom_format: "CycloneDX",
+        spec_version: "1.3",
+        version: 1,
+        metadata: CycloneDxMetadata {
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            tools: vec![CycloneDxTool {
+                vendor: "rust-lang",
+                name: "cargo",
+                version: crate::version().to_string(),
+            }],
+            component: component(&components[&root_id], root_kind),
+        },
+        components: components
+            .values()
+            .filter(|c| c.pkg.package_id() != root_id)
+            .map(|c| component(c, "library"))
+            .collect(),
+        dependencies: components
+            .values()
+            .map(|c| CycloneDxDependency {
+                bom_ref: bom_ref(c.pkg.package_id()),
+                depends_on: c.deps.iter().map(|id| bom_ref(*id)).collect(),
+            })
+            .collect(),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: String,
+    document_namespace: String,
+    creation_info: SpdxCreationInfo,
+    document_describes: Vec<String>,
+    packages: Vec<SpdxPackage>,
+    relationships: Vec<SpdxRelationship>,
+}
+
+#[derive(Serialize)]
+struct SpdxCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: String,
+    version_info: String,
+    download_location: String,
+    files_analyzed: bool,
+    license_concluded: &'static str,
+    license_declared: String,
+    copyright_text: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checksums: Vec<SpdxChecksum>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_refs: Vec<SpdxExternalRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxChecksum {
+    algorithm: &'static str,
+    checksum_value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxExternalRef {
+    reference_category: &'static str,
+    reference_type: &'static str,
+    reference_locator: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationship {
+    spdx_element_id: String,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+fn spdx(root: &Unit, components: &BTreeMap<PackageId, Component<'_>>) -> CargoResult<SpdxDocument> {
+    // SPDX identifiers may only contain letters, numbers, `.` and `-`.
+    let spdx_ids: HashMap<PackageId, String> = components
+        .keys()
+        .enumerate()
+        .map(|(index, id)| {
+            let spdx_id = format!("SPDXRef-Package-{}-{}", id.name().replace('_', "-"), index);
+            (*id, spdx_id)
+        })
+        .collect();
+    let spdx_id = |id: PackageId| spdx_ids[&id].clone();
+    const NOASSERTION: &str = "NOASSERTION";
+
+    let root_id = root.pkg.package_id();
+    let root_spdx_id = spdx_id(root_id);
+    let packages = components
+        .values()
+        .map(|c| {
+            let id = c.pkg.package_id();
+            let metadata = c.pkg.manifest().metadata();
+            let mut comment = Vec::new();
+            if !c.features.is_empty() {
+                comment.push(format!("features: {}", features_string(&c.features)));
+            }
+            if c.build_only {
+                comment.push(String::from("only used at build time"));
+            }
+            SpdxPackage {
+                spdx_id: spdx_id(id),
+                name: id.name().to_string(),
+                version_info: id.version().to_string(),
+                download_location: id.source_id().as_url().to_string(),
+                files_analyzed: false,
+                license_concluded: NOASSERTION,
+                license_declared: metadata
+                    .license
+                    .clone()
+                    .unwrap_or_else(|| NOASSERTION.to_string()),
+                copyright_text: NOASSERTION,
+                checksums: c
+                    .pkg
+                    .summary()
+                    .checksum()
+                    .map(|cksum| SpdxChecksum {
+                        algorithm: "SHA256",
+                        checksum_value: cksum.to_string(),
+                    })
+                    .into_iter()
+                    .collect(),
+                external_refs: purl(id)
+                    .map(|purl| SpdxExternalRef {
+                        reference_category: "PACKAGE-MANAGER",
+                        reference_type: "purl",
+                        reference_locator: purl,
+                    })
+                    .into_iter()
+                    .collect(),
+                comment: if comment.is_empty() {
+                    None
+                } else {
+                    Some(comment.join("; "))
+                },
+            }
+        })
+        .collect();
+
+    let mut relationships = vec![SpdxRelationship {
+        spdx_element_id: String::from("SPDXRef-DOCUMENT"),
+        relationship_type: "DESCRIBES",
+        related_spdx_element: root_spdx_id.clone(),
+    }];
+    for c in components.values() {
+        for dep in c.deps.iter() {
+            let relationship_type = if components[dep].build_only {
+                "BUILD_DEPENDENCY_OF"
+            } else {
+                "DEPENDENCY_OF"
+            };
+            relationships.push(SpdxRelationship {
+                spdx_element_id: spdx_id(*dep),
+                relationship_type,
+                related_spdx_element: spdx_id(c.pkg.package_id()),
+            });
+        }
+    }
+
+    let mut doc = SpdxDocument {
+        spdx_version: "SPDX-2.2",
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: document_name(root),
+        document_namespace: String::new(),
+        creation_info: SpdxCreationInfo {
+            created: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            creators: vec![format!("Tool: {}", crate::version())],
+        },
+        document_describes: vec![root_spdx_id],
+        packages,
+        relationships,
+    };
+    // The namespace has to be unique for every document, including every
+    // version of the document for the same artifact. A hash of the contents
+    // covers that, since they include the creation time.
+    let hash = Sha256::new()
+        .update(&serde_json::to_vec(&doc)?)
+        .finish_hex();
+    doc.document_namespace = format!("https://spdx.org/spdxdocs/{}-{}", doc.name, hash);
+    Ok(doc)
+}
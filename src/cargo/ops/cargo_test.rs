@@ -63,9 +63,7 @@ pub fn run_benches(
 }
 
 fn compile_tests<'a>(ws: &Workspace<'a>, options: &TestOptions) -> CargoResult<Compilation<'a>> {
-    let mut compilation = ops::compile(ws, &options.compile_opts)?;
-    compilation.tests.sort();
-    Ok(compilation)
+    ops::compile(ws, &options.compile_opts)
 }
 
 /// Runs the unit and integration tests of a package.
@@ -145,6 +143,7 @@ fn run_doc_tests(
             unstable_opts,
             unit,
             linker,
+            env,
         } = doctest_info;
 
         if !doctest_xcompile {
@@ -161,6 +160,9 @@ fn run_doc_tests(
 
         config.shell().status("Doc-tests", unit.target.name())?;
         let mut p = compilation.rustdoc_process(unit)?;
+        for (var, value) in env {
+            p.env(var, value);
+        }
         p.arg("--test")
             .arg(unit.target.src_path().path().unwrap())
             .arg("--crate-name")
@@ -0,0 +1,211 @@
+//! Implementation of `cargo verify-lock`.
+//!
+//! This checks a committed `Cargo.lock` against a set of policies without
+//! building anything. Policies come from the `[lockfile]` config table and
+//! can be extended with command-line flags.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{PackageId, Source, SourceId, Workspace};
+use crate::ops;
+use crate::sources::{SourceConfigMap, CRATES_IO_REGISTRY};
+use crate::util::{CanonicalUrl, CargoResult};
+
+pub struct VerifyLockOptions {
+    /// Error on packages that are yanked from their registry.
+    pub deny_yanked: bool,
+    /// Error on packages that come from a git repository.
+    pub deny_git: bool,
+    /// Error on path packages that live outside of the workspace root.
+    pub deny_external_paths: bool,
+    /// Error on registry packages without a checksum.
+    pub require_checksums: bool,
+    /// Names of the registries packages may come from, in addition to the
+    /// `lockfile.allowed-registries` config value. Everything is allowed if
+    /// both are empty.
+    pub allowed_registries: Vec<String>,
+    /// Print the report as JSON on stdout instead of human readable errors.
+    pub json: bool,
+}
+
+/// The `[lockfile]` config table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LockfileConfig {
+    deny_yanked: Option<bool>,
+    deny_git: Option<bool>,
+    deny_external_paths: Option<bool>,
+    require_checksums: Option<bool>,
+    allowed_registries: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    success: bool,
+    violations: Vec<Violation>,
+}
+
+#[derive(Serialize)]
+struct Violation {
+    package_id: PackageId,
+    kind: ViolationKind,
+    message: String,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+enum ViolationKind {
+    Yanked,
+    GitSource,
+    ExternalPath,
+    MissingChecksum,
+    RegistryNotAllowed,
+}
+
+pub fn verify_lock(ws: &Workspace<'_>, opts: &VerifyLockOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let cfg = config
+        .get::<Option<LockfileConfig>>("lockfile")?
+        .unwrap_or_default();
+    let deny_yanked = opts.deny_yanked || cfg.deny_yanked.unwrap_or(false);
+    let deny_git = opts.deny_git || cfg.deny_git.unwrap_or(false);
+    let deny_external_paths = opts.deny_external_paths || cfg.deny_external_paths.unwrap_or(false);
+    let require_checksums = opts.require_checksums || cfg.require_checksums.unwrap_or(false);
+    let allowed_registries = cfg
+        .allowed_registries
+        .unwrap_or_default()
+        .iter()
+        .chain(opts.allowed_registries.iter())
+        .map(|name| registry_url(ws, name))
+        .collect::<CargoResult<BTreeSet<CanonicalUrl>>>()?;
+
+    let resolve = match ops::load_pkg_lockfile(ws)? {
+        Some(resolve) => resolve,
+        None => anyhow::bail!(
+            "no Cargo.lock file found in `{}`, run `cargo generate-lockfile` to create one",
+            ws.root().display()
+        ),
+    };
+
+    let mut violations = Vec::new();
+    let mut violation = |package_id, kind, message| {
+        violations.push(Violation {
+            package_id,
+            kind,
+            message,
+        })
+    };
+
+    // Group registry packages by source, so each registry is only loaded
+    // once for the yanked check.
+    let mut registry_pkgs: BTreeMap<SourceId, Vec<PackageId>> = BTreeMap::new();
+    let mut ids = resolve.iter().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        let source_id = id.source_id();
+        if source_id.is_registry() {
+            registry_pkgs.entry(source_id).or_default().push(id);
+            if require_checksums && !matches!(resolve.checksums().get(&id), Some(Some(_))) {
+                violation(
+                    id,
+                    ViolationKind::MissingChecksum,
+                    format!("package `{}` does not have a checksum", id),
+                );
+            }
+            if !allowed_registries.is_empty()
+                && !allowed_registries.contains(source_id.canonical_url())
+            {
+                violation(
+                    id,
+                    ViolationKind::RegistryNotAllowed,
+                    format!(
+                        "package `{}` comes from registry `{}` which is not allowed",
+                        id,
+                        source_id.display_registry_name()
+                    ),
+                );
+            }
+        } else if source_id.is_git() {
+            if deny_git {
+                violation(
+                    id,
+                    ViolationKind::GitSource,
+                    format!("package `{}` comes from a git repository", id),
+                );
+            }
+        } else if source_id.is_path() && deny_external_paths {
+            let inside_workspace = source_id
+                .url()
+                .to_file_path()
+                .map_or(false, |path| path.starts_with(ws.root()));
+            if !inside_workspace {
+                violation(
+                    id,
+                    ViolationKind::ExternalPath,
+                    format!(
+                        "package `{}` is a path dependency outside the workspace",
+                        id
+                    ),
+                );
+            }
+        }
+    }
+
+    if deny_yanked {
+        // Checking the yanked status involves taking a look at the registry and
+        // maybe updating files, so be sure to lock it here.
+        let _lock = config.acquire_package_cache_lock()?;
+        let source_config = SourceConfigMap::new(config)?;
+        for (source_id, pkgs) in registry_pkgs {
+            let mut source = source_config.load(source_id, &HashSet::new())?;
+            for id in pkgs {
+                if source.is_yanked(id)? {
+                    violation(
+                        id,
+                        ViolationKind::Yanked,
+                        format!(
+                            "package `{}` is yanked in registry `{}`",
+                            id,
+                            source_id.display_registry_name()
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let report = Report {
+        success: violations.is_empty(),
+        violations,
+    };
+    if opts.json {
+        config.shell().print_json(&report);
+    } else {
+        for v in report.violations.iter() {
+            config.shell().error(&v.message)?;
+        }
+    }
+    if !report.success {
+        anyhow::bail!(
+            "{} lock file policy violation{} found",
+            report.violations.len(),
+            if report.violations.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        );
+    }
+    Ok(())
+}
+
+fn registry_url(ws: &Workspace<'_>, name: &str) -> CargoResult<CanonicalUrl> {
+    let source_id = if name == CRATES_IO_REGISTRY {
+        SourceId::crates_io(ws.config())?
+    } else {
+        SourceId::alt_registry(ws.config(), name)?
+    };
+    Ok(source_id.canonical_url().clone())
+}
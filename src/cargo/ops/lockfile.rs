@@ -1,7 +1,12 @@
+use std::collections::BTreeSet;
 use std::io::prelude::*;
+use std::path::Path;
 
-use crate::core::{resolver, Resolve, ResolveVersion, Workspace};
+use termcolor::Color::Cyan;
+
+use crate::core::{resolver, PackageId, Resolve, ResolveVersion, Workspace};
 use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::interning::InternedString;
 use crate::util::toml as cargo_toml;
 use crate::util::Filesystem;
 
@@ -18,14 +23,91 @@ pub fn load_pkg_lockfile(ws: &Workspace<'_>) -> CargoResult<Option<Resolve>> {
         .chain_err(|| format!("failed to read file: {}", f.path().display()))?;
 
     let resolve = (|| -> CargoResult<Option<Resolve>> {
-        let resolve: toml::Value = cargo_toml::parse(&s, f.path(), ws.config())?;
-        let v: resolver::EncodableResolve = resolve.try_into()?;
-        Ok(Some(v.into_resolve(&s, ws)?))
+        if let Some((ours, theirs)) = split_merge_conflict(&s)? {
+            let ours = parse_resolve(&ours, f.path(), ws)
+                .chain_err(|| "failed to parse our side of the merge conflict")?;
+            let theirs = parse_resolve(&theirs, f.path(), ws)
+                .chain_err(|| "failed to parse their side of the merge conflict")?;
+            return Ok(Some(ours.merge_conflict_sides(theirs)?));
+        }
+        Ok(Some(parse_resolve(&s, f.path(), ws)?))
     })()
     .chain_err(|| format!("failed to parse lock file at: {}", f.path().display()))?;
     Ok(resolve)
 }
 
+fn parse_resolve(s: &str, path: &Path, ws: &Workspace<'_>) -> CargoResult<Resolve> {
+    let resolve: toml::Value = cargo_toml::parse(s, path, ws.config())?;
+    let v: resolver::EncodableResolve = resolve.try_into()?;
+    v.into_resolve(s, ws)
+}
+
+/// Splits the contents of a lock file containing git merge conflict markers
+/// into the "ours" and "theirs" versions of the file.
+///
+/// Returns `None` if there are no conflict markers. The common ancestor
+/// section written by `merge.conflictStyle = diff3` is discarded.
+fn split_merge_conflict(s: &str) -> CargoResult<Option<(String, String)>> {
+    enum Side {
+        Both,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    if !s.lines().any(|line| line.starts_with("<<<<<<<")) {
+        return Ok(None);
+    }
+
+    let mut ours = String::new();
+    let mut theirs = String::new();
+    let mut side = Side::Both;
+    for (i, line) in s.lines().enumerate() {
+        let unexpected = || {
+            anyhow::format_err!(
+                "unexpected merge conflict marker on line {}: `{}`",
+                i + 1,
+                line
+            )
+        };
+        side = match side {
+            Side::Both if line.starts_with("<<<<<<<") => Side::Ours,
+            Side::Ours if line.starts_with("|||||||") => Side::Base,
+            Side::Ours | Side::Base if line.starts_with("=======") => Side::Theirs,
+            Side::Theirs if line.starts_with(">>>>>>>") => Side::Both,
+            _ if ["<<<<<<<", "|||||||", "=======", ">>>>>>>"]
+                .iter()
+                .any(|marker| line.starts_with(marker)) =>
+            {
+                return Err(unexpected());
+            }
+            Side::Both => {
+                ours.push_str(line);
+                ours.push('\n');
+                theirs.push_str(line);
+                theirs.push('\n');
+                continue;
+            }
+            Side::Ours => {
+                ours.push_str(line);
+                ours.push('\n');
+                continue;
+            }
+            Side::Base => continue,
+            Side::Theirs => {
+                theirs.push_str(line);
+                theirs.push('\n');
+                continue;
+            }
+        };
+    }
+    if let Side::Both = side {
+        Ok(Some((ours, theirs)))
+    } else {
+        anyhow::bail!("unterminated merge conflict, expected a `>>>>>>>` marker")
+    }
+}
+
 /// Generate a toml String of Cargo.lock from a Resolve.
 pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoResult<String> {
     let (_orig, out, _ws_root) = resolve_to_string_orig(ws, resolve)?;
@@ -62,6 +144,12 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
         );
     }
 
+    if let Some(orig) = &orig {
+        if let Ok(Some((ours, theirs))) = split_merge_conflict(orig) {
+            report_merge_conflict_resolution(ws, &ours, &theirs, resolve)?;
+        }
+    }
+
     // While we're updating the lock file anyway go ahead and update its
     // encoding to whatever the latest default is. That way we can slowly roll
     // out lock file updates as they're otherwise already updated, and changes
@@ -84,6 +172,59 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &mut Resolve) -> CargoRes
     Ok(())
 }
 
+/// Tells the user which versions were picked for packages that differed
+/// between the two sides of a conflicted lock file.
+fn report_merge_conflict_resolution(
+    ws: &Workspace<'_>,
+    ours: &str,
+    theirs: &str,
+    resolve: &Resolve,
+) -> CargoResult<()> {
+    let path = ws.root().join("Cargo.lock");
+    let ids = |s: &str| -> CargoResult<BTreeSet<PackageId>> {
+        Ok(parse_resolve(s, &path, ws)?.iter().collect())
+    };
+    let ours = ids(ours)?;
+    let theirs = ids(theirs)?;
+    let versions = |set: &BTreeSet<PackageId>, name: InternedString| {
+        let versions: Vec<_> = set
+            .iter()
+            .filter(|id| id.name() == name)
+            .map(|id| format!("v{}", id.version()))
+            .collect();
+        if versions.is_empty() {
+            String::from("none")
+        } else {
+            versions.join(", ")
+        }
+    };
+
+    let mut shell = ws.config().shell();
+    shell.status(
+        "Merging",
+        format!("conflicted lock file {}", path.display()),
+    )?;
+    let chosen: BTreeSet<PackageId> = resolve.iter().collect();
+    let names: BTreeSet<InternedString> = ours
+        .symmetric_difference(&theirs)
+        .map(|id| id.name())
+        .collect();
+    for name in names {
+        shell.status_with_color(
+            "Selected",
+            format!(
+                "{} {} (ours: {}, theirs: {})",
+                name,
+                versions(&chosen, name),
+                versions(&ours, name),
+                versions(&theirs, name),
+            ),
+            Cyan,
+        )?;
+    }
+    Ok(())
+}
+
 fn resolve_to_string_orig(
     ws: &Workspace<'_>,
     resolve: &mut Resolve,
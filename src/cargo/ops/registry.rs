@@ -1,28 +1,30 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::iter::repeat;
 use std::str;
-use std::time::Duration;
-use std::{cmp, env};
+use std::time::{Duration, Instant};
+use std::{cmp, env, thread};
 
 use anyhow::{bail, format_err};
 use crates_io::{self, NewCrate, NewCrateDependency, Registry};
 use curl::easy::{Easy, InfoType, SslOpt, SslVersion};
 use log::{log, Level};
 use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use url::Url;
 
 use crate::core::dependency::DepKind;
 use crate::core::manifest::ManifestMetadata;
 use crate::core::source::Source;
-use crate::core::{Package, SourceId, Workspace};
+use crate::core::{Dependency, Package, PackageId, SourceId, Workspace};
 use crate::ops;
+use crate::ops::cargo_package::{self, package_with_patch};
 use crate::sources::{RegistrySource, SourceConfigMap, CRATES_IO_REGISTRY};
 use crate::util::config::{self, Config, SslVersionConfig, SslVersionConfigRange};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::important_paths::find_root_manifest_for_wd;
-use crate::util::IntoUrl;
 use crate::util::{paths, validate_package_name};
+use crate::util::{FileLock, IntoUrl};
 use crate::{drop_print, drop_println, version};
 
 /// Registry settings loaded from config files.
@@ -37,6 +39,7 @@ pub struct RegistryConfig {
 
 pub struct PublishOpts<'cfg> {
     pub config: &'cfg Config,
+    pub to_publish: ops::Packages,
     pub token: Option<String>,
     pub index: Option<String>,
     pub verify: bool,
@@ -51,7 +54,179 @@ pub struct PublishOpts<'cfg> {
 }
 
 pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
+    match opts.to_publish {
+        ops::Packages::Default => publish_current(ws, opts),
+        ops::Packages::Packages(ref specs) if specs.is_empty() => publish_current(ws, opts),
+        _ => publish_members(ws, opts),
+    }
+}
+
+fn publish_current(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
     let pkg = ws.current()?;
+    let publish_registry = select_registry(pkg, opts)?;
+
+    let (mut registry, reg_id) = registry(
+        opts.config,
+        opts.token.clone(),
+        opts.index.clone(),
+        publish_registry,
+        true,
+        !opts.dry_run,
+    )?;
+    verify_dependencies(pkg, &registry, reg_id)?;
+
+    // Prepare a tarball, with a non-suppressible warning if metadata
+    // is missing since this is being put online.
+    let tarball = ops::package(ws, &package_opts(opts))?.unwrap();
+
+    // Upload said tarball to the specified destination
+    opts.config
+        .shell()
+        .status("Uploading", pkg.package_id().to_string())?;
+    transmit(
+        opts.config,
+        pkg,
+        tarball.file(),
+        &mut registry,
+        reg_id,
+        opts.dry_run,
+    )?;
+
+    Ok(())
+}
+
+/// Publishes the selected members of the workspace, each after the members
+/// it depends on.
+///
+/// Every package is packaged and verified before anything is uploaded, with
+/// the members it depends on patched to their packaged versions since those
+/// aren't published yet. Before uploading a package, the index is polled
+/// until the members it depends on show up, so that the registry accepts it.
+fn publish_members(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
+    let config = opts.config;
+    let specs = opts.to_publish.to_package_id_specs(ws)?;
+    let explicit = matches!(opts.to_publish, ops::Packages::Packages(_));
+    let mut pkgs = Vec::new();
+    for pkg in ws.members() {
+        if !specs.iter().any(|spec| spec.matches(pkg.package_id())) {
+            continue;
+        }
+        // Members with `publish = false` are skipped, unless they're asked
+        // for by name.
+        let unpublishable = pkg.publish().as_ref().map_or(false, |regs| regs.is_empty());
+        if unpublishable && !explicit {
+            continue;
+        }
+        pkgs.push(pkg);
+    }
+    if explicit {
+        for spec in specs.iter() {
+            if !pkgs.iter().any(|pkg| spec.matches(pkg.package_id())) {
+                bail!("package `{}` is not a member of the workspace", spec);
+            }
+        }
+    }
+    if pkgs.is_empty() {
+        bail!("there are no packages in the workspace to publish");
+    }
+    let pkgs = publish_order(&pkgs)?;
+
+    let publish_registry = select_registry(pkgs[0], opts)?;
+    for pkg in &pkgs[1..] {
+        if select_registry(pkg, opts)? != publish_registry {
+            bail!(
+                "`{}` and `{}` are published to different registries\n\
+                 Publish them separately with `--package`.",
+                pkgs[0].name(),
+                pkg.name()
+            );
+        }
+    }
+    let (mut registry, reg_id) = registry(
+        config,
+        opts.token.clone(),
+        opts.index.clone(),
+        publish_registry,
+        true,
+        !opts.dry_run,
+    )?;
+    for pkg in &pkgs {
+        verify_dependencies(pkg, &registry, reg_id)?;
+    }
+
+    let mut tarballs = HashMap::new();
+    for pkg in &pkgs {
+        let patch = verification_patch(ws, pkg, &pkgs, &tarballs, opts.verify)?;
+        let tarball = package_member(ws, pkg, &package_opts(opts), &patch)?;
+        tarballs.insert(pkg.package_id(), tarball);
+    }
+
+    let mut available = HashSet::new();
+    for (i, pkg) in pkgs.iter().enumerate() {
+        let result = (|| -> CargoResult<()> {
+            let deps = member_deps(pkg, &pkgs[..i]);
+            if !opts.dry_run {
+                for (_, dep) in deps.iter() {
+                    if available.insert(dep.package_id()) {
+                        wait_for_publish(config, reg_id, dep)?;
+                    }
+                }
+            }
+            let mut tarball = tarballs.remove(&pkg.package_id()).unwrap();
+            // The packaged `Cargo.lock` was generated against the packaged
+            // members, so it's generated again against the registry now that
+            // they are published. The package was already verified.
+            if pkg.include_lockfile() && !deps.is_empty() && !opts.dry_run {
+                let pkg_opts = ops::PackageOpts {
+                    verify: false,
+                    ..package_opts(opts)
+                };
+                tarball = package_member(ws, pkg, &pkg_opts, &HashMap::new())?;
+            }
+            config
+                .shell()
+                .status("Uploading", pkg.package_id().to_string())?;
+            transmit(
+                config,
+                pkg,
+                tarball.file(),
+                &mut registry,
+                reg_id,
+                opts.dry_run,
+            )?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            if i > 0 && !opts.dry_run {
+                let names = |pkgs: &[&Package]| {
+                    pkgs.iter()
+                        .map(|pkg| format!("`{} v{}`", pkg.name(), pkg.version()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                config.shell().note(format!(
+                    "these packages were published: {}",
+                    names(&pkgs[..i])
+                ))?;
+                config.shell().note(format!(
+                    "these packages were not published: {}",
+                    names(&pkgs[i..])
+                ))?;
+            }
+            return Err(e.context(format!(
+                "failed to publish `{} v{}`",
+                pkg.name(),
+                pkg.version()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Picks the registry to publish `pkg` to, checking that it's allowed by the
+/// `publish` field of its manifest.
+fn select_registry(pkg: &Package, opts: &PublishOpts<'_>) -> CargoResult<Option<String>> {
     let mut publish_registry = opts.registry.clone();
 
     if let Some(ref allowed_registries) = *pkg.publish() {
@@ -83,49 +258,175 @@ pub fn publish(ws: &Workspace<'_>, opts: &PublishOpts<'_>) -> CargoResult<()> {
         }
     }
 
-    let (mut registry, reg_id) = registry(
-        opts.config,
-        opts.token.clone(),
-        opts.index.clone(),
-        publish_registry,
-        true,
-        !opts.dry_run,
-    )?;
-    verify_dependencies(pkg, &registry, reg_id)?;
+    Ok(publish_registry)
+}
 
-    // Prepare a tarball, with a non-suppressible warning if metadata
-    // is missing since this is being put online.
-    let tarball = ops::package(
-        ws,
-        &ops::PackageOpts {
-            config: opts.config,
-            verify: opts.verify,
-            list: false,
-            check_metadata: true,
-            allow_dirty: opts.allow_dirty,
-            targets: opts.targets.clone(),
-            jobs: opts.jobs,
-            features: opts.features.clone(),
-            all_features: opts.all_features,
-            no_default_features: opts.no_default_features,
-        },
-    )?
-    .unwrap();
+fn package_opts<'cfg>(opts: &PublishOpts<'cfg>) -> ops::PackageOpts<'cfg> {
+    ops::PackageOpts {
+        config: opts.config,
+        verify: opts.verify,
+        list: false,
+        check_metadata: true,
+        allow_dirty: opts.allow_dirty,
+        targets: opts.targets.clone(),
+        jobs: opts.jobs,
+        features: opts.features.clone(),
+        all_features: opts.all_features,
+        no_default_features: opts.no_default_features,
+    }
+}
 
-    // Upload said tarball to the specified destination
-    opts.config
-        .shell()
-        .status("Uploading", pkg.package_id().to_string())?;
-    transmit(
-        opts.config,
-        pkg,
-        tarball.file(),
-        &mut registry,
-        reg_id,
-        opts.dry_run,
-    )?;
+/// Packages the workspace member `pkg`, verifying it with `patch`.
+fn package_member(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    opts: &ops::PackageOpts<'_>,
+    patch: &HashMap<Url, Vec<Dependency>>,
+) -> CargoResult<FileLock> {
+    let mut member_ws = Workspace::new(pkg.manifest_path(), opts.config)?;
+    member_ws.set_target_dir(ws.target_dir());
+    Ok(package_with_patch(&member_ws, opts, patch)?.unwrap())
+}
 
-    Ok(())
+/// The dependencies of `pkg` on the packages in `pkgs`, which will be
+/// published along with it.
+fn member_deps<'a, 'b>(
+    pkg: &'a Package,
+    pkgs: &[&'b Package],
+) -> Vec<(&'a Dependency, &'b Package)> {
+    pkg.dependencies()
+        .iter()
+        .filter(|dep| dep.specified_req())
+        .filter_map(|dep| {
+            let member = pkgs.iter().find(|pkg| dep.matches_id(pkg.package_id()))?;
+            Some((dep, *member))
+        })
+        .collect()
+}
+
+/// Sorts `pkgs` so that each package comes after the members it depends on.
+/// Dev-dependencies don't count, since they may form cycles.
+fn publish_order<'a>(pkgs: &[&'a Package]) -> CargoResult<Vec<&'a Package>> {
+    let mut remaining = pkgs.to_vec();
+    remaining.sort_by_key(|pkg| pkg.name());
+    let mut sorted = Vec::new();
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|pkg| {
+            member_deps(pkg, &remaining)
+                .iter()
+                .all(|(dep, _)| dep.kind() == DepKind::Development)
+        });
+        match ready {
+            Some(i) => sorted.push(remaining.remove(i)),
+            None => bail!(
+                "cannot publish the packages {}, which depend on each other in a cycle",
+                remaining
+                    .iter()
+                    .map(|pkg| format!("`{}`", pkg.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+    Ok(sorted)
+}
+
+/// The `[patch]` entries pointing the members `pkg` depends on, directly or
+/// through other members, to their packaged versions in `tarballs`.
+///
+/// Verifying a package unpacks it next to its tarball; if the packages
+/// weren't `verified`, they are unpacked here.
+fn verification_patch(
+    ws: &Workspace<'_>,
+    pkg: &Package,
+    pkgs: &[&Package],
+    tarballs: &HashMap<PackageId, FileLock>,
+    verified: bool,
+) -> CargoResult<HashMap<Url, Vec<Dependency>>> {
+    let mut patch: HashMap<Url, Vec<Dependency>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = member_deps(pkg, pkgs);
+    while let Some((dep, member)) = queue.pop() {
+        if !seen.insert(member.package_id()) {
+            continue;
+        }
+        let tarball = match tarballs.get(&member.package_id()) {
+            Some(tarball) => tarball,
+            None => continue,
+        };
+        let unpacked = if verified {
+            tarball
+                .parent()
+                .join(format!("{}-{}", member.name(), member.version()))
+        } else {
+            cargo_package::unpack(tarball, member)?
+        };
+        let version = format!("={}", member.version());
+        let patched = Dependency::parse_no_deprecated(
+            member.name(),
+            Some(&version),
+            SourceId::for_path(&unpacked)?,
+        )?;
+        // Path dependencies are published as dependencies on the registry
+        // in their `registry` field, or crates.io.
+        let url = match dep.registry_id() {
+            Some(id) => id.url().clone(),
+            None => SourceId::crates_io(ws.config())?.url().clone(),
+        };
+        patch.entry(url).or_default().push(patched);
+        queue.extend(
+            member_deps(member, pkgs)
+                .into_iter()
+                .filter(|(dep, _)| dep.kind() != DepKind::Development),
+        );
+    }
+    Ok(patch)
+}
+
+/// How long to wait for a published package to show up in the index.
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Polls the index of the registry `reg_id` until the just published `pkg`
+/// is in it.
+fn wait_for_publish(config: &Config, reg_id: SourceId, pkg: &Package) -> CargoResult<()> {
+    let version = format!("={}", pkg.version());
+    let dep = Dependency::parse_no_deprecated(pkg.name(), Some(&version), reg_id)?;
+    let started = Instant::now();
+    let mut waiting = false;
+    loop {
+        {
+            let _lock = config.acquire_package_cache_lock()?;
+            // The index is normally only fetched once per session.
+            config.updated_sources().remove(&reg_id);
+            let mut src = RegistrySource::remote(reg_id, &HashSet::new(), config);
+            src.update()
+                .chain_err(|| format!("failed to update {}", reg_id))?;
+            if !src.query_vec(&dep)?.is_empty() {
+                return Ok(());
+            }
+        }
+        if started.elapsed() >= PUBLISH_TIMEOUT {
+            bail!(
+                "timed out waiting for `{} v{}` to be available in {}",
+                pkg.name(),
+                pkg.version(),
+                reg_id.display_registry_name()
+            );
+        }
+        if !waiting {
+            config.shell().status(
+                "Waiting",
+                format!(
+                    "for `{} v{}` to be available in {}",
+                    pkg.name(),
+                    pkg.version(),
+                    reg_id.display_registry_name()
+                ),
+            )?;
+            waiting = true;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
 }
 
 fn verify_dependencies(
@@ -368,6 +669,73 @@ pub fn registry_configuration(
     Ok(RegistryConfig { index, token })
 }
 
+/// Returns the token to send in the `Authorization` header to the registry
+/// `sid`, for registries with `auth-required` set in their `config.json`.
+///
+/// The token is looked up the same way as for `cargo publish`: crates.io and
+/// its replacement use `registry.token`, and other registries are matched by
+/// their index URL against the `[registries]` table.
+pub fn registry_auth_token(config: &Config, sid: SourceId) -> CargoResult<String> {
+    let name = match registry_name(config, sid)? {
+        Some(name) => name,
+        None => bail!(
+            "{} requires authentication, but is not one of the registries \
+             configured in `[registries]`\n\
+             Add it to `[registries]` along with a token to download from it.",
+            sid.display_registry_name()
+        ),
+    };
+    if name == CRATES_IO_REGISTRY {
+        match config.registry_token(None)? {
+            Some(token) => Ok(token),
+            None => bail!(
+                "no token found for {}, which requires authentication\n\
+                 Run `cargo login` to add a token.",
+                sid.display_registry_name()
+            ),
+        }
+    } else {
+        match config.registry_token(Some(&name))? {
+            Some(token) => Ok(token),
+            None => bail!(
+                "no token found for registry `{}`, which requires authentication\n\
+                 Run `cargo login --registry {}` to add a token.",
+                name,
+                name
+            ),
+        }
+    }
+}
+
+/// Returns whether `registry.require-signatures`, or
+/// `registries.<name>.require-signatures` for the registry `sid`, is set.
+pub fn registry_requires_signatures(config: &Config, sid: SourceId) -> CargoResult<bool> {
+    let key = match registry_name(config, sid)? {
+        Some(name) if name == CRATES_IO_REGISTRY => "registry.require-signatures".to_string(),
+        Some(name) => format!("registries.{}.require-signatures", name),
+        None => return Ok(false),
+    };
+    Ok(config.get::<Option<bool>>(&key)?.unwrap_or(false))
+}
+
+/// Finds the name of the registry `sid` in the `[registries]` table, which is
+/// `crates-io` for crates.io and its replacement. Returns `None` for
+/// registries which aren't configured.
+fn registry_name(config: &Config, sid: SourceId) -> CargoResult<Option<String>> {
+    if sid.is_default_registry() || get_source_id(config, None, None)? == sid {
+        return Ok(Some(CRATES_IO_REGISTRY.to_string()));
+    }
+    let registries = config
+        .get::<Option<BTreeMap<String, toml::Value>>>("registries")?
+        .unwrap_or_default();
+    for name in registries.keys() {
+        if SourceId::alt_registry(config, name)? == sid {
+            return Ok(Some(name.clone()));
+        }
+    }
+    Ok(None)
+}
+
 /// Returns the `Registry` and `Source` based on command-line and config settings.
 ///
 /// * `token`: The token from the command-line. If not set, uses the token
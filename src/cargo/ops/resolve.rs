@@ -11,18 +11,19 @@
 //!   providing the most power and flexibility.
 
 use crate::core::compiler::{CompileKind, RustcTargetData};
-use crate::core::registry::PackageRegistry;
+use crate::core::dependency::DepKind;
+use crate::core::registry::{PackageRegistry, Registry};
 use crate::core::resolver::features::{FeatureResolver, ForceAllTargets, ResolvedFeatures};
 use crate::core::resolver::{self, HasDevUnits, Resolve, ResolveOpts};
 use crate::core::summary::Summary;
 use crate::core::Feature;
-use crate::core::{PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
+use crate::core::{Dependency, PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
 use crate::ops;
 use crate::sources::PathSource;
 use crate::util::errors::{CargoResult, CargoResultExt};
-use crate::util::{profile, CanonicalUrl};
+use crate::util::{profile, CanonicalUrl, Config};
 use log::{debug, trace};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 /// Result for `resolve_ws_with_opts`.
 pub struct WorkspaceResolve<'cfg> {
@@ -57,7 +58,8 @@ version. This may also occur with an optional dependency that is not enabled.";
 /// `package`, which don't specify any options or features.
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
     let mut registry = PackageRegistry::new(ws.config())?;
-    let resolve = resolve_with_registry(ws, &mut registry)?;
+    let prev = ops::load_pkg_lockfile(ws)?;
+    let resolve = resolve_with_registry(ws, &mut registry, prev.as_ref())?;
     let packages = get_resolved_packages(&resolve, registry)?;
     Ok((packages, resolve))
 }
@@ -83,12 +85,14 @@ pub fn resolve_ws_with_opts<'cfg>(
 ) -> CargoResult<WorkspaceResolve<'cfg>> {
     let mut registry = PackageRegistry::new(ws.config())?;
     let mut add_patches = true;
+    let mut prev = None;
     let resolve = if ws.ignore_lock() {
         None
     } else if ws.require_optional_deps() {
         // First, resolve the root_package's *listed* dependencies, as well as
         // downloading and updating all remotes and such.
-        let resolve = resolve_with_registry(ws, &mut registry)?;
+        prev = ops::load_pkg_lockfile(ws)?;
+        let resolve = resolve_with_registry(ws, &mut registry, prev.as_ref())?;
         // No need to add patches again, `resolve_with_registry` has done it.
         add_patches = false;
 
@@ -125,6 +129,33 @@ pub fn resolve_ws_with_opts<'cfg>(
         add_patches,
     )?;
 
+    let config = ws.config();
+    if config.offline() || config.net_config()?.prefer_offline == Some(true) {
+        // The dependencies of the targeted resolve are locked to the
+        // workspace resolve, so the requirements are taken from the latter.
+        // Without `require_optional_deps` the lock file is used as is.
+        let (prev, requested) = if ws.require_optional_deps() {
+            (prev.as_ref(), resolve.as_ref())
+        } else {
+            (resolve.as_ref(), None)
+        };
+        let is_used = |dep: &Dependency| {
+            (dep.kind() != DepKind::Development || has_dev_units == HasDevUnits::Yes)
+                && requested_targets
+                    .iter()
+                    .chain(Some(&CompileKind::Host))
+                    .any(|kind| target_data.dep_platform_activated(dep, *kind))
+        };
+        note_downloaded_versions(
+            config,
+            &mut registry,
+            &resolved_with_overrides,
+            requested.unwrap_or(&resolved_with_overrides),
+            prev,
+            &is_used,
+        )?;
+    }
+
     let pkg_set = get_resolved_packages(&resolved_with_overrides, registry)?;
 
     let member_ids = ws
@@ -163,13 +194,13 @@ pub fn resolve_ws_with_opts<'cfg>(
 fn resolve_with_registry<'cfg>(
     ws: &Workspace<'cfg>,
     registry: &mut PackageRegistry<'cfg>,
+    prev: Option<&Resolve>,
 ) -> CargoResult<Resolve> {
-    let prev = ops::load_pkg_lockfile(ws)?;
     let mut resolve = resolve_with_previous(
         registry,
         ws,
         &ResolveOpts::everything(),
-        prev.as_ref(),
+        prev,
         None,
         &[],
         true,
@@ -209,6 +240,12 @@ pub fn resolve_with_previous<'cfg>(
     // involve a lot of frobbing of the global caches.
     let _lock = ws.config().acquire_package_cache_lock()?;
 
+    // A previous resolve which was assembled from both sides of a conflicted
+    // lock file only serves as a set of preferred versions. Nothing in it is
+    // locked, so the resolver is free to pick whichever side's versions
+    // satisfy the current manifests.
+    let locked_previous = previous.filter(|r| !r.is_merge_conflict());
+
     // Here we place an artificial limitation that all non-registry sources
     // cannot be locked at more than one revision. This means that if a Git
     // repository provides more than one package, they must all be updated in
@@ -238,7 +275,7 @@ pub fn resolve_with_previous<'cfg>(
     let mut avoid_patch_ids = HashSet::new();
     if register_patches {
         for (url, patches) in ws.root_patch() {
-            let previous = match previous {
+            let previous = match locked_previous {
                 Some(r) => r,
                 None => {
                     let patches: Vec<_> = patches.iter().map(|p| (p, None)).collect();
@@ -285,7 +322,7 @@ pub fn resolve_with_previous<'cfg>(
     // In the case where a previous instance of resolve is available, we
     // want to lock as many packages as possible to the previous version
     // without disturbing the graph structure.
-    if let Some(r) = previous {
+    if let Some(r) = locked_previous {
         trace!("previous: {:?}", r);
         register_previous_locks(ws, registry, r, &keep);
     }
@@ -328,7 +365,7 @@ pub fn resolve_with_previous<'cfg>(
 
     let root_replace = ws.root_replace();
 
-    let replace = match previous {
+    let replace = match locked_previous {
         Some(r) => root_replace
             .iter()
             .map(|&(ref spec, ref dep)| {
@@ -379,6 +416,60 @@ pub fn resolve_with_previous<'cfg>(
     Ok(resolved)
 }
 
+/// Notes the packages of `resolve` from registries where a downloaded version
+/// was selected while a newer version matching the same requirements isn't
+/// downloaded, which is what would have been used online.
+///
+/// The requirements are the dependencies in `requested` for which `is_used`
+/// returns `true`. Packages from the previous lock file are skipped, as they
+/// would have been kept online as well.
+fn note_downloaded_versions(
+    config: &Config,
+    registry: &mut PackageRegistry<'_>,
+    resolve: &Resolve,
+    requested: &Resolve,
+    prev: Option<&Resolve>,
+    is_used: &dyn Fn(&Dependency) -> bool,
+) -> CargoResult<()> {
+    let _lock = config.acquire_package_cache_lock()?;
+    let mut reqs: BTreeMap<PackageId, Vec<&Dependency>> = BTreeMap::new();
+    for parent in requested.iter().filter(|p| resolve.contains(p)) {
+        for (id, deps) in requested.deps_not_replaced(parent) {
+            if !id.source_id().is_registry()
+                || !resolve.contains(&id)
+                || prev.map_or(false, |p| p.contains(&id))
+            {
+                continue;
+            }
+            let used = deps.iter().filter(|dep| is_used(dep));
+            reqs.entry(id).or_default().extend(used);
+        }
+    }
+    for (id, deps) in reqs {
+        if deps.is_empty() || !registry.is_crate_downloaded(id) {
+            continue;
+        }
+        let newest = registry
+            .query_online(deps[0])?
+            .into_iter()
+            .map(|s| s.package_id())
+            .filter(|&candidate| deps.iter().all(|d| d.matches_ignoring_source(candidate)))
+            .max_by(|a, b| a.version().cmp(b.version()));
+        if let Some(newest) = newest {
+            if newest.version() > id.version() && !registry.is_crate_downloaded(newest) {
+                config.shell().note(format!(
+                    "using the downloaded version v{} of `{}` \
+                     instead of v{}, which is not downloaded",
+                    id.version(),
+                    id.name(),
+                    newest.version()
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Read the `paths` configuration variable to discover all path overrides that
 /// have been configured.
 pub fn add_overrides<'a>(
@@ -1,21 +1,35 @@
+use crate::core::compiler::{CompileKind, CompileTarget, RustcTargetData};
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::ResolveOpts;
 use crate::core::shell::Verbosity;
-use crate::core::{GitReference, Workspace};
+use crate::core::{
+    GitReference, GitSubmodules, Package, PackageId, PackageSet, Resolve, Workspace,
+};
 use crate::ops;
 use crate::sources::path::PathSource;
+use crate::util::toml::VecStringOrBool;
 use crate::util::Sha256;
 use crate::util::{paths, CargoResult, CargoResultExt, Config};
 use anyhow::bail;
-use serde::Serialize;
+use semver::Version;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use termcolor::Color::{self, Cyan, Green, Red};
 
 pub struct VendorOptions<'a> {
     pub no_delete: bool,
     pub versioned_dirs: bool,
     pub destination: &'a Path,
     pub extra: Vec<PathBuf>,
+    /// Only vendor the sources of packages used on these target triples,
+    /// packages which aren't are replaced with stubs.
+    pub platforms: Vec<String>,
+    /// Only check that the vendor directory is up to date, without changing
+    /// it.
+    pub check: bool,
 }
 
 pub fn vendor(ws: &Workspace<'_>, opts: &VendorOptions<'_>) -> CargoResult<()> {
@@ -30,7 +44,7 @@ pub fn vendor(ws: &Workspace<'_>, opts: &VendorOptions<'_>) -> CargoResult<()> {
     let vendor_config =
         sync(config, &workspaces, opts).chain_err(|| "failed to sync".to_string())?;
 
-    if config.shell().verbosity() != Verbosity::Quiet {
+    if !opts.check && config.shell().verbosity() != Verbosity::Quiet {
         crate::drop_eprint!(
             config,
             "To use vendored sources, add this to your .cargo/config for this project:\n\n"
@@ -62,6 +76,7 @@ enum VendorSource {
         branch: Option<String>,
         tag: Option<String>,
         rev: Option<String>,
+        submodules: Option<VecStringOrBool>,
         #[serde(rename = "replace-with")]
         replace_with: String,
     },
@@ -78,19 +93,15 @@ fn sync(
         .map(|p| &**p)
         .unwrap_or(opts.destination);
 
-    paths::create_dir_all(&canonical_destination)?;
+    if !opts.check {
+        paths::create_dir_all(&canonical_destination)?;
+    }
+    // Only packages which were vendored before are removed, anything else in
+    // the vendor directory, like patches, is left alone.
+    let vendored = vendored_packages(canonical_destination)?;
     let mut to_remove = HashSet::new();
     if !opts.no_delete {
-        for entry in canonical_destination.read_dir()? {
-            let entry = entry?;
-            if !entry
-                .file_name()
-                .to_str()
-                .map_or(false, |s| s.starts_with('.'))
-            {
-                to_remove.insert(entry.path());
-            }
-        }
+        to_remove.extend(vendored.keys().cloned());
     }
 
     // First up attempt to work around rust-lang/cargo#5956. Apparently build
@@ -104,7 +115,7 @@ fn sync(
     // Note that errors are largely ignored here as this is a best-effort
     // attempt. If anything fails here we basically just move on to the next
     // crate to work with.
-    for ws in workspaces {
+    for ws in workspaces.iter().filter(|_| !opts.check) {
         let (packages, resolve) =
             ops::resolve_ws(ws).chain_err(|| "failed to load pkg lockfile")?;
 
@@ -133,17 +144,27 @@ fn sync(
 
     let mut checksums = HashMap::new();
     let mut ids = BTreeMap::new();
+    let mut used = HashSet::new();
 
     // Next up let's actually download all crates and start storing internal
     // tables about them.
     for ws in workspaces {
         let (packages, resolve) =
-            ops::resolve_ws(ws).chain_err(|| "failed to load pkg lockfile")?;
+            resolve_ws(ws, opts.check).chain_err(|| "failed to load pkg lockfile")?;
 
         packages
             .get_many(resolve.iter())
             .chain_err(|| "failed to download packages")?;
 
+        if !opts.platforms.is_empty() {
+            let kinds = opts
+                .platforms
+                .iter()
+                .map(|p| Ok(CompileKind::Target(CompileTarget::new(p)?)))
+                .collect::<CargoResult<Vec<_>>>()?;
+            used.extend(used_on_platforms(ws, &resolve, &kinds)?);
+        }
+
         for pkg in resolve.iter() {
             // No need to vendor path crates since they're already in the
             // repository
@@ -182,6 +203,8 @@ fn sync(
     }
 
     let mut sources = BTreeSet::new();
+    let mut out_of_date = Vec::new();
+    let mut dsts = HashSet::new();
     for (id, pkg) in ids.iter() {
         // Next up, copy it to the vendor directory
         let src = pkg
@@ -201,41 +224,108 @@ fn sync(
         sources.insert(id.source_id());
         let dst = canonical_destination.join(&dst_name);
         to_remove.remove(&dst);
+        dsts.insert(dst.clone());
         let cksum = dst.join(".cargo-checksum.json");
-        if dir_has_version_suffix && cksum.exists() {
-            // Always re-copy directory without version suffix in case the version changed
+        let stub = !opts.platforms.is_empty() && !used.contains(id);
+        let files = if stub {
+            stub_files(pkg, src)
+        } else {
+            let pathsource = PathSource::new(src, id.source_id(), config);
+            source_files(src, &pathsource.list_files(pkg)?)
+        };
+
+        // Packages are only copied again if they changed, which is known from
+        // the checksum of the package, or of its files if it has none (like
+        // git dependencies). Vendored files must also still match their
+        // checksums, or the directory source refuses to use them, so local
+        // edits are overwritten as well.
+        let prev = VendoredChecksum::read(&cksum);
+        let up_to_date = match &prev {
+            Some(prev) if prev.stub == stub => match checksums[id].clone().flatten() {
+                Some(package) => prev.package == Some(package),
+                None => prev.package.is_none() && prev.files == hash_files(&files)?,
+            },
+            _ => false,
+        };
+        let modified = match &prev {
+            Some(prev) if up_to_date => prev.modified_files(&dst)?,
+            _ => Vec::new(),
+        };
+        if opts.check {
+            if !up_to_date {
+                out_of_date.push(format!("{} is missing or out of date", id));
+            }
+            for file in modified {
+                out_of_date.push(format!("`{}` of {} was modified", file, id));
+            }
+            continue;
+        }
+        if up_to_date && modified.is_empty() {
             continue;
         }
-
-        config.shell().status(
-            "Vendoring",
-            &format!("{} ({}) to {}", id, src.to_string_lossy(), dst.display()),
-        )?;
 
         let _ = fs::remove_dir_all(&dst);
-        let pathsource = PathSource::new(src, id.source_id(), config);
-        let paths = pathsource.list_files(pkg)?;
-        let mut map = BTreeMap::new();
-        cp_sources(src, &paths, &dst, &mut map)
+        if stub {
+            config.shell().status(
+                "Stubbing",
+                &format!("{} (not used on {})", id, opts.platforms.join(", ")),
+            )?;
+        } else {
+            config.shell().status(
+                "Vendoring",
+                &format!("{} ({}) to {}", id, src.to_string_lossy(), dst.display()),
+            )?;
+        }
+        let map = cp_files(&files, &dst)
             .chain_err(|| format!("failed to copy over vendored sources for: {}", id))?;
 
         // Finally, emit the metadata about this package
-        let json = serde_json::json!({
+        let mut json = serde_json::json!({
             "package": checksums.get(id),
             "files": map,
         });
+        if stub {
+            json["stub"] = serde_json::json!(true);
+        }
 
         paths::write(&cksum, json.to_string())?;
     }
 
-    for path in to_remove {
-        if path.is_dir() {
+    for path in to_remove.iter() {
+        if opts.check {
+            out_of_date.push(format!("`{}` is no longer used", path.display()));
+        } else if path.is_dir() {
             paths::remove_dir_all(&path)?;
         } else {
             paths::remove_file(&path)?;
         }
     }
 
+    // Summarize what changed, based on the packages in the vendor directory.
+    // With `--check` nothing changed, the differences are reported below.
+    if !opts.check {
+        let before = vendored.values().cloned().collect::<BTreeSet<_>>();
+        let after = vendored
+            .iter()
+            .filter(|(path, _)| !to_remove.contains(*path) && !dsts.contains(*path))
+            .map(|(_, package)| package.clone())
+            .chain(
+                ids.keys()
+                    .map(|id| (id.name().to_string(), id.version().clone())),
+            )
+            .collect::<BTreeSet<_>>();
+        print_changes(config, &before, &after)?;
+    }
+    if !out_of_date.is_empty() {
+        out_of_date.sort();
+        bail!(
+            "the vendor directory `{}` is not up to date:\n  {}\n\
+             Run `cargo vendor` without `--check` to update it.",
+            opts.destination.display(),
+            out_of_date.join("\n  ")
+        );
+    }
+
     // add our vendored source
     let mut config = BTreeMap::new();
 
@@ -283,6 +373,11 @@ fn sync(
                 branch,
                 tag,
                 rev,
+                submodules: source_id.git_submodules().map(|s| match s {
+                    GitSubmodules::All => VecStringOrBool::Bool(true),
+                    GitSubmodules::None => VecStringOrBool::Bool(false),
+                    GitSubmodules::Paths(paths) => VecStringOrBool::VecString(paths.clone()),
+                }),
                 replace_with: merged_source_name.to_string(),
             }
         } else {
@@ -294,12 +389,183 @@ fn sync(
     Ok(VendorConfig { source: config })
 }
 
-fn cp_sources(
-    src: &Path,
-    paths: &[PathBuf],
-    dst: &Path,
-    cksums: &mut BTreeMap<String, String>,
+/// Resolves the dependencies of `ws` like `ops::resolve_ws` does. With
+/// `check` the lock file is left alone, even if it's out of date.
+fn resolve_ws<'a>(ws: &Workspace<'a>, check: bool) -> CargoResult<(PackageSet<'a>, Resolve)> {
+    if !check {
+        return ops::resolve_ws(ws);
+    }
+    let mut registry = PackageRegistry::new(ws.config())?;
+    let prev = ops::load_pkg_lockfile(ws)?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        &ResolveOpts::everything(),
+        prev.as_ref(),
+        None,
+        &[],
+        true,
+    )?;
+    let packages = ops::get_resolved_packages(&resolve, registry)?;
+    Ok((packages, resolve))
+}
+
+/// Returns the packages used when building the workspace for any of
+/// `kinds`, in the same way as `cargo fetch --target` does.
+pub(super) fn used_on_platforms(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    kinds: &[CompileKind],
+) -> CargoResult<HashSet<PackageId>> {
+    let data = RustcTargetData::new(ws, kinds)?;
+    let mut used = HashSet::new();
+    let mut to_visit = ws.members().map(|p| p.package_id()).collect::<Vec<_>>();
+    while let Some(id) = to_visit.pop() {
+        if !used.insert(id) {
+            continue;
+        }
+        let deps = resolve
+            .deps(id)
+            .filter(|&(_id, deps)| {
+                deps.iter().any(|d| {
+                    kinds
+                        .iter()
+                        .any(|kind| data.dep_platform_activated(d, *kind))
+                })
+            })
+            .map(|(id, _deps)| id);
+        to_visit.extend(deps);
+    }
+    Ok(used)
+}
+
+/// The parts of a `.cargo-checksum.json` which tell whether a vendored
+/// package is up to date.
+#[derive(Deserialize)]
+struct VendoredChecksum {
+    package: Option<String>,
+    #[serde(default)]
+    files: BTreeMap<String, String>,
+    #[serde(default)]
+    stub: bool,
+}
+
+impl VendoredChecksum {
+    fn read(cksum: &Path) -> Option<VendoredChecksum> {
+        let contents = paths::read(cksum).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Returns the files of the package vendored in `dir` which are missing
+    /// or don't match their checksum.
+    fn modified_files(&self, dir: &Path) -> CargoResult<Vec<String>> {
+        let mut modified = Vec::new();
+        for (file, cksum) in self.files.iter() {
+            let path = file.split('/').fold(dir.to_owned(), |acc, c| acc.join(c));
+            let actual = if path.is_file() {
+                Some(Sha256::new().update_path(&path)?.finish_hex())
+            } else {
+                None
+            };
+            if actual.as_ref() != Some(cksum) {
+                modified.push(file.clone());
+            }
+        }
+        Ok(modified)
+    }
+}
+
+/// Returns the name and version of each package vendored in `dir`, keyed by
+/// its directory. Directories without a `.cargo-checksum.json` weren't
+/// vendored by Cargo and are left out.
+fn vendored_packages(dir: &Path) -> CargoResult<BTreeMap<PathBuf, (String, Version)>> {
+    let mut packages = BTreeMap::new();
+    let entries = match dir.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return Ok(packages),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if !path.join(".cargo-checksum.json").is_file() {
+            continue;
+        }
+        let manifest = match paths::read(&path.join("Cargo.toml")) {
+            Ok(manifest) => manifest,
+            Err(_) => continue,
+        };
+        let package = manifest
+            .parse::<toml::Value>()
+            .ok()
+            .and_then(|toml| toml.get("package").cloned());
+        let name = package.as_ref().and_then(|p| p.get("name")?.as_str());
+        let version = package
+            .as_ref()
+            .and_then(|p| p.get("version")?.as_str()?.parse::<Version>().ok());
+        if let (Some(name), Some(version)) = (name, version) {
+            packages.insert(path, (name.to_string(), version));
+        }
+    }
+    Ok(packages)
+}
+
+/// Prints the packages which are added to, removed from, and updated in the
+/// vendor directory, like `cargo update` does for the lock file.
+fn print_changes(
+    config: &Config,
+    before: &BTreeSet<(String, Version)>,
+    after: &BTreeSet<(String, Version)>,
 ) -> CargoResult<()> {
+    let mut changes = BTreeMap::new();
+    for (name, version) in before.difference(after) {
+        let change = changes.entry(name).or_insert((Vec::new(), Vec::new()));
+        change.0.push(version);
+    }
+    for (name, version) in after.difference(before) {
+        let change = changes.entry(name).or_insert((Vec::new(), Vec::new()));
+        change.1.push(version);
+    }
+    let print_change = |status: &str, msg: String, color: Color| {
+        config.shell().status_with_color(status, msg, color)
+    };
+    for (name, (removed, added)) in changes {
+        if removed.len() == 1 && added.len() == 1 {
+            let msg = format!("{} v{} -> v{}", name, removed[0], added[0]);
+            print_change("Updating", msg, Green)?;
+        } else {
+            for version in removed {
+                print_change("Removing", format!("{} v{}", name, version), Red)?;
+            }
+            for version in added {
+                print_change("Adding", format!("{} v{}", name, version), Cyan)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the files which make up a stand-in for `pkg`: its manifest and an
+/// empty file for each of its targets. This is enough for the package to be
+/// loaded and resolved from the vendor directory, but not to build it.
+fn stub_files(pkg: &Package, src: &Path) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut files = BTreeMap::new();
+    files.insert(
+        PathBuf::from("Cargo.toml"),
+        Some(pkg.manifest_path().to_path_buf()),
+    );
+    for target in pkg.targets() {
+        if let Some(path) = target.src_path().path() {
+            if let Ok(relative) = path.strip_prefix(src) {
+                files.insert(relative.to_path_buf(), None);
+            }
+        }
+    }
+    files.into_iter().collect()
+}
+
+/// Returns the files of the package in `src` which are vendored, out of
+/// `paths`, relative to `src`.
+fn source_files(src: &Path, paths: &[PathBuf]) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut files = Vec::new();
     for p in paths {
         let relative = p.strip_prefix(&src).unwrap();
 
@@ -324,6 +590,31 @@ fn cp_sources(
             _ => {}
         };
 
+        files.push((relative.to_path_buf(), Some(p.clone())));
+    }
+    files
+}
+
+/// Returns the checksums of `files` as they would be vendored.
+fn hash_files(files: &[(PathBuf, Option<PathBuf>)]) -> CargoResult<BTreeMap<String, String>> {
+    let mut cksums = BTreeMap::new();
+    for (relative, src) in files {
+        let cksum = match src {
+            Some(src) => Sha256::new().update_path(src)?.finish_hex(),
+            None => Sha256::new().finish_hex(),
+        };
+        cksums.insert(relative.to_str().unwrap().replace("\\", "/"), cksum);
+    }
+    Ok(cksums)
+}
+
+/// Copies `files` to `dst`, returning their checksums.
+fn cp_files(
+    files: &[(PathBuf, Option<PathBuf>)],
+    dst: &Path,
+) -> CargoResult<BTreeMap<String, String>> {
+    let mut cksums = BTreeMap::new();
+    for (relative, src) in files {
         // Join pathname components individually to make sure that the joined
         // path uses the correct directory separators everywhere, since
         // `relative` may use Unix-style and `dst` may require Windows-style
@@ -334,9 +625,14 @@ fn cp_sources(
 
         paths::create_dir_all(dst.parent().unwrap())?;
 
-        paths::copy(&p, &dst)?;
+        match src {
+            Some(src) => {
+                paths::copy(src, &dst)?;
+            }
+            None => paths::write(&dst, "")?,
+        }
         let cksum = Sha256::new().update_path(dst)?.finish_hex();
         cksums.insert(relative.to_str().unwrap().replace("\\", "/"), cksum);
     }
-    Ok(())
+    Ok(cksums)
 }
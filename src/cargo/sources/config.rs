@@ -4,10 +4,11 @@
 //! structure usable by Cargo itself. Currently this is primarily used to map
 //! sources to one another via the `replace-with` key in `.cargo/config`.
 
-use crate::core::{GitReference, PackageId, Source, SourceId};
+use crate::core::{GitReference, GitSubmodules, PackageId, Source, SourceId};
 use crate::sources::{ReplacedSource, CRATES_IO_REGISTRY};
 use crate::util::config::{self, ConfigRelativePath, OptValue};
 use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::toml::VecStringOrBool;
 use crate::util::{Config, IntoUrl};
 use anyhow::bail;
 use log::debug;
@@ -43,6 +44,8 @@ struct SourceConfigDef {
     tag: OptValue<String>,
     /// The git revision.
     rev: OptValue<String>,
+    /// The git submodules to check out.
+    submodules: Option<VecStringOrBool>,
 }
 
 /// Configuration for a particular source, found in TOML looking like:
@@ -229,7 +232,16 @@ restore the source replacement configuration to continue the build
                     },
                 },
             };
-            srcs.push(SourceId::for_git(&url, reference)?);
+            let submodules = def.submodules.map(|submodules| match submodules {
+                VecStringOrBool::Bool(true) => GitSubmodules::All,
+                VecStringOrBool::Bool(false) => GitSubmodules::None,
+                VecStringOrBool::VecString(mut paths) => {
+                    paths.sort();
+                    paths.dedup();
+                    GitSubmodules::Paths(paths)
+                }
+            });
+            srcs.push(SourceId::for_git(&url, reference)?.with_git_submodules(submodules));
         } else {
             let check_not_set = |key, v: OptValue<String>| {
                 if let Some(val) = v {
@@ -246,6 +258,13 @@ restore the source replacement configuration to continue the build
             check_not_set("branch", def.branch)?;
             check_not_set("tag", def.tag)?;
             check_not_set("rev", def.rev)?;
+            if def.submodules.is_some() {
+                bail!(
+                    "source definition `source.{}` specifies `submodules`, \
+                     but that requires a `git` key to be specified",
+                    name
+                );
+            }
         }
         if name == "crates-io" && srcs.is_empty() {
             srcs.push(SourceId::crates_io(self.config)?);
@@ -1,4 +1,4 @@
 pub use self::source::GitSource;
-pub use self::utils::{fetch, GitCheckout, GitDatabase, GitRemote};
+pub use self::utils::{fetch, fetch_shallow, GitCheckout, GitDatabase, GitRemote};
 mod source;
 mod utils;
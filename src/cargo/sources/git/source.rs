@@ -1,26 +1,56 @@
 use crate::core::source::{MaybePackage, Source, SourceId};
-use crate::core::GitReference;
 use crate::core::{Dependency, Package, PackageId, Summary};
-use crate::sources::git::utils::GitRemote;
-use crate::sources::PathSource;
-use crate::util::errors::CargoResult;
+use crate::core::{GitReference, GitSubmodules};
+use crate::ops;
+use crate::sources::git::utils::{GitDatabase, GitRemote};
+use crate::util::errors::{internal, CargoResult};
 use crate::util::hex::short_hash;
-use crate::util::Config;
+use crate::util::{normalize_path, Config};
 use anyhow::Context;
-use log::trace;
+use log::{info, trace};
+use std::collections::HashSet;
 use std::fmt::{self, Debug, Formatter};
+use std::path::{Component, Path, PathBuf};
 use url::Url;
 
+/// A git repository, checked out at the locked revision.
+///
+/// By default the whole revision is checked out and every package in it is
+/// read, like a `PathSource` would. With `net.git-sparse-checkout`, packages
+/// are found either through the `path` of a git dependency, or by looking
+/// through the manifests in the tree of the locked revision, and only the
+/// directories of those packages and of their path dependencies within the
+/// repository are checked out, see `GitDatabase::copy_dirs_to`.
 pub struct GitSource<'cfg> {
     remote: GitRemote,
     manifest_reference: GitReference,
     locked_rev: Option<git2::Oid>,
     source_id: SourceId,
-    path_source: Option<PathSource<'cfg>>,
+    checkout: Option<Checkout>,
+    /// The packages which have been read from the checkout so far.
+    packages: Vec<Package>,
+    /// The directories of the repository that packages have been read from.
+    loaded_dirs: HashSet<String>,
+    /// The directory and name of every package in the repository, found
+    /// when a package is asked for without knowing where it is. The name is
+    /// `None` for manifests which could not be parsed.
+    all_packages: Option<Vec<(String, Option<String>)>>,
     ident: String,
     config: &'cfg Config,
 }
 
+/// The checkout of the locked revision, set up by `update`.
+struct Checkout {
+    db: GitDatabase,
+    rev: git2::Oid,
+    path: PathBuf,
+    submodules: GitSubmodules,
+    /// Whether only the directories of packages are checked out.
+    sparse: bool,
+    /// The source ID of the packages, with the precise revision.
+    source_id: SourceId,
+}
+
 impl<'cfg> GitSource<'cfg> {
     pub fn new(source_id: SourceId, config: &'cfg Config) -> CargoResult<GitSource<'cfg>> {
         assert!(source_id.is_git(), "id is not git, id={}", source_id);
@@ -38,7 +68,10 @@ impl<'cfg> GitSource<'cfg> {
                 None => None,
             },
             source_id,
-            path_source: None,
+            checkout: None,
+            packages: Vec::new(),
+            loaded_dirs: HashSet::new(),
+            all_packages: None,
             ident,
             config,
         };
@@ -51,11 +84,175 @@ impl<'cfg> GitSource<'cfg> {
     }
 
     pub fn read_packages(&mut self) -> CargoResult<Vec<Package>> {
-        if self.path_source.is_none() {
+        if self.checkout.is_none() {
             self.update()?;
         }
-        self.path_source.as_mut().unwrap().read_packages()
+        let dirs = self
+            .all_packages()?
+            .iter()
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        self.load_packages(dirs, false)?;
+        if self.packages.is_empty() {
+            anyhow::bail!("could not find any packages in {}", self.remote.url());
+        }
+        Ok(self.packages.clone())
+    }
+
+    fn all_packages(&mut self) -> CargoResult<&[(String, Option<String>)]> {
+        if self.all_packages.is_none() {
+            let checkout = self.checkout()?;
+            let packages = checkout.db.find_packages(checkout.rev)?;
+            self.all_packages = Some(packages);
+        }
+        Ok(self.all_packages.as_ref().unwrap())
+    }
+
+    /// Checks out the whole revision and reads every package in it, the same
+    /// way as a recursive `PathSource` does.
+    fn read_full_checkout(&mut self) -> CargoResult<()> {
+        let checkout = self
+            .checkout
+            .as_ref()
+            .ok_or_else(|| internal("BUG: `update()` must be called before loading packages"))?;
+        checkout.db.copy_to(
+            checkout.rev,
+            &checkout.path,
+            &checkout.submodules,
+            self.config,
+        )?;
+        let packages = ops::read_packages(&checkout.path, checkout.source_id, self.config)?;
+        let mut all_packages = Vec::new();
+        for pkg in &packages {
+            if let Some(dir) = repo_dir(&checkout.path, pkg.root()) {
+                self.loaded_dirs.insert(dir.clone());
+                all_packages.push((dir, Some(pkg.name().to_string())));
+            }
+        }
+        self.all_packages = Some(all_packages);
+        self.packages = packages;
+        Ok(())
+    }
+
+    fn checkout(&self) -> CargoResult<&Checkout> {
+        self.checkout
+            .as_ref()
+            .ok_or_else(|| internal("BUG: `update()` must be called before loading packages"))
     }
+
+    /// Makes sure the packages named `name` have been read. If `dir` is given
+    /// the package is expected in that directory of the repository, otherwise
+    /// the repository is searched for it.
+    fn load_named(&mut self, name: &str, dir: Option<&str>) -> CargoResult<()> {
+        if let Some(dir) = dir {
+            return self.load_packages(vec![dir.to_string()], true);
+        }
+        if self.packages.iter().any(|pkg| pkg.name() == name) {
+            return Ok(());
+        }
+        let all_packages = self.all_packages()?;
+        let dirs: Vec<String> = all_packages
+            .iter()
+            .filter(|(_, n)| n.as_deref() == Some(name))
+            .map(|(dir, _)| dir.clone())
+            .collect();
+        if dirs.is_empty() {
+            // The package is most likely the one at the root of the
+            // repository if its manifest couldn't be parsed, so report why.
+            let malformed_root = all_packages
+                .iter()
+                .any(|(dir, n)| dir.is_empty() && n.is_none());
+            if malformed_root {
+                return self.load_packages(vec![String::new()], true);
+            }
+        }
+        self.load_packages(dirs, false)
+    }
+
+    /// Checks out and reads the packages in `dirs`, along with their path
+    /// dependencies within the repository.
+    ///
+    /// Errors reading the packages in `dirs` are returned if `required` is
+    /// set. Otherwise they're skipped, just like malformed manifests found
+    /// when searching a full checkout.
+    fn load_packages(&mut self, dirs: Vec<String>, mut required: bool) -> CargoResult<()> {
+        let checkout = self
+            .checkout
+            .as_ref()
+            .ok_or_else(|| internal("BUG: `update()` must be called before loading packages"))?;
+        let mut pending = dirs;
+        pending.retain(|dir| !self.loaded_dirs.contains(dir));
+        while !pending.is_empty() {
+            if checkout.sparse {
+                checkout.db.copy_dirs_to(
+                    checkout.rev,
+                    &checkout.path,
+                    &pending,
+                    &checkout.submodules,
+                    self.config,
+                )?;
+            }
+            let mut nested_dirs = Vec::new();
+            for dir in pending.drain(..) {
+                if !self.loaded_dirs.insert(dir.clone()) {
+                    continue;
+                }
+                let manifest_path = checkout.path.join(&dir).join("Cargo.toml");
+                let (pkg, nested) =
+                    match ops::read_package(&manifest_path, checkout.source_id, self.config) {
+                        Ok(ret) => ret,
+                        Err(e) if required => {
+                            let location = if dir.is_empty() {
+                                "the root".to_string()
+                            } else {
+                                format!("`{}`", dir)
+                            };
+                            return Err(e.context(format!(
+                                "failed to load package in {} of {}",
+                                location,
+                                self.remote.url()
+                            )));
+                        }
+                        Err(e) => {
+                            info!("skipping package found at `{}`: {:?}", dir, e);
+                            continue;
+                        }
+                    };
+                for path in nested {
+                    match repo_dir(&checkout.path, &pkg.root().join(path)) {
+                        Some(dir) => nested_dirs.push(dir),
+                        None => info!("skipping path dependency outside of the repository"),
+                    }
+                }
+                if !self
+                    .packages
+                    .iter()
+                    .any(|p| p.package_id() == pkg.package_id())
+                {
+                    self.packages.push(pkg);
+                }
+            }
+            nested_dirs.retain(|dir| !self.loaded_dirs.contains(dir));
+            pending = nested_dirs;
+            required = false;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `path` relative to the root of the checkout at `root`, separated
+/// by `/`, or `None` if it's outside of the checkout.
+fn repo_dir(root: &Path, path: &Path) -> Option<String> {
+    let path = normalize_path(path);
+    let relative = path.strip_prefix(root).ok()?;
+    let mut dir = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(s) => dir.push(s.to_str()?),
+            _ => return None,
+        }
+    }
+    Some(dir.join("/"))
 }
 
 fn ident(id: &SourceId) -> String {
@@ -84,19 +281,22 @@ impl<'cfg> Debug for GitSource<'cfg> {
 
 impl<'cfg> Source for GitSource<'cfg> {
     fn query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
-        let src = self
-            .path_source
-            .as_mut()
-            .expect("BUG: `update()` must be called before `query()`");
-        src.query(dep, f)
+        self.load_named(&dep.package_name(), dep.git_subdirectory())?;
+        for s in self.packages.iter().map(|p| p.summary()) {
+            if dep.matches(s) {
+                f(s.clone())
+            }
+        }
+        Ok(())
     }
 
-    fn fuzzy_query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
-        let src = self
-            .path_source
-            .as_mut()
-            .expect("BUG: `update()` must be called before `query()`");
-        src.fuzzy_query(dep, f)
+    fn fuzzy_query(&mut self, _dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        // Suggestions need to know about every package in the repository.
+        self.read_packages()?;
+        for s in self.packages.iter().map(|p| p.summary()) {
+            f(s.clone())
+        }
+        Ok(())
     }
 
     fn supports_checksums(&self) -> bool {
@@ -114,9 +314,37 @@ impl<'cfg> Source for GitSource<'cfg> {
     fn update(&mut self) -> CargoResult<()> {
         let git_path = self.config.git_path();
         let git_path = self.config.assert_package_cache_locked(&git_path);
-        let db_path = git_path.join("db").join(&self.ident);
+        let net_config = self.config.net_config()?;
+        // Shallow clones are kept in their own database so they never get
+        // mixed up with a full clone of the same repository.
+        let shallow = net_config.git_shallow_fetch == Some(true);
+        let sparse = net_config.git_sparse_checkout == Some(true);
+        let submodules = match self.source_id.git_submodules() {
+            Some(submodules) => submodules.clone(),
+            None if net_config.git_submodules == Some(false) => GitSubmodules::None,
+            None => GitSubmodules::All,
+        };
+        let full_db_path = git_path.join("db").join(&self.ident);
+        let db_path = if shallow {
+            git_path.join("db").join(format!("{}-shallow", self.ident))
+        } else {
+            full_db_path.clone()
+        };
 
-        let db = self.remote.db_at(&db_path).ok();
+        let mut db = self.remote.db_at(&db_path).ok();
+        if shallow {
+            // A revision which couldn't be fetched shallowly before was
+            // fetched into the full database instead.
+            if let Some(rev) = self.locked_rev {
+                if !db.as_ref().map_or(false, |db| db.contains(rev)) {
+                    if let Ok(full_db) = self.remote.db_at(&full_db_path) {
+                        if full_db.contains(rev) {
+                            db = Some(full_db);
+                        }
+                    }
+                }
+            }
+        }
         let (db, actual_rev) = match (self.locked_rev, db) {
             // If we have a locked revision, and we have a preexisting database
             // which has that revision, then no update needs to happen.
@@ -139,7 +367,7 @@ impl<'cfg> Source for GitSource<'cfg> {
             // that we still check for being offline here, for example in the
             // situation that we have a locked revision but the database
             // doesn't have it.
-            (locked_rev, db) => {
+            (locked_rev, mut db) => {
                 if self.config.offline() {
                     anyhow::bail!(
                         "can't checkout from '{}': you are in the offline mode (--offline)",
@@ -153,13 +381,32 @@ impl<'cfg> Source for GitSource<'cfg> {
 
                 trace!("updating git source `{:?}`", self.remote);
 
-                self.remote.checkout(
-                    &db_path,
-                    db,
-                    &self.manifest_reference,
-                    locked_rev,
-                    self.config,
-                )?
+                let shallow_checkout = if shallow {
+                    self.remote.checkout_shallow(
+                        &db_path,
+                        db.take(),
+                        &self.manifest_reference,
+                        locked_rev,
+                        self.config,
+                    )?
+                } else {
+                    None
+                };
+                match shallow_checkout {
+                    Some(ret) => ret,
+                    None => {
+                        // Full fetches always go to the regular database, also
+                        // when falling back from a shallow fetch.
+                        let db = db.or_else(|| self.remote.db_at(&full_db_path).ok());
+                        self.remote.checkout(
+                            &full_db_path,
+                            db,
+                            &self.manifest_reference,
+                            locked_rev,
+                            self.config,
+                        )?
+                    }
+                }
             }
         };
 
@@ -168,21 +415,43 @@ impl<'cfg> Source for GitSource<'cfg> {
         // <https://github.com/servo/servo/pull/14397>.
         let short_id = db.to_short_id(actual_rev)?;
 
-        // Check out `actual_rev` from the database to a scoped location on the
-        // filesystem. This will use hard links and such to ideally make the
-        // checkout operation here pretty fast.
+        // `actual_rev` is checked out from the database to a scoped location
+        // on the filesystem. This will use hard links and such to ideally make
+        // the checkout operation pretty fast. With a sparse checkout only the
+        // directories of packages which are asked for are checked out, see
+        // `load_packages`. Sparse checkouts and checkouts leaving out
+        // submodules get a directory of their own.
+        let mut checkout_dir = match &submodules {
+            GitSubmodules::All => short_id.as_str().to_string(),
+            GitSubmodules::None => format!("{}-no-submodules", short_id.as_str()),
+            GitSubmodules::Paths(paths) => {
+                format!("{}-submodules-{}", short_id.as_str(), short_hash(paths))
+            }
+        };
+        if sparse {
+            checkout_dir.push_str("-sparse");
+        }
         let checkout_path = git_path
             .join("checkouts")
             .join(&self.ident)
-            .join(short_id.as_str());
-        db.copy_to(actual_rev, &checkout_path, self.config)?;
-
-        let source_id = self.source_id.with_precise(Some(actual_rev.to_string()));
-        let path_source = PathSource::new_recursive(&checkout_path, source_id, self.config);
-
-        self.path_source = Some(path_source);
+            .join(checkout_dir);
+
+        self.checkout = Some(Checkout {
+            db,
+            rev: actual_rev,
+            path: checkout_path,
+            submodules,
+            sparse,
+            source_id: self.source_id.with_precise(Some(actual_rev.to_string())),
+        });
+        self.packages.clear();
+        self.loaded_dirs.clear();
+        self.all_packages = None;
         self.locked_rev = Some(actual_rev);
-        self.path_source.as_mut().unwrap().update()
+        if !sparse {
+            self.read_full_checkout()?;
+        }
+        Ok(())
     }
 
     fn download(&mut self, id: PackageId) -> CargoResult<MaybePackage> {
@@ -191,10 +460,13 @@ impl<'cfg> Source for GitSource<'cfg> {
             id,
             self.remote
         );
-        self.path_source
-            .as_mut()
-            .expect("BUG: `update()` must be called before `get()`")
-            .download(id)
+        self.load_named(&id.name(), None)?;
+        self.packages
+            .iter()
+            .find(|pkg| pkg.package_id() == id)
+            .cloned()
+            .map(MaybePackage::Ready)
+            .ok_or_else(|| internal(format!("failed to find {} in git source", id)))
     }
 
     fn finish_download(&mut self, _id: PackageId, _data: Vec<u8>) -> CargoResult<Package> {
@@ -106,7 +106,7 @@
 //!
 //! That's the dream at least, we'll see how this plays out.
 
-use crate::core::GitReference;
+use crate::core::{GitReference, GitSubmodules};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::paths;
 use crate::util::process_builder::process;
@@ -121,6 +121,7 @@ use std::env;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str;
 use url::Url;
 
 fn serialize_str<T, S>(t: &T, s: S) -> Result<S::Ok, S::Error>
@@ -239,6 +240,78 @@ impl GitRemote {
         ))
     }
 
+    /// Fetches only the commit that `locked_rev` (or `reference`, if not
+    /// locked) points to, without any history, into the database at `into`.
+    ///
+    /// Returns `None` if the revision could not be found this way, in which
+    /// case the caller should fall back to a full fetch with `checkout`. The
+    /// full fetch must go to a different database than `into`, as shallow and
+    /// full clones are kept apart.
+    pub fn checkout_shallow(
+        &self,
+        into: &Path,
+        db: Option<GitDatabase>,
+        reference: &GitReference,
+        locked_rev: Option<git2::Oid>,
+        cargo_config: &Config,
+    ) -> CargoResult<Option<(GitDatabase, git2::Oid)>> {
+        match self.fetch_shallow_into(into, db, reference, locked_rev, cargo_config) {
+            Ok(Some(ret)) => return Ok(Some(ret)),
+            Ok(None) => debug!("revision not reachable with a shallow fetch"),
+            Err(e) => debug!("shallow fetch failed: {:?}", e),
+        }
+        cargo_config.shell().verbose(|s| {
+            s.note(format!(
+                "shallow fetch of `{}` did not find the revision, \
+                 falling back to a full fetch",
+                self.url
+            ))
+        })?;
+        Ok(None)
+    }
+
+    fn fetch_shallow_into(
+        &self,
+        into: &Path,
+        db: Option<GitDatabase>,
+        reference: &GitReference,
+        locked_rev: Option<git2::Oid>,
+        cargo_config: &Config,
+    ) -> CargoResult<Option<(GitDatabase, git2::Oid)>> {
+        let mut repo = match db {
+            Some(db) => db.repo,
+            None => {
+                if into.exists() {
+                    paths::remove_dir_all(into)?;
+                }
+                paths::create_dir_all(into)?;
+                init(into, true)?
+            }
+        };
+        // A locked revision is fetched directly by its id, so it doesn't
+        // matter whether it's still the tip of `reference`.
+        let fetch_reference = match locked_rev {
+            Some(rev) => GitReference::Rev(rev.to_string()),
+            None => reference.clone(),
+        };
+        fetch_shallow(&mut repo, self.url.as_str(), &fetch_reference, cargo_config)
+            .context(format!("failed to fetch into: {}", into.display()))?;
+        let db = GitDatabase {
+            remote: self.clone(),
+            path: into.to_path_buf(),
+            repo,
+        };
+        let rev = match locked_rev {
+            Some(rev) if db.contains(rev) => rev,
+            Some(_) => return Ok(None),
+            None => match reference.resolve(&db.repo, Some((&self.url, cargo_config))) {
+                Ok(rev) => rev,
+                Err(_) => return Ok(None),
+            },
+        };
+        Ok(Some((db, rev)))
+    }
+
     pub fn db_at(&self, db_path: &Path) -> CargoResult<GitDatabase> {
         let repo = git2::Repository::open(db_path)?;
         Ok(GitDatabase {
@@ -254,12 +327,17 @@ impl GitDatabase {
         &self,
         rev: git2::Oid,
         dest: &Path,
+        submodules: &GitSubmodules,
         cargo_config: &Config,
     ) -> CargoResult<GitCheckout<'_>> {
         let mut checkout = None;
         if let Ok(repo) = git2::Repository::open(dest) {
             let mut co = GitCheckout::new(dest, self, rev, repo);
-            if !co.is_fresh() {
+            if !co.is_fresh() && self.is_shallow() {
+                // libgit2 can't negotiate a fetch with a shallow repository,
+                // so always start over with a fresh checkout.
+                debug!("discarding stale checkout of a shallow database");
+            } else if !co.is_fresh() {
                 // After a successful fetch operation the subsequent reset can
                 // fail sometimes for corrupt repositories where the fetch
                 // operation succeeds but the object isn't actually there in one
@@ -282,15 +360,133 @@ impl GitDatabase {
             Some(c) => c,
             None => GitCheckout::clone_into(dest, self, rev, cargo_config)?,
         };
-        checkout.update_submodules(cargo_config)?;
+        checkout.update_submodules(cargo_config, &|path| {
+            submodules.includes(path.to_str().unwrap_or(""))
+        })?;
+        Ok(checkout)
+    }
+
+    /// Like `copy_to`, but only checks out `dirs` (paths relative to the root
+    /// of the repository, separated by `/`) instead of the whole tree.
+    ///
+    /// Directories checked out by earlier calls for the same `rev` are kept,
+    /// so a checkout can be grown one package at a time. They are recorded in
+    /// a `.cargo-sparse` file, while `.cargo-ok` is only created by a full
+    /// checkout.
+    pub fn copy_dirs_to(
+        &self,
+        rev: git2::Oid,
+        dest: &Path,
+        dirs: &[String],
+        submodules: &GitSubmodules,
+        cargo_config: &Config,
+    ) -> CargoResult<GitCheckout<'_>> {
+        if dirs.iter().any(|dir| dir.is_empty()) {
+            return self.copy_to(rev, dest, submodules, cargo_config);
+        }
+        let mut checkout = None;
+        if let Ok(repo) = git2::Repository::open(dest) {
+            let co = GitCheckout::new(dest, self, rev, repo);
+            if co.is_fresh() {
+                // A full checkout already has every directory.
+                return Ok(co);
+            }
+            if co.head() == Some(rev) {
+                checkout = Some(co);
+            }
+        }
+        let checkout = match checkout {
+            Some(c) => c,
+            None => GitCheckout::clone_sparse(dest, self, rev, cargo_config)?,
+        };
+
+        let present = checkout.sparse_dirs();
+        let missing: Vec<&str> = dirs
+            .iter()
+            .map(|dir| dir.as_str())
+            .filter(|dir| !present.iter().any(|p| is_within(dir, p)))
+            .collect();
+        if !missing.is_empty() {
+            checkout.checkout_dirs(&missing, cargo_config)?;
+            checkout.update_submodules(cargo_config, &|path| {
+                let path = path.to_str().unwrap_or("");
+                missing.iter().any(|dir| is_within(path, dir)) && submodules.includes(path)
+            })?;
+        }
         Ok(checkout)
     }
 
+    /// Finds the package manifests in the tree of `rev` without checking it
+    /// out. Returns the directory of each package relative to the root of the
+    /// repository, along with the name of the package, or `None` if the
+    /// manifest could not be parsed.
+    ///
+    /// This skips the same directories as `ops::read_packages` does when
+    /// walking a checkout: hidden directories, `target` directories next to a
+    /// manifest, and submodules.
+    pub fn find_packages(&self, rev: git2::Oid) -> CargoResult<Vec<(String, Option<String>)>> {
+        let tree = self.repo.find_commit(rev)?.tree()?;
+        let mut manifests: Vec<(String, git2::Oid)> = Vec::new();
+        tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+            let name = match entry.name() {
+                Some(name) => name,
+                None => return git2::TreeWalkResult::Skip,
+            };
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    // `Cargo.toml` sorts before `target`, so a manifest next to
+                    // it has already been seen.
+                    if name.starts_with('.')
+                        || (name == "target" && manifests.iter().any(|(d, _)| d == dir))
+                    {
+                        return git2::TreeWalkResult::Skip;
+                    }
+                }
+                Some(ObjectType::Blob) if name == "Cargo.toml" => {
+                    manifests.push((dir.to_string(), entry.id()));
+                }
+                _ => {}
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+
+        let mut packages = Vec::new();
+        for (dir, id) in manifests {
+            let dir = dir.trim_end_matches('/').to_string();
+            let blob = self.repo.find_blob(id)?;
+            let manifest = match str::from_utf8(blob.content())
+                .ok()
+                .and_then(|s| s.parse::<toml::Value>().ok())
+            {
+                Some(manifest) => manifest,
+                None => {
+                    info!("found malformed manifest in `{}`", dir);
+                    packages.push((dir, None));
+                    continue;
+                }
+            };
+            let name = manifest
+                .get("package")
+                .or_else(|| manifest.get("project"))
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str());
+            if let Some(name) = name {
+                packages.push((dir, Some(name.to_string())));
+            }
+        }
+        Ok(packages)
+    }
+
     pub fn to_short_id(&self, revision: git2::Oid) -> CargoResult<GitShortID> {
         let obj = self.repo.find_object(revision, None)?;
         Ok(GitShortID(obj.short_id()?))
     }
 
+    /// Whether this database was populated with a shallow fetch.
+    pub fn is_shallow(&self) -> bool {
+        self.repo.is_shallow()
+    }
+
     pub fn contains(&self, oid: git2::Oid) -> bool {
         self.repo.revparse_single(&oid.to_string()).is_ok()
     }
@@ -401,12 +597,54 @@ impl<'a> GitCheckout<'a> {
         revision: git2::Oid,
         config: &Config,
     ) -> CargoResult<GitCheckout<'a>> {
+        let repo = GitCheckout::clone_repo(into, database, revision, config)?;
+        let checkout = GitCheckout::new(into, database, revision, repo);
+        checkout.reset(config)?;
+        Ok(checkout)
+    }
+
+    /// Clones the database into `into` without checking anything out, and
+    /// points `HEAD` at `revision`.
+    fn clone_sparse(
+        into: &Path,
+        database: &'a GitDatabase,
+        revision: git2::Oid,
+        config: &Config,
+    ) -> CargoResult<GitCheckout<'a>> {
+        let repo = GitCheckout::clone_repo(into, database, revision, config)?;
+        // Ensure libgit2 won't mess with newlines when we vendor.
+        if let Ok(mut git_config) = repo.config() {
+            git_config.set_bool("core.autocrlf", false)?;
+        }
+        repo.set_head_detached(revision)?;
+        Ok(GitCheckout::new(into, database, revision, repo))
+    }
+
+    /// Clones the database into `into`, with a working directory that is
+    /// yet to be checked out.
+    fn clone_repo(
+        into: &Path,
+        database: &GitDatabase,
+        revision: git2::Oid,
+        config: &Config,
+    ) -> CargoResult<git2::Repository> {
         let dirname = into.parent().unwrap();
         paths::create_dir_all(&dirname)?;
         if into.exists() {
             paths::remove_dir_all(into)?;
         }
 
+        // A local clone with libgit2 walks the history of the database, which
+        // doesn't exist in a shallow one. Have `git` fetch just the commit
+        // we're interested in instead.
+        if database.is_shallow() {
+            let mut repo = init(into, false)?;
+            let url = database.path.into_url()?;
+            let refspec = format!("+{0}:refs/commit/{0}", revision);
+            fetch_with_cli(&mut repo, url.as_str(), &[refspec], false, true, config)?;
+            return Ok(repo);
+        }
+
         // we're doing a local filesystem-to-filesystem clone so there should
         // be no need to respect global configuration options, so pass in
         // an empty instance of `git2::Config` below.
@@ -434,23 +672,53 @@ impl<'a> GitCheckout<'a> {
             repo = Some(r);
             Ok(())
         })?;
-        let repo = repo.unwrap();
+        Ok(repo.unwrap())
+    }
 
-        let checkout = GitCheckout::new(into, database, revision, repo);
-        checkout.reset(config)?;
-        Ok(checkout)
+    fn head(&self) -> Option<git2::Oid> {
+        self.repo.revparse_single("HEAD").ok().map(|head| head.id())
     }
 
     fn is_fresh(&self) -> bool {
-        match self.repo.revparse_single("HEAD") {
-            Ok(ref head) if head.id() == self.revision => {
-                // See comments in reset() for why we check this
-                self.location.join(".cargo-ok").exists()
-            }
-            _ => false,
+        // See comments in reset() for why we check for `.cargo-ok`
+        self.head() == Some(self.revision) && self.location.join(".cargo-ok").exists()
+    }
+
+    /// The directories of a sparse checkout which have been checked out.
+    fn sparse_dirs(&self) -> Vec<String> {
+        match paths::read(&self.location.join(".cargo-sparse")) {
+            Ok(s) => s.lines().map(|line| line.to_string()).collect(),
+            Err(_) => Vec::new(),
         }
     }
 
+    fn checkout_dirs(&self, dirs: &[&str], config: &Config) -> CargoResult<()> {
+        info!(
+            "checkout {:?} of {} at {}",
+            dirs,
+            self.repo.path().display(),
+            self.revision
+        );
+        let mut pb = Progress::new("Checkout", config);
+        let mut opts = git2::build::CheckoutBuilder::new();
+        opts.force();
+        for dir in dirs {
+            opts.path(*dir);
+        }
+        opts.progress(|_, cur, max| {
+            drop(pb.tick(cur, max));
+        });
+        let object = self.repo.find_object(self.revision, None)?;
+        self.repo.checkout_tree(&object, Some(&mut opts))?;
+
+        // Only record the directories once they're fully checked out, so an
+        // interrupted checkout is retried.
+        let mut all = self.sparse_dirs();
+        all.extend(dirs.iter().map(|dir| dir.to_string()));
+        paths::write(&self.location.join(".cargo-sparse"), all.join("\n"))?;
+        Ok(())
+    }
+
     fn fetch(&mut self, cargo_config: &Config) -> CargoResult<()> {
         info!("fetch {}", self.repo.path().display());
         let url = self.database.path.into_url()?;
@@ -483,13 +751,26 @@ impl<'a> GitCheckout<'a> {
         Ok(())
     }
 
-    fn update_submodules(&self, cargo_config: &Config) -> CargoResult<()> {
-        return update_submodules(&self.repo, cargo_config);
+    /// Updates the submodules of the checkout for which `include` returns
+    /// `true` when given their path, and all of their nested submodules.
+    fn update_submodules(
+        &self,
+        cargo_config: &Config,
+        include: &dyn Fn(&Path) -> bool,
+    ) -> CargoResult<()> {
+        return update_submodules(&self.repo, cargo_config, include);
 
-        fn update_submodules(repo: &git2::Repository, cargo_config: &Config) -> CargoResult<()> {
+        fn update_submodules(
+            repo: &git2::Repository,
+            cargo_config: &Config,
+            include: &dyn Fn(&Path) -> bool,
+        ) -> CargoResult<()> {
             info!("update submodules for: {:?}", repo.workdir().unwrap());
 
             for mut child in repo.submodules()? {
+                if !include(child.path()) {
+                    continue;
+                }
                 update_submodule(repo, &mut child, cargo_config).chain_err(|| {
                     format!(
                         "failed to update submodule `{}`",
@@ -528,7 +809,7 @@ impl<'a> GitCheckout<'a> {
             let mut repo = match head_and_repo {
                 Ok((head, repo)) => {
                     if child.head_id() == head {
-                        return update_submodules(&repo, cargo_config);
+                        return update_submodules(&repo, cargo_config, &|_| true);
                     }
                     repo
                 }
@@ -553,11 +834,29 @@ impl<'a> GitCheckout<'a> {
 
             let obj = repo.find_object(head, None)?;
             reset(&repo, &obj, cargo_config)?;
-            update_submodules(&repo, cargo_config)
+            update_submodules(&repo, cargo_config, &|_| true)
+        }
+    }
+}
+
+impl GitSubmodules {
+    /// Checks whether the submodule at `path` in the repository is to be
+    /// checked out.
+    fn includes(&self, path: &str) -> bool {
+        match self {
+            GitSubmodules::All => true,
+            GitSubmodules::None => false,
+            GitSubmodules::Paths(dirs) => dirs.iter().any(|dir| is_within(path, dir)),
         }
     }
 }
 
+/// Checks whether the repository path `path` is `dir` or inside of it. An
+/// empty `dir` is the root of the repository.
+fn is_within(path: &str, dir: &str) -> bool {
+    dir.is_empty() || path == dir || (path.starts_with(dir) && path[dir.len()..].starts_with('/'))
+}
+
 /// Prepare the authentication callbacks for cloning a git repository.
 ///
 /// The main purpose of this function is to construct the "authentication
@@ -860,6 +1159,36 @@ pub fn fetch(
     url: &str,
     reference: &GitReference,
     config: &Config,
+) -> CargoResult<()> {
+    fetch_inner(repo, url, reference, false, config)
+}
+
+/// Like `fetch`, but only fetches the commits `reference` points to without
+/// any of their history, as with `git fetch --depth=1`.
+///
+/// A `GitReference::Rev` which is a full commit id is fetched directly, which
+/// requires the server to allow fetching unadvertised objects. Shorter revs
+/// can't be fetched this way, so only the tips of all branches are fetched
+/// and the rev may not be found.
+///
+/// libgit2 doesn't support shallow fetches, so this always uses the `git`
+/// executable. Repositories fetched this way should be kept apart from those
+/// with full history, see `GitDatabase::is_shallow`.
+pub fn fetch_shallow(
+    repo: &mut git2::Repository,
+    url: &str,
+    reference: &GitReference,
+    config: &Config,
+) -> CargoResult<()> {
+    fetch_inner(repo, url, reference, true, config)
+}
+
+fn fetch_inner(
+    repo: &mut git2::Repository,
+    url: &str,
+    reference: &GitReference,
+    shallow: bool,
+    config: &Config,
 ) -> CargoResult<()> {
     if config.frozen() {
         anyhow::bail!(
@@ -908,6 +1237,12 @@ pub fn fetch(
         // For `rev` dependencies we don't know what the rev will point to. To
         // handle this situation we fetch all branches and tags, and then we
         // pray it's somewhere in there.
+        // A full commit id can be fetched on its own when fetching shallowly.
+        GitReference::Rev(rev)
+            if shallow && rev.len() == 40 && git2::Oid::from_str(rev).is_ok() =>
+        {
+            refspecs.push(format!("+{0}:refs/commit/{0}", rev));
+        }
         GitReference::Rev(_) => {
             refspecs.push(String::from("refs/heads/*:refs/remotes/origin/*"));
             refspecs.push(String::from("HEAD:refs/remotes/origin/HEAD"));
@@ -921,8 +1256,8 @@ pub fn fetch(
     // repositories instead of `libgit2`-the-library. This should make more
     // flavors of authentication possible while also still giving us all the
     // speed and portability of using `libgit2`.
-    if let Some(true) = config.net_config()?.git_fetch_with_cli {
-        return fetch_with_cli(repo, url, &refspecs, tags, config);
+    if shallow || config.net_config()?.git_fetch_with_cli == Some(true) {
+        return fetch_with_cli(repo, url, &refspecs, tags, shallow, config);
     }
 
     debug!("doing a fetch for {}", url);
@@ -975,6 +1310,7 @@ fn fetch_with_cli(
     url: &str,
     refspecs: &[String],
     tags: bool,
+    shallow: bool,
     config: &Config,
 ) -> CargoResult<()> {
     let mut cmd = process("git");
@@ -982,6 +1318,9 @@ fn fetch_with_cli(
     if tags {
         cmd.arg("--tags");
     }
+    if shallow {
+        cmd.arg("--depth=1");
+    }
     cmd.arg("--force") // handle force pushes
         .arg("--update-head-ok") // see discussion in #2078
         .arg(url)
@@ -68,14 +68,16 @@
 
 use crate::core::dependency::Dependency;
 use crate::core::{PackageId, SourceId, Summary};
+use crate::sources::registry::signature::{EntrySignature, RegistrySignatures};
 use crate::sources::registry::{RegistryData, RegistryPackage};
 use crate::util::interning::InternedString;
 use crate::util::paths;
-use crate::util::{internal, CargoResult, Config, Filesystem, ToSemver};
+use crate::util::{internal, CargoResult, Config, Filesystem, Sha256, ToSemver};
 use log::info;
 use semver::{Version, VersionReq};
 use std::collections::{HashMap, HashSet};
-use std::fs;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom};
 use std::path::Path;
 use std::str;
 
@@ -168,6 +170,9 @@ pub struct RegistryIndex<'cfg> {
     source_id: SourceId,
     path: Filesystem,
     summaries_cache: HashMap<InternedString, Summaries>,
+    /// The signature policy of the registry, loaded along with the first
+    /// summaries found in the index.
+    signatures: Option<RegistrySignatures>,
     config: &'cfg Config,
 }
 
@@ -215,6 +220,10 @@ enum MaybeIndexSummary {
 pub struct IndexSummary {
     pub summary: Summary,
     pub yanked: bool,
+    /// The signature of the entry, see `RegistrySignatures`.
+    pub signature: Option<EntrySignature>,
+    /// Whether the signature has been checked already.
+    verified: bool,
 }
 
 /// A representation of the cache on disk that Cargo maintains of summaries.
@@ -236,6 +245,7 @@ impl<'cfg> RegistryIndex<'cfg> {
             source_id,
             path: path.clone(),
             summaries_cache: HashMap::new(),
+            signatures: None,
             config,
         }
     }
@@ -275,7 +285,23 @@ impl<'cfg> RegistryIndex<'cfg> {
         // has run previously this will parse a Cargo-specific cache file rather
         // than the registry itself. In effect this is intended to be a quite
         // cheap operation.
-        let summaries = self.load_summaries(name, load)?;
+        let found = !self.load_summaries(name, load)?.versions.is_empty();
+
+        // Check the signatures of the matching entries, which fails loudly
+        // rather than skipping the entry like a parse error does.
+        let signatures = if found {
+            Some(self.signatures(load)?)
+        } else {
+            None
+        };
+        let summaries = self.summaries_cache.get_mut(&name).unwrap();
+        if let Some(signatures) = signatures.filter(|s| s.is_enabled()) {
+            for (version, maybe) in summaries.versions.iter_mut() {
+                if req.matches(version) {
+                    maybe.verify(&summaries.raw_data, source_id, &signatures)?;
+                }
+            }
+        }
 
         // Iterate over our summaries, extract all relevant ones which match our
         // version requirement, and then parse all corresponding rows in the
@@ -297,6 +323,54 @@ impl<'cfg> RegistryIndex<'cfg> {
             }))
     }
 
+    /// Returns the signature policy of the registry.
+    fn signatures(&mut self, load: &mut dyn RegistryData) -> CargoResult<RegistrySignatures> {
+        if self.signatures.is_none() {
+            let registry = load.config()?;
+            self.signatures = Some(RegistrySignatures::new(
+                self.source_id,
+                registry.as_ref(),
+                self.config,
+            )?);
+        }
+        Ok(self.signatures.clone().unwrap())
+    }
+
+    /// Checks the downloaded `.crate` file of `pkg` against the checksum in
+    /// its signed entry in the index, before it is unpacked.
+    pub fn verify_crate(
+        &mut self,
+        pkg: PackageId,
+        tarball: &File,
+        load: &mut dyn RegistryData,
+    ) -> CargoResult<()> {
+        let signatures = self.signatures(load)?;
+        if !signatures.is_enabled() {
+            return Ok(());
+        }
+        let req = VersionReq::exact(pkg.version());
+        // Looking up the entry checks its signature.
+        let expected = self
+            .summaries(pkg.name(), &req, load)?
+            .next()
+            .ok_or_else(|| internal(format!("no index entry for {}", pkg)))?
+            .summary
+            .checksum()
+            .map(|s| s.to_string());
+        let mut tarball = tarball;
+        tarball.seek(SeekFrom::Start(0))?;
+        let cksum = Sha256::new().update_file(tarball)?.finish_hex();
+        tarball.seek(SeekFrom::Start(0))?;
+        if expected.as_deref() != Some(&cksum) {
+            anyhow::bail!(
+                "the checksum of `{}` does not match its signed entry in {}",
+                pkg,
+                self.source_id.display_index()
+            );
+        }
+        Ok(())
+    }
+
     fn load_summaries(
         &mut self,
         name: InternedString,
@@ -381,7 +455,7 @@ impl<'cfg> RegistryIndex<'cfg> {
         Ok(())
     }
 
-    fn query_inner_with_online(
+    pub fn query_inner_with_online(
         &mut self,
         dep: &Dependency,
         load: &mut dyn RegistryData,
@@ -696,6 +770,30 @@ impl MaybeIndexSummary {
     }
 }
 
+impl MaybeIndexSummary {
+    /// Checks the signature of this entry, unless it was already checked.
+    ///
+    /// Entries which fail to parse are left alone, they're skipped when
+    /// iterating over the summaries.
+    fn verify(
+        &mut self,
+        raw_data: &[u8],
+        source_id: SourceId,
+        signatures: &RegistrySignatures,
+    ) -> CargoResult<()> {
+        if self.parse(raw_data, source_id).is_err() {
+            return Ok(());
+        }
+        if let MaybeIndexSummary::Parsed(summary) = self {
+            if !summary.verified {
+                signatures.verify(summary.summary.package_id(), summary.signature.as_ref())?;
+                summary.verified = true;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl From<IndexSummary> for MaybeIndexSummary {
     fn from(summary: IndexSummary) -> MaybeIndexSummary {
         MaybeIndexSummary::Parsed(summary)
@@ -716,6 +814,7 @@ impl IndexSummary {
             features,
             yanked,
             links,
+            sig,
         } = serde_json::from_slice(line)?;
         log::trace!("json parsed registry {}/{}", name, vers);
         let pkgid = PackageId::new(name, &vers, source_id)?;
@@ -726,9 +825,12 @@ impl IndexSummary {
         let namespaced_features = false;
         let mut summary = Summary::new(pkgid, deps, &features, links, namespaced_features)?;
         summary.set_checksum(cksum);
+        let signature = sig.map(|sig| EntrySignature::new(line, sig)).transpose()?;
         Ok(IndexSummary {
             summary,
             yanked: yanked.unwrap_or(false),
+            signature,
+            verified: false,
         })
     }
 }
@@ -168,7 +168,7 @@ use std::path::{Path, PathBuf};
 use flate2::read::GzDecoder;
 use log::debug;
 use semver::{Version, VersionReq};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tar::Archive;
 
 use crate::core::dependency::{DepKind, Dependency};
@@ -222,10 +222,20 @@ pub struct RegistryConfig {
     /// operations like yanks, owner modifications, publish new crates, etc.
     /// If this is None, the registry does not support API commands.
     pub api: Option<String>,
+
+    /// Whether crate downloads need the registry token, which is then sent
+    /// in the `Authorization` header.
+    #[serde(rename = "auth-required", default)]
+    pub auth_required: bool,
+
+    /// Hex-encoded ed25519 public key which signs the entries of the index,
+    /// see the `signature` module.
+    #[serde(rename = "signing-key")]
+    pub signing_key: Option<String>,
 }
 
 /// A single line in the index representing a single version of a package.
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct RegistryPackage<'a> {
     name: InternedString,
     vers: Version,
@@ -243,6 +253,72 @@ pub struct RegistryPackage<'a> {
     /// Added early 2018 (see https://github.com/rust-lang/cargo/pull/4978),
     /// can be `None` if published before then.
     links: Option<InternedString>,
+    /// Hex-encoded signature of this entry, for registries with a
+    /// `signing-key`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sig: Option<String>,
+}
+
+impl RegistryPackage<'static> {
+    /// Creates the index entry of the package with `summary`, whose `.crate`
+    /// file has the checksum `cksum`, for an index which replaces crates.io.
+    pub fn new(summary: &Summary, cksum: &str) -> RegistryPackage<'static> {
+        let deps = summary
+            .dependencies()
+            .iter()
+            .map(|dep| {
+                let (name, package) = if dep.explicit_name_in_toml().is_some() {
+                    (dep.name_in_toml(), Some(dep.package_name()))
+                } else {
+                    (dep.package_name(), None)
+                };
+                let kind = match dep.kind() {
+                    DepKind::Normal => "normal",
+                    DepKind::Development => "dev",
+                    DepKind::Build => "build",
+                };
+                // In the index, "registry" is null if it is from the same index.
+                let registry = if dep.source_id().is_default_registry() {
+                    None
+                } else {
+                    Some(Cow::Owned(dep.source_id().url().to_string()))
+                };
+                RegistryDependency {
+                    name,
+                    req: Cow::Owned(dep.version_req().to_string()),
+                    features: dep.features().to_vec(),
+                    optional: dep.is_optional(),
+                    default_features: dep.uses_default_features(),
+                    target: dep.platform().map(|p| Cow::Owned(p.to_string())),
+                    kind: Some(Cow::Borrowed(kind)),
+                    registry,
+                    package,
+                    public: None,
+                }
+            })
+            .collect();
+        let features = summary
+            .features()
+            .iter()
+            .map(|(feature, values)| {
+                let values = values
+                    .iter()
+                    .map(|v| InternedString::new(&v.to_string(summary)))
+                    .collect();
+                (*feature, values)
+            })
+            .collect();
+        RegistryPackage {
+            name: summary.name(),
+            vers: summary.version().clone(),
+            deps,
+            features,
+            cksum: cksum.to_string(),
+            yanked: Some(false),
+            links: summary.links(),
+            sig: None,
+        }
+    }
 }
 
 #[test]
@@ -290,7 +366,7 @@ enum Field {
     Links,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 struct RegistryDependency<'a> {
     name: InternedString,
     #[serde(borrow)]
@@ -302,6 +378,7 @@ struct RegistryDependency<'a> {
     kind: Option<Cow<'a, str>>,
     registry: Option<Cow<'a, str>>,
     package: Option<InternedString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     public: Option<bool>,
 }
 
@@ -393,12 +470,17 @@ pub trait RegistryData {
 
 pub enum MaybeLock {
     Ready(File),
-    Download { url: String, descriptor: String },
+    Download {
+        url: String,
+        descriptor: String,
+        authorization: Option<String>,
+    },
 }
 
 mod index;
 mod local;
 mod remote;
+mod signature;
 
 fn short_name(id: SourceId) -> String {
     let hash = hex::short_hash(&id);
@@ -457,7 +539,7 @@ impl<'cfg> RegistrySource<'cfg> {
     /// compiled.
     ///
     /// No action is taken if the source looks like it's already unpacked.
-    fn unpack_package(&self, pkg: PackageId, tarball: &File) -> CargoResult<PathBuf> {
+    fn unpack_package(&mut self, pkg: PackageId, tarball: &File) -> CargoResult<PathBuf> {
         // The `.cargo-ok` file is used to track if the source is already
         // unpacked.
         let package_dir = format!("{}-{}", pkg.name(), pkg.version());
@@ -466,6 +548,11 @@ impl<'cfg> RegistrySource<'cfg> {
         let path = dst.join(PACKAGE_SOURCE_LOCK);
         let path = self.config.assert_package_cache_locked(&path);
         let unpack_dir = path.parent().unwrap();
+        // Already unpacked sources are only used if their `.crate` file still
+        // matches the signed entry.
+        self.index
+            .verify_crate(pkg, tarball, &mut *self.ops)
+            .chain_err(|| format!("failed to verify the signature of `{}`", pkg))?;
         if let Ok(meta) = path.metadata() {
             if meta.len() > 0 {
                 return Ok(unpack_dir.to_path_buf());
@@ -598,6 +685,21 @@ impl<'cfg> Source for RegistrySource<'cfg> {
             .query_inner(dep, &mut *self.ops, &self.yanked_whitelist, f)
     }
 
+    fn query_online(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        self.index.query_inner_with_online(
+            dep,
+            &mut *self.ops,
+            &self.yanked_whitelist,
+            &mut |s| {
+                if dep.matches(&s) {
+                    f(s);
+                }
+            },
+            true,
+        )?;
+        Ok(())
+    }
+
     fn supports_checksums(&self) -> bool {
         true
     }
@@ -630,9 +732,15 @@ impl<'cfg> Source for RegistrySource<'cfg> {
         let hash = self.index.hash(package, &mut *self.ops)?;
         match self.ops.download(package, hash)? {
             MaybeLock::Ready(file) => self.get_pkg(package, &file).map(MaybePackage::Ready),
-            MaybeLock::Download { url, descriptor } => {
-                Ok(MaybePackage::Download { url, descriptor })
-            }
+            MaybeLock::Download {
+                url,
+                descriptor,
+                authorization,
+            } => Ok(MaybePackage::Download {
+                url,
+                descriptor,
+                authorization,
+            }),
         }
     }
 
@@ -660,4 +768,8 @@ impl<'cfg> Source for RegistrySource<'cfg> {
         }
         self.index.is_yanked(pkg, &mut *self.ops)
     }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        self.ops.is_crate_downloaded(pkg)
+    }
 }
@@ -1,4 +1,5 @@
 use crate::core::{GitReference, PackageId, SourceId};
+use crate::ops;
 use crate::sources::git;
 use crate::sources::registry::MaybeLock;
 use crate::sources::registry::{
@@ -20,6 +21,14 @@ use std::mem;
 use std::path::Path;
 use std::str;
 
+fn shallow_index(config: &Config) -> bool {
+    // Errors in the `[net]` table are reported when fetching the index.
+    matches!(
+        config.net_config().map(|net| net.git_shallow_fetch),
+        Ok(Some(true))
+    )
+}
+
 fn make_crate_prefix(name: &str) -> String {
     match name.len() {
         1 => String::from("1"),
@@ -34,26 +43,40 @@ pub struct RemoteRegistry<'cfg> {
     cache_path: Filesystem,
     source_id: SourceId,
     index_git_ref: GitReference,
+    /// Whether the index is fetched with `net.git-shallow-fetch`.
+    shallow: bool,
     config: &'cfg Config,
     tree: RefCell<Option<git2::Tree<'static>>>,
     repo: LazyCell<git2::Repository>,
     head: Cell<Option<git2::Oid>>,
     current_sha: Cell<Option<InternedString>>,
+    /// The token sent with downloads if the registry has `auth-required`
+    /// set, read once for all downloads from this source.
+    auth_token: LazyCell<String>,
 }
 
 impl<'cfg> RemoteRegistry<'cfg> {
     pub fn new(source_id: SourceId, config: &'cfg Config, name: &str) -> RemoteRegistry<'cfg> {
+        // A shallow index is kept apart from a full one, see `update_index`.
+        let shallow = shallow_index(config);
+        let index_name = if shallow {
+            format!("{}-shallow", name)
+        } else {
+            name.to_string()
+        };
         RemoteRegistry {
-            index_path: config.registry_index_path().join(name),
+            index_path: config.registry_index_path().join(index_name),
             cache_path: config.registry_cache_path().join(name),
             source_id,
             config,
             // TODO: we should probably make this configurable
             index_git_ref: GitReference::DefaultBranch,
+            shallow,
             tree: RefCell::new(None),
             repo: LazyCell::new(),
             head: Cell::new(None),
             current_sha: Cell::new(None),
+            auth_token: LazyCell::new(),
         }
     }
 
@@ -236,8 +259,14 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
         // checkout.
         let url = self.source_id.url();
         let repo = self.repo.borrow_mut().unwrap();
-        git::fetch(repo, url.as_str(), &self.index_git_ref, self.config)
-            .chain_err(|| format!("failed to fetch `{}`", url))?;
+        // Only the tree of the latest commit of the index is ever read, so
+        // its history doesn't need to be fetched.
+        let res = if self.shallow {
+            git::fetch_shallow(repo, url.as_str(), &self.index_git_ref, self.config)
+        } else {
+            git::fetch(repo, url.as_str(), &self.index_git_ref, self.config)
+        };
+        res.chain_err(|| format!("failed to fetch `{}`", url))?;
         self.config.updated_sources().insert(self.source_id);
 
         // Create a dummy file to record the mtime for when we updated the
@@ -281,9 +310,19 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
             .replace(PREFIX_TEMPLATE, &prefix)
             .replace(LOWER_PREFIX_TEMPLATE, &prefix.to_lowercase());
 
+        let authorization = if config.auth_required {
+            let token = self
+                .auth_token
+                .try_borrow_with(|| ops::registry_auth_token(self.config, self.source_id))?;
+            Some(token.clone())
+        } else {
+            None
+        };
+
         Ok(MaybeLock::Download {
             url,
             descriptor: pkg.to_string(),
+            authorization,
         })
     }
 
@@ -0,0 +1,146 @@
+//! Verification of signed index entries.
+//!
+//! A registry opts in by listing a hex-encoded ed25519 public key as
+//! `signing-key` in its `config.json`. Each index entry may then carry a
+//! hex-encoded `sig` of the entry itself, see `EntrySignature::new`. This ties
+//! the dependencies and features of the package, as well as the checksum of
+//! the `.crate` file and so its contents, to the key rather than only to
+//! whoever can write to the index.
+//!
+//! Entries are checked when they are read from the index, and the `.crate`
+//! file is checked against the signed checksum when it is unpacked.
+
+use std::convert::TryFrom;
+
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use serde_json::Value;
+
+use crate::core::{PackageId, SourceId};
+use crate::ops;
+use crate::sources::registry::RegistryConfig;
+use crate::util::errors::CargoResult;
+use crate::util::Config;
+
+/// The signature of an index entry, along with the message it signs.
+pub struct EntrySignature {
+    sig: String,
+    message: String,
+}
+
+impl EntrySignature {
+    /// Creates the signature `sig` of the index entry `line`.
+    ///
+    /// The signed message is the entry without its `sig` field, serialized
+    /// as JSON with the keys of every object sorted and without any
+    /// whitespace, so it doesn't depend on how the index formats the entry.
+    pub fn new(line: &[u8], sig: String) -> CargoResult<EntrySignature> {
+        let mut entry: Value = serde_json::from_slice(line)?;
+        if let Value::Object(entry) = &mut entry {
+            entry.remove("sig");
+        }
+        let message = serde_json::to_string(&canonicalize(entry))?;
+        Ok(EntrySignature { sig, message })
+    }
+}
+
+/// Sorts the keys of all objects in `value`.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map
+                .into_iter()
+                .map(|(k, v)| (k, canonicalize(v)))
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
+
+/// The signature policy of a registry.
+#[derive(Clone)]
+pub struct RegistrySignatures {
+    source_id: SourceId,
+    /// The key from `config.json`, if the registry signs its entries.
+    key: Option<PublicKey>,
+    /// Whether unsigned entries are an error, from the
+    /// `require-signatures` config of the registry.
+    required: bool,
+}
+
+impl RegistrySignatures {
+    pub fn new(
+        source_id: SourceId,
+        registry: Option<&RegistryConfig>,
+        config: &Config,
+    ) -> CargoResult<RegistrySignatures> {
+        let key = match registry.and_then(|r| r.signing_key.as_ref()) {
+            Some(key) => Some(parse_key(key).map_err(|e| {
+                anyhow::format_err!(
+                    "invalid `signing-key` in the config.json of {}: {}",
+                    source_id.display_index(),
+                    e
+                )
+            })?),
+            None => None,
+        };
+        let required =
+            source_id.is_remote_registry() && ops::registry_requires_signatures(config, source_id)?;
+        if required && key.is_none() {
+            anyhow::bail!(
+                "registry `{}` requires signatures, but its config.json has no `signing-key`",
+                source_id.display_registry_name()
+            );
+        }
+        Ok(RegistrySignatures {
+            source_id,
+            key,
+            required,
+        })
+    }
+
+    /// Returns whether there is anything to check for this registry.
+    pub fn is_enabled(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Checks the signature of the index entry of `pkg`.
+    pub fn verify(&self, pkg: PackageId, sig: Option<&EntrySignature>) -> CargoResult<()> {
+        let key = match &self.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        let sig = match sig {
+            Some(sig) => sig,
+            None if self.required => anyhow::bail!(
+                "package `{}` is not signed, and registry `{}` requires signatures",
+                pkg,
+                self.source_id.display_registry_name()
+            ),
+            None => return Ok(()),
+        };
+        let valid = parse_signature(&sig.sig)
+            .map(|s| key.verify(sig.message.as_bytes(), &s).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            anyhow::bail!(
+                "invalid signature for package `{}` in {}",
+                pkg,
+                self.source_id.display_index()
+            );
+        }
+        Ok(())
+    }
+}
+
+fn parse_key(key: &str) -> CargoResult<PublicKey> {
+    let bytes = hex::decode(key)?;
+    PublicKey::from_bytes(&bytes).map_err(|e| anyhow::format_err!("{}", e))
+}
+
+fn parse_signature(sig: &str) -> CargoResult<Signature> {
+    let bytes = hex::decode(sig)?;
+    Signature::try_from(&bytes[..]).map_err(|e| anyhow::format_err!("{}", e))
+}
@@ -63,6 +63,18 @@ impl<'cfg> Source for ReplacedSource<'cfg> {
         Ok(())
     }
 
+    fn query_online(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
+        let (replace_with, to_replace) = (self.replace_with, self.to_replace);
+        let dep = dep.clone().map_source(to_replace, replace_with);
+
+        self.inner
+            .query_online(&dep, &mut |summary| {
+                f(summary.map_source(replace_with, to_replace))
+            })
+            .chain_err(|| format!("failed to query replaced source {}", self.to_replace))?;
+        Ok(())
+    }
+
     fn update(&mut self) -> CargoResult<()> {
         self.inner
             .update()
@@ -125,4 +137,9 @@ impl<'cfg> Source for ReplacedSource<'cfg> {
     fn is_yanked(&mut self, pkg: PackageId) -> CargoResult<bool> {
         self.inner.is_yanked(pkg)
     }
+
+    fn is_crate_downloaded(&self, pkg: PackageId) -> bool {
+        self.inner
+            .is_crate_downloaded(pkg.with_source_id(self.replace_with))
+    }
 }
@@ -163,6 +163,13 @@ pub trait AppExt: Sized {
         ))
     }
 
+    fn arg_keep_going(self) -> Self {
+        self._arg(opt(
+            "keep-going",
+            "Do not abort the build as soon as there is an error (unstable)",
+        ))
+    }
+
     fn arg_unit_graph(self) -> Self {
         self._arg(opt("unit-graph", "Output build graph in JSON (unstable)").hidden(true))
     }
@@ -459,6 +466,7 @@ pub trait ArgMatchesExt {
         build_config.requested_profile = self.get_profile_name(config, "dev", profile_checking)?;
         build_config.build_plan = self._is_present("build-plan");
         build_config.unit_graph = self._is_present("unit-graph");
+        build_config.keep_going = self._is_present("keep-going");
         if build_config.build_plan {
             config
                 .cli_unstable()
@@ -469,6 +477,11 @@ pub trait ArgMatchesExt {
                 .cli_unstable()
                 .fail_if_stable_opt("--unit-graph", 8002)?;
         }
+        if build_config.keep_going {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt("--keep-going", 10496)?;
+        }
 
         let opts = CompileOptions {
             build_config,
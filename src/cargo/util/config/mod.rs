@@ -89,7 +89,7 @@ mod key;
 use key::ConfigKey;
 
 mod path;
-pub use path::{ConfigRelativePath, PathAndArgs};
+pub use path::{BuildTargetConfig, ConfigRelativePath, PathAndArgs};
 
 mod target;
 pub use target::{TargetCfgConfig, TargetConfig};
@@ -1072,10 +1072,34 @@ impl Config {
 
     /// Loads credentials config from the credentials file, if present.
     pub fn load_credentials(&mut self) -> CargoResult<()> {
+        let value = match self.credentials_value()? {
+            Some(value) => value,
+            None => return Ok(()),
+        };
+
+        if let CV::Table(map, _) = value {
+            let base_map = self.values_mut()?;
+            for (k, v) in map {
+                match base_map.entry(k) {
+                    Vacant(entry) => {
+                        entry.insert(v);
+                    }
+                    Occupied(mut entry) => {
+                        entry.get_mut().merge(v, true)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the credentials file, if present.
+    fn credentials_value(&self) -> CargoResult<Option<ConfigValue>> {
         let home_path = self.home_path.clone().into_path_unlocked();
         let credentials = match self.get_file_path(&home_path, "credentials", true)? {
             Some(credentials) => credentials,
-            None => return Ok(()),
+            None => return Ok(None),
         };
 
         let mut value = self.load_file(&credentials)?;
@@ -1095,22 +1119,42 @@ impl Config {
                 }
             }
         }
+        Ok(Some(value))
+    }
 
-        if let CV::Table(map, _) = value {
-            let base_map = self.values_mut()?;
-            for (k, v) in map {
-                match base_map.entry(k) {
-                    Vacant(entry) => {
-                        entry.insert(v);
-                    }
-                    Occupied(mut entry) => {
-                        entry.get_mut().merge(v, true)?;
-                    }
-                }
-            }
+    /// Gets the token of the registry named `registry`, or of crates.io if
+    /// `None`. Unlike `get_string`, this also looks in the credentials file
+    /// when `load_credentials` hasn't been called.
+    pub fn registry_token(&self, registry: Option<&str>) -> CargoResult<Option<String>> {
+        let key = match registry {
+            Some(registry) => format!("registries.{}.token", registry),
+            None => "registry.token".to_string(),
+        };
+        if let Some(token) = self.get_string(&key)? {
+            return Ok(Some(token.val));
+        }
+        let mut value = match self.credentials_value()? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        for part in key.split('.') {
+            value = match value {
+                CV::Table(mut map, _) => match map.remove(part) {
+                    Some(value) => value,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            };
+        }
+        match value {
+            CV::String(token, _) => Ok(Some(token)),
+            other => bail!(
+                "expected a string for `{}`, but found {} in {}",
+                key,
+                other.desc(),
+                other.definition()
+            ),
         }
-
-        Ok(())
     }
 
     /// Looks for a path for `tool` in an environment variable or the given config, and returns
@@ -1740,7 +1784,11 @@ pub struct SslVersionConfigRange {
 pub struct CargoNetConfig {
     pub retry: Option<u32>,
     pub offline: Option<bool>,
+    pub prefer_offline: Option<bool>,
     pub git_fetch_with_cli: Option<bool>,
+    pub git_shallow_fetch: Option<bool>,
+    pub git_sparse_checkout: Option<bool>,
+    pub git_submodules: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1750,7 +1798,7 @@ pub struct CargoBuildConfig {
     pub dep_info_basedir: Option<ConfigRelativePath>,
     pub target_dir: Option<ConfigRelativePath>,
     pub incremental: Option<bool>,
-    pub target: Option<ConfigRelativePath>,
+    pub target: Option<BuildTargetConfig>,
     pub jobs: Option<u32>,
     pub rustflags: Option<StringList>,
     pub rustdocflags: Option<StringList>,
@@ -1759,6 +1807,9 @@ pub struct CargoBuildConfig {
     pub rustc: Option<PathBuf>,
     pub rustdoc: Option<PathBuf>,
     pub out_dir: Option<ConfigRelativePath>,
+    pub dependency_script_warnings: Option<bool>,
+    pub build_std: Option<StringList>,
+    pub build_std_features: Option<StringList>,
 }
 
 /// A type to deserialize a list of strings from a toml file.
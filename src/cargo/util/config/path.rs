@@ -71,3 +71,57 @@ impl<'de> serde::Deserialize<'de> for PathAndArgs {
         })
     }
 }
+
+/// The value of `build.target`, which is either a single target or a list of
+/// targets to build for.
+///
+/// Targets ending in `.json` are paths to custom target specifications and
+/// are resolved relative to the location of the config file.
+#[derive(Debug, Clone)]
+pub struct BuildTargetConfig(Value<Vec<String>>);
+
+impl BuildTargetConfig {
+    /// Returns the requested targets, with target specification paths
+    /// resolved to absolute paths.
+    pub fn values(&self, config: &Config) -> Vec<String> {
+        self.0
+            .val
+            .iter()
+            .map(|value| {
+                if value.ends_with(".json") {
+                    let path = self.0.definition.root(config).join(value);
+                    path.to_str().expect("must be utf-8 in toml").to_string()
+                } else {
+                    value.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The raw `build.target` value. Unlike other string lists, a string is
+/// never split on whitespace, since it may be a path to a target
+/// specification.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BuildTargetConfigInner {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl<'de> serde::Deserialize<'de> for BuildTargetConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let v = Value::<BuildTargetConfigInner>::deserialize(deserializer)?;
+        let val = match v.val {
+            BuildTargetConfigInner::One(target) => vec![target],
+            BuildTargetConfigInner::Many(targets) => targets,
+        };
+        Ok(BuildTargetConfig(Value {
+            val,
+            definition: v.definition,
+        }))
+    }
+}
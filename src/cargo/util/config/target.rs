@@ -1,5 +1,5 @@
 use super::{Config, ConfigKey, ConfigRelativePath, OptValue, PathAndArgs, StringList, CV};
-use crate::core::compiler::BuildOutput;
+use crate::core::compiler::{BuildOutput, LinkType};
 use crate::util::CargoResult;
 use serde::Deserialize;
 use std::collections::{BTreeMap, HashMap};
@@ -28,6 +28,8 @@ pub struct TargetConfig {
     pub rustflags: OptValue<StringList>,
     /// The path of the linker for this target.
     pub linker: OptValue<ConfigRelativePath>,
+    /// The standard library crates to build from source for this target.
+    pub build_std: OptValue<StringList>,
     /// Build script override for the given library name.
     ///
     /// Any package with a `links` value for the given library name will skip
@@ -74,6 +76,7 @@ pub(super) fn load_target_triple(config: &Config, triple: &str) -> CargoResult<T
     let runner: OptValue<PathAndArgs> = config.get(&format!("target.{}.runner", triple))?;
     let rustflags: OptValue<StringList> = config.get(&format!("target.{}.rustflags", triple))?;
     let linker: OptValue<ConfigRelativePath> = config.get(&format!("target.{}.linker", triple))?;
+    let build_std: OptValue<StringList> = config.get(&format!("target.{}.build-std", triple))?;
     // Links do not support environment variables.
     let target_key = ConfigKey::from_str(&format!("target.{}", triple));
     let links_overrides = match config.get_table(&target_key)? {
@@ -84,6 +87,7 @@ pub(super) fn load_target_triple(config: &Config, triple: &str) -> CargoResult<T
         runner,
         rustflags,
         linker,
+        build_std,
         links_overrides,
     })
 }
@@ -97,7 +101,7 @@ fn parse_links_overrides(
         // Skip these keys, it shares the namespace with `TargetConfig`.
         match lib_name.as_str() {
             // `ar` is a historical thing.
-            "ar" | "linker" | "runner" | "rustflags" => continue,
+            "ar" | "linker" | "runner" | "rustflags" | "build-std" => continue,
             _ => {}
         }
         let mut output = BuildOutput::default();
@@ -129,9 +133,24 @@ fn parse_links_overrides(
                         .library_paths
                         .extend(list.iter().map(|v| PathBuf::from(&v.0)));
                 }
-                "rustc-cdylib-link-arg" => {
+                "rustc-link-arg"
+                | "rustc-cdylib-link-arg"
+                | "rustc-link-arg-bins"
+                | "rustc-link-arg-tests"
+                | "rustc-link-arg-benches"
+                | "rustc-link-arg-examples" => {
+                    let lt = match key.as_str() {
+                        "rustc-link-arg" => LinkType::All,
+                        "rustc-cdylib-link-arg" => LinkType::Cdylib,
+                        "rustc-link-arg-bins" => LinkType::Bin,
+                        "rustc-link-arg-tests" => LinkType::Test,
+                        "rustc-link-arg-benches" => LinkType::Bench,
+                        _ => LinkType::Example,
+                    };
                     let args = value.list(key)?;
-                    output.linker_args.extend(args.iter().map(|v| v.0.clone()));
+                    output
+                        .linker_args
+                        .extend(args.iter().map(|v| (lt.clone(), v.0.clone())));
                 }
                 "rustc-cfg" => {
                     let list = value.list(key)?;
@@ -143,7 +162,7 @@ fn parse_links_overrides(
                         output.env.push((name.clone(), val.to_string()));
                     }
                 }
-                "warning" | "rerun-if-changed" | "rerun-if-env-changed" => {
+                "warning" | "error" | "rerun-if-changed" | "rerun-if-env-changed" => {
                     anyhow::bail!("`{}` is not supported in build script overrides", key);
                 }
                 _ => {
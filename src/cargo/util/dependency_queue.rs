@@ -157,6 +157,11 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         self.dep_map.len()
     }
 
+    /// Returns an iterator over the remaining packages to be built.
+    pub fn remaining(&self) -> impl Iterator<Item = &N> {
+        self.dep_map.keys()
+    }
+
     /// Indicate that something has finished.
     ///
     /// Calling this function indicates that the `node` has produced `edge`. All
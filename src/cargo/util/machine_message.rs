@@ -4,6 +4,7 @@ use serde::ser;
 use serde::Serialize;
 use serde_json::{self, json, value::RawValue};
 
+use crate::core::profiles::DebugInfo;
 use crate::core::{compiler::CompileMode, PackageId, Target};
 
 pub trait Message: ser::Serialize {
@@ -53,7 +54,7 @@ impl<'a> Message for Artifact<'a> {
 #[derive(Serialize)]
 pub struct ArtifactProfile {
     pub opt_level: &'static str,
-    pub debuginfo: Option<u32>,
+    pub debuginfo: Option<DebugInfo>,
     pub debug_assertions: bool,
     pub overflow_checks: bool,
     pub test: bool,
@@ -75,6 +76,20 @@ impl<'a> Message for BuildScript<'a> {
     }
 }
 
+#[derive(Serialize)]
+pub struct BuildScriptMessage<'a> {
+    pub package_id: PackageId,
+    /// Either `"warning"` or `"error"`.
+    pub level: &'a str,
+    pub message: &'a str,
+}
+
+impl<'a> Message for BuildScriptMessage<'a> {
+    fn reason(&self) -> &str {
+        "build-script-message"
+    }
+}
+
 #[derive(Serialize)]
 pub struct TimingInfo<'a> {
     pub package_id: PackageId,
@@ -60,6 +60,7 @@ mod read2;
 pub mod restricted_names;
 pub mod rustc;
 mod sha256;
+pub mod spdx;
 pub mod to_semver;
 pub mod toml;
 mod vcs;
@@ -0,0 +1,264 @@
+//! A small parser for SPDX license expressions, as used by the `license`
+//! field of the manifest.
+//!
+//! This understands the `AND`, `OR` and `WITH` operators and parentheses,
+//! with `AND` binding tighter than `OR`. The legacy `/` separator (as in
+//! `MIT/Apache-2.0`) is accepted as an alias for `OR`. License identifiers
+//! are not checked against the SPDX license list.
+
+use std::fmt;
+
+use crate::util::errors::CargoResult;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LicenseExpr {
+    /// A single license identifier such as `MIT`, `GPL-2.0+` or
+    /// `LicenseRef-Proprietary`.
+    License(String),
+    /// A license with an exception, such as `Apache-2.0 WITH LLVM-exception`.
+    With(String, String),
+    And(Box<LicenseExpr>, Box<LicenseExpr>),
+    Or(Box<LicenseExpr>, Box<LicenseExpr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    And,
+    Or,
+    With,
+    Open,
+    Close,
+}
+
+impl LicenseExpr {
+    pub fn parse(s: &str) -> CargoResult<LicenseExpr> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            anyhow::bail!("unexpected `{}` in license expression `{}`", token, s);
+        }
+        Ok(expr)
+    }
+
+    /// Checks whether the terms of this expression can be met by only using
+    /// licenses which are `allowed` and not `denied`.
+    ///
+    /// For a license with an exception, the license identifier is checked
+    /// against `denied`, while `allowed` may accept either the identifier or
+    /// the full `LICENSE WITH EXCEPTION` form. A denied license stays denied
+    /// no matter which exception comes with it.
+    pub fn is_satisfied_by(
+        &self,
+        denied: &dyn Fn(&str) -> bool,
+        allowed: &dyn Fn(&str) -> bool,
+    ) -> bool {
+        match self {
+            LicenseExpr::License(id) => !denied(id) && allowed(id),
+            LicenseExpr::With(id, exception) => {
+                !denied(id) && (allowed(id) || allowed(&format!("{} WITH {}", id, exception)))
+            }
+            LicenseExpr::And(a, b) => {
+                a.is_satisfied_by(denied, allowed) && b.is_satisfied_by(denied, allowed)
+            }
+            LicenseExpr::Or(a, b) => {
+                a.is_satisfied_by(denied, allowed) || b.is_satisfied_by(denied, allowed)
+            }
+        }
+    }
+}
+
+impl fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseExpr::License(id) => write!(f, "{}", id),
+            LicenseExpr::With(id, exception) => write!(f, "{} WITH {}", id, exception),
+            LicenseExpr::And(a, b) => {
+                // `OR` binds more loosely, so it needs parentheses inside `AND`.
+                for (i, e) in [a, b].iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    match ***e {
+                        LicenseExpr::Or(..) => write!(f, "({})", e)?,
+                        _ => write!(f, "{}", e)?,
+                    }
+                }
+                Ok(())
+            }
+            LicenseExpr::Or(a, b) => write!(f, "{} OR {}", a, b),
+        }
+    }
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::With => write!(f, "WITH"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> CargoResult<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start();
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '/' => tokens.push(Token::Or),
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '+' || c == ':' => {
+                let end = rest
+                    .find(|c: char| {
+                        !(c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '+' || c == ':')
+                    })
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                tokens.push(match word {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "WITH" => Token::With,
+                    _ => Token::Ident(word),
+                });
+                rest = &rest[end..];
+                continue;
+            }
+            c => anyhow::bail!("invalid character `{}` in license expression `{}`", c, s),
+        }
+        rest = &rest[1..];
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> CargoResult<LicenseExpr> {
+        let mut expr = self.and()?;
+        while let Some(Token::Or) = self.peek() {
+            self.pos += 1;
+            expr = LicenseExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> CargoResult<LicenseExpr> {
+        let mut expr = self.primary()?;
+        while let Some(Token::And) = self.peek() {
+            self.pos += 1;
+            expr = LicenseExpr::And(Box::new(expr), Box::new(self.primary()?));
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> CargoResult<LicenseExpr> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => anyhow::bail!("expected `)` in license expression"),
+                }
+            }
+            Some(Token::Ident(id)) => {
+                if let Some(Token::With) = self.peek() {
+                    self.pos += 1;
+                    match self.next() {
+                        Some(Token::Ident(exception)) => {
+                            Ok(LicenseExpr::With(id.to_string(), exception.to_string()))
+                        }
+                        _ => anyhow::bail!("expected an exception identifier after `WITH`"),
+                    }
+                } else {
+                    Ok(LicenseExpr::License(id.to_string()))
+                }
+            }
+            Some(token) => anyhow::bail!("expected a license identifier, found `{}`", token),
+            None => anyhow::bail!("expected a license identifier, found end of expression"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LicenseExpr;
+
+    #[test]
+    fn parse_and_display() {
+        fn ok(s: &str, expected: &str) {
+            let expr = LicenseExpr::parse(s).unwrap();
+            assert_eq!(expr.to_string(), expected);
+        }
+
+        ok("MIT", "MIT");
+        ok("MIT OR Apache-2.0", "MIT OR Apache-2.0");
+        ok("MIT/Apache-2.0", "MIT OR Apache-2.0");
+        ok(
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+            "(MIT OR Apache-2.0) AND BSD-3-Clause",
+        );
+        ok(
+            "MIT OR Apache-2.0 AND BSD-3-Clause",
+            "MIT OR Apache-2.0 AND BSD-3-Clause",
+        );
+        ok(
+            "Apache-2.0 WITH LLVM-exception OR MIT",
+            "Apache-2.0 WITH LLVM-exception OR MIT",
+        );
+        ok("GPL-2.0+", "GPL-2.0+");
+    }
+
+    #[test]
+    fn parse_errors() {
+        for s in &["", "MIT OR", "(MIT", "MIT)", "MIT WITH", "MIT, Apache-2.0"] {
+            assert!(LicenseExpr::parse(s).is_err(), "`{}` should not parse", s);
+        }
+    }
+
+    #[test]
+    fn satisfied_by() {
+        let denied = |l: &str| l == "GPL-3.0";
+        let allowed = |l: &str| {
+            l == "MIT" || l == "Apache-2.0 WITH LLVM-exception" || l.starts_with("GPL-3.0")
+        };
+        let check = |s: &str| {
+            LicenseExpr::parse(s)
+                .unwrap()
+                .is_satisfied_by(&denied, &allowed)
+        };
+
+        assert!(check("MIT"));
+        assert!(check("MIT OR GPL-3.0"));
+        assert!(!check("MIT AND GPL-3.0"));
+        assert!(check("Apache-2.0 WITH LLVM-exception"));
+        assert!(!check("Apache-2.0"));
+        assert!(check("(GPL-3.0 OR MIT) AND MIT"));
+        assert!(!check("GPL-3.0"));
+        assert!(!check("GPL-3.0 WITH Classpath-exception-2.0"));
+        assert!(check("GPL-3.0 WITH Classpath-exception-2.0 OR MIT"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 use std::str;
 
@@ -13,13 +13,15 @@ use serde::ser;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::core::dependency::DepKind;
+use crate::core::dependency::{Artifact, DepKind};
 use crate::core::manifest::{ManifestMetadata, TargetSourcePath, Warnings};
-use crate::core::profiles::Strip;
+use crate::core::profiles::{Strip, TomlDebugInfo};
 use crate::core::resolver::ResolveBehavior;
 use crate::core::{Dependency, Manifest, PackageId, Summary, Target};
 use crate::core::{Edition, EitherManifest, Feature, Features, VirtualManifest, Workspace};
-use crate::core::{GitReference, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig};
+use crate::core::{
+    GitReference, GitSubmodules, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig,
+};
 use crate::sources::{CRATES_IO_INDEX, CRATES_IO_REGISTRY};
 use crate::util::errors::{CargoResult, CargoResultExt, ManifestError};
 use crate::util::interning::InternedString;
@@ -234,6 +236,8 @@ pub struct DetailedTomlDependency {
     branch: Option<String>,
     tag: Option<String>,
     rev: Option<String>,
+    submodules: Option<VecStringOrBool>,
+    subdirectory: Option<String>,
     features: Option<Vec<String>>,
     optional: Option<bool>,
     default_features: Option<bool>,
@@ -241,6 +245,9 @@ pub struct DetailedTomlDependency {
     default_features2: Option<bool>,
     package: Option<String>,
     public: Option<bool>,
+    artifact: Option<StringOrVec>,
+    lib: Option<bool>,
+    target: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -346,60 +353,13 @@ impl ser::Serialize for TomlOptLevel {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
-#[serde(untagged)]
-pub enum U32OrBool {
-    U32(u32),
-    Bool(bool),
-}
-
-impl<'de> de::Deserialize<'de> for U32OrBool {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: de::Deserializer<'de>,
-    {
-        struct Visitor;
-
-        impl<'de> de::Visitor<'de> for Visitor {
-            type Value = U32OrBool;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-                formatter.write_str("a boolean or an integer")
-            }
-
-            fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                Ok(U32OrBool::Bool(b))
-            }
-
-            fn visit_i64<E>(self, u: i64) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                Ok(U32OrBool::U32(u as u32))
-            }
-
-            fn visit_u64<E>(self, u: u64) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                Ok(U32OrBool::U32(u as u32))
-            }
-        }
-
-        deserializer.deserialize_any(Visitor)
-    }
-}
-
 #[derive(Deserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct TomlProfile {
     pub opt_level: Option<TomlOptLevel>,
     pub lto: Option<StringOrBool>,
     pub codegen_units: Option<u32>,
-    pub debug: Option<U32OrBool>,
+    pub debug: Option<TomlDebugInfo>,
     pub debug_assertions: Option<bool>,
     pub rpath: Option<bool>,
     pub panic: Option<String>,
@@ -410,6 +370,10 @@ pub struct TomlProfile {
     pub dir_name: Option<InternedString>,
     pub inherits: Option<InternedString>,
     pub strip: Option<Strip>,
+    pub split_debuginfo: Option<String>,
+    pub build_std_features: Option<Vec<String>>,
+    pub rustflags: Option<Vec<InternedString>>,
+    pub codegen_backend: Option<InternedString>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
@@ -459,12 +423,12 @@ impl TomlProfile {
 
         if let Some(ref profile) = self.build_override {
             features.require(Feature::profile_overrides())?;
-            profile.validate_override("build-override")?;
+            profile.validate_override("build-override", features)?;
         }
         if let Some(ref packages) = self.package {
             features.require(Feature::profile_overrides())?;
             for profile in packages.values() {
-                profile.validate_override("package")?;
+                profile.validate_override("package", features)?;
             }
         }
 
@@ -526,9 +490,28 @@ impl TomlProfile {
             }
         }
 
+        if let Some(split_debuginfo) = &self.split_debuginfo {
+            if !matches!(split_debuginfo.as_str(), "off" | "packed" | "unpacked") {
+                bail!(
+                    "`split-debuginfo` setting of `{}` is not a valid setting, \
+                     must be `off`, `packed` or `unpacked`",
+                    split_debuginfo
+                );
+            }
+        }
+
         if self.strip.is_some() {
             features.require(Feature::strip())?;
         }
+
+        if self.rustflags.is_some() {
+            features.require(Feature::profile_rustflags())?;
+        }
+
+        if let Some(codegen_backend) = &self.codegen_backend {
+            features.require(Feature::codegen_backend())?;
+            Self::validate_name(codegen_backend, "codegen-backend")?;
+        }
         Ok(())
     }
 
@@ -561,7 +544,7 @@ impl TomlProfile {
         Ok(())
     }
 
-    fn validate_override(&self, which: &str) -> CargoResult<()> {
+    fn validate_override(&self, which: &str, features: &Features) -> CargoResult<()> {
         if self.package.is_some() {
             bail!("package-specific profiles cannot be nested");
         }
@@ -577,6 +560,19 @@ impl TomlProfile {
         if self.rpath.is_some() {
             bail!("`rpath` may not be specified in a `{}` profile", which)
         }
+        if self.build_std_features.is_some() {
+            bail!(
+                "`build-std-features` may not be specified in a `{}` profile",
+                which
+            )
+        }
+        if self.rustflags.is_some() {
+            features.require(Feature::profile_rustflags())?;
+        }
+        if let Some(codegen_backend) = &self.codegen_backend {
+            features.require(Feature::codegen_backend())?;
+            Self::validate_name(codegen_backend, "codegen-backend")?;
+        }
         Ok(())
     }
 
@@ -652,6 +648,22 @@ impl TomlProfile {
         if let Some(v) = profile.strip {
             self.strip = Some(v);
         }
+
+        if let Some(v) = &profile.split_debuginfo {
+            self.split_debuginfo = Some(v.clone());
+        }
+
+        if let Some(v) = &profile.build_std_features {
+            self.build_std_features = Some(v.clone());
+        }
+
+        if let Some(v) = &profile.rustflags {
+            self.rustflags = Some(v.clone());
+        }
+
+        if let Some(v) = profile.codegen_backend {
+            self.codegen_backend = Some(v);
+        }
     }
 }
 
@@ -1640,8 +1652,21 @@ impl DetailedTomlDependency {
                     cx.warnings.push(msg)
                 }
             }
+            for &(key, key_name) in &[
+                (self.submodules.is_some(), "submodules"),
+                (self.subdirectory.is_some(), "subdirectory"),
+            ] {
+                if key {
+                    cx.warnings.push(format!(
+                        "key `{}` is ignored for dependency ({}). \
+                         This will be considered an error in future versions",
+                        key_name, name_in_toml
+                    ));
+                }
+            }
         }
 
+        let mut git_subdirectory = None;
         let new_source_id = match (
             self.git.as_ref(),
             self.path.as_ref(),
@@ -1668,6 +1693,11 @@ impl DetailedTomlDependency {
                     );
                     cx.warnings.push(msg)
                 }
+                // The directory of the package within the repository, which
+                // saves searching for it.
+                if let Some(dir) = &self.subdirectory {
+                    git_subdirectory = Some(git_subdirectory_from_path(name_in_toml, dir)?);
+                }
 
                 let n_details = [&self.branch, &self.tag, &self.rev]
                     .iter()
@@ -1703,7 +1733,11 @@ impl DetailedTomlDependency {
                     cx.warnings.push(msg)
                 }
 
-                SourceId::for_git(&loc, reference)?
+                let submodules = match &self.submodules {
+                    Some(submodules) => Some(git_submodules(name_in_toml, submodules)?),
+                    None => None,
+                };
+                SourceId::for_git(&loc, reference)?.with_git_submodules(submodules)
             }
             (None, Some(path), _, _) => {
                 cx.nested_paths.push(PathBuf::from(path));
@@ -1759,6 +1793,9 @@ impl DetailedTomlDependency {
             dep.set_registry_id(registry_id);
         }
 
+        if let Some(dir) = git_subdirectory {
+            dep.set_git_subdirectory(dir);
+        }
         if let Some(kind) = kind {
             dep.set_kind(kind);
         }
@@ -1776,10 +1813,98 @@ impl DetailedTomlDependency {
 
             dep.set_public(p);
         }
+
+        match (&self.artifact, self.lib, &self.target) {
+            (Some(artifact), lib, target) => {
+                cx.features.require(Feature::artifact_dependencies())?;
+                let artifact =
+                    Artifact::parse(&artifact.0, lib.unwrap_or(false), target.as_deref())
+                        .chain_err(|| {
+                            format!(
+                                "failed to parse the artifact of dependency `{}`",
+                                name_in_toml
+                            )
+                        })?;
+                dep.set_artifact(artifact);
+            }
+            (None, lib, target) => {
+                let artifact_only_keys = [(lib.is_some(), "lib"), (target.is_some(), "target")];
+                for &(is_set, key_name) in &artifact_only_keys {
+                    if is_set {
+                        cx.warnings.push(format!(
+                            "key `{}` is ignored for dependency ({}) without an `artifact` key",
+                            key_name, name_in_toml
+                        ));
+                    }
+                }
+            }
+        }
         Ok(dep)
     }
 }
 
+/// Converts the `subdirectory` of a git dependency to a `/` separated path
+/// relative to the root of the repository.
+fn git_subdirectory_from_path(name_in_toml: &str, path: &str) -> CargoResult<String> {
+    let mut dir = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(s) => match s.to_str() {
+                Some(s) => dir.push(s),
+                None => bail!("non-utf8 path for dependency ({})", name_in_toml),
+            },
+            Component::CurDir => {}
+            _ => bail!(
+                "the `subdirectory` of git dependency ({}) must be a relative path \
+                 inside the repository, found `{}`",
+                name_in_toml,
+                path
+            ),
+        }
+    }
+    Ok(dir.join("/"))
+}
+
+/// Converts the `submodules` key of a git dependency, which is either a
+/// boolean or a list of paths within the repository.
+fn git_submodules(name_in_toml: &str, submodules: &VecStringOrBool) -> CargoResult<GitSubmodules> {
+    let paths = match submodules {
+        VecStringOrBool::Bool(true) => return Ok(GitSubmodules::All),
+        VecStringOrBool::Bool(false) => return Ok(GitSubmodules::None),
+        VecStringOrBool::VecString(paths) => paths,
+    };
+    let mut dirs = Vec::new();
+    for path in paths {
+        let mut dir = Vec::new();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(s) => match s.to_str() {
+                    Some(s) => dir.push(s),
+                    None => bail!("non-utf8 submodule path for dependency ({})", name_in_toml),
+                },
+                Component::CurDir => {}
+                _ => bail!(
+                    "the `submodules` of git dependency ({}) must be relative paths \
+                     inside the repository, found `{}`",
+                    name_in_toml,
+                    path
+                ),
+            }
+        }
+        if dir.is_empty() {
+            // The root of the repository contains every submodule.
+            return Ok(GitSubmodules::All);
+        }
+        dirs.push(dir.join("/"));
+    }
+    if dirs.is_empty() {
+        return Ok(GitSubmodules::None);
+    }
+    dirs.sort();
+    dirs.dedup();
+    Ok(GitSubmodules::Paths(dirs))
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 struct TomlTarget {
     name: Option<String>,
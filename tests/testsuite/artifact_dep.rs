@@ -0,0 +1,263 @@
+//! Tests for artifact dependencies.
+
+use cargo_test_support::{basic_bin_manifest, basic_manifest, cross_compile, project};
+
+#[cargo_test]
+fn build_script_with_bin_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["artifact-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [build-dependencies]
+                bar = { path = "bar", artifact = "bin" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                use std::env;
+                use std::path::Path;
+
+                fn main() {
+                    let bar = env::var("CARGO_BIN_FILE_BAR").unwrap();
+                    assert!(Path::new(&bar).is_file());
+                    assert_eq!(bar, env::var("CARGO_BIN_FILE_BAR_bar").unwrap());
+                    let dir = env::var("CARGO_BIN_DIR_BAR").unwrap();
+                    assert_eq!(Path::new(&bar).parent().unwrap(), Path::new(&dir));
+
+                    let status = std::process::Command::new(&bar).status().unwrap();
+                    assert!(status.success());
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[COMPILING] bar v0.5.0 ([CWD]/bar)")
+        .with_stderr_does_not_contain("[..]--extern bar[..]")
+        .run();
+}
+
+#[cargo_test]
+fn lib_with_selected_bin_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["artifact-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar", artifact = "bin:baz", lib = true }
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                pub fn baz_path() -> &'static str {
+                    env!("CARGO_BIN_FILE_BAR_baz")
+                }
+
+                pub fn answer() -> u32 {
+                    bar::answer()
+                }
+            "#,
+        )
+        .file(
+            "tests/t.rs",
+            r#"
+                #[test]
+                fn t() {
+                    assert!(std::path::Path::new(foo::baz_path()).is_file());
+                    assert_eq!(foo::answer(), 42);
+                }
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "pub fn answer() -> u32 { 42 }")
+        .file("bar/src/bin/baz.rs", "fn main() {}")
+        .file("bar/src/bin/other.rs", "fn main() {}")
+        .build();
+
+    p.cargo("test -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name baz [..]")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name other [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]--extern bar=[..]")
+        .run();
+}
+
+#[cargo_test]
+fn missing_artifact() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["artifact-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar", artifact = "cdylib" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] dependency `bar` in package `foo` requires a `cdylib` artifact, \
+but package `bar` has no such target
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar", artifact = "bin" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  feature `artifact-dependencies` is required
+
+  consider adding `cargo-features = [\"artifact-dependencies\"]` to the manifest
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_artifact_kind() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["artifact-dependencies"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                bar = { path = "bar", artifact = ["bin", "rlib"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  failed to parse the artifact of dependency `bar`
+
+Caused by:
+  `rlib` is not a valid artifact kind, expected `bin`, `bin:<name>`, `cdylib` or `staticlib`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn artifact_for_other_target() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let target = cross_compile::alternate();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["artifact-dependencies"]
+
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    authors = []
+
+                    [build-dependencies]
+                    bar = {{ path = "bar", artifact = "bin", target = "{}" }}
+                "#,
+                target
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            &format!(
+                r#"
+                    fn main() {{
+                        let bar = std::env::var("CARGO_BIN_FILE_BAR").unwrap();
+                        assert!(bar.contains("{}"), "{{}}", bar);
+                        assert!(std::path::Path::new(&bar).is_file());
+                    }}
+                "#,
+                target
+            ),
+        )
+        .file("bar/Cargo.toml", &basic_bin_manifest("bar"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains(&format!(
+            "[RUNNING] `rustc --crate-name bar [..]--target {} [..]",
+            target
+        ))
+        .run();
+}
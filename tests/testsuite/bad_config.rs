@@ -1117,6 +1117,62 @@ This will be considered an error in future versions
         .run();
 }
 
+#[cargo_test]
+fn git_subdirectory_outside_repository() {
+    let foo = project()
+        .file(
+            "Cargo.toml",
+            r#"
+        [package]
+        name = "foo"
+        version = "0.0.0"
+        authors = []
+
+        [dependencies.bar]
+        git = "http://127.0.0.1"
+        subdirectory = "../bar"
+    "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    foo.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(
+            "  the `subdirectory` of git dependency (bar) must be a relative path \
+             inside the repository, found `../bar`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn subdirectory_without_git() {
+    Package::new("bar", "0.1.0").publish();
+    let foo = project()
+        .file(
+            "Cargo.toml",
+            r#"
+        [package]
+        name = "foo"
+        version = "0.0.0"
+        authors = []
+
+        [dependencies.bar]
+        version = "0.1.0"
+        subdirectory = "bar"
+    "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    foo.cargo("build")
+        .with_stderr_contains(
+            "[WARNING] key `subdirectory` is ignored for dependency (bar). \
+             This will be considered an error in future versions",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn bad_source_config6() {
     let p = project()
@@ -1308,7 +1364,7 @@ fn bad_debuginfo() {
 error: failed to parse manifest at `[..]`
 
 Caused by:
-  invalid type: string \"a\", expected a boolean or an integer for [..]
+  invalid value: string \"a\", expected a boolean, 0, 1, 2, \"none\", \"limited\", \"full\", \"line-tables-only\" or \"line-directives-only\" for [..]
 ",
         )
         .run();
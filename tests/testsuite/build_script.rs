@@ -3142,6 +3142,203 @@ warning: bar
         .run();
 }
 
+#[cargo_test]
+fn warnings_printed_with_config() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:warning=foo");
+                }
+            "#,
+        )
+        .file(
+            "Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.1.0"
+                authors = []
+                build = "build.rs"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            bar = "*"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .env("CARGO_BUILD_DEPENDENCY_SCRIPT_WARNINGS", "true")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 ([..])
+[COMPILING] bar v0.1.0
+warning: foo
+[COMPILING] foo v0.5.0 ([..])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn errors_emitted() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+            build = "build.rs"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+            fn main() {
+                println!("cargo:warning=foo");
+                println!("cargo:error=bar");
+                println!("cargo:error=baz");
+            }
+        "#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.5.0 ([CWD])
+The following warnings were emitted during compilation:
+
+warning: foo
+
+error: bar
+error: baz
+error: build script of `foo v0.5.0 ([CWD])` reported errors
+",
+        )
+        .run();
+
+    // Errors are not cached, the build script runs again.
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("error: bar")
+        .run();
+}
+
+#[cargo_test]
+fn errors_emitted_for_upstream() {
+    Package::new("bar", "0.1.0")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:error=libbar not found");
+                }
+            "#,
+        )
+        .file(
+            "Cargo.toml",
+            r#"
+                [project]
+                name = "bar"
+                version = "0.1.0"
+                authors = []
+                build = "build.rs"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            bar = "*"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 ([..])
+[COMPILING] bar v0.1.0
+error: libbar not found
+error: build script of `bar v0.1.0` reported errors
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_script_messages_json() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+            fn main() {
+                println!("cargo:warning=foo");
+                println!("cargo:error=bar");
+            }
+        "#,
+        )
+        .build();
+
+    p.cargo("build --message-format=json")
+        .with_status(101)
+        .with_json_contains_unordered(
+            r#"
+                {
+                    "reason": "build-script-message",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "level": "warning",
+                    "message": "foo"
+                }
+
+                {
+                    "reason": "build-script-message",
+                    "package_id": "foo 0.0.1 ([..])",
+                    "level": "error",
+                    "message": "bar"
+                }
+            "#,
+        )
+        .run();
+}
+
 #[cargo_test]
 fn output_shows_on_vv() {
     let p = project()
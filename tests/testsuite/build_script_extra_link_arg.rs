@@ -0,0 +1,162 @@
+//! Tests for the `cargo:rustc-link-arg*` build script instructions.
+
+use cargo_test_support::{basic_bin_manifest, basic_manifest, project};
+
+#[cargo_test]
+fn build_script_extra_link_arg_bin() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rustc-link-arg-bins=--this-is-a-bogus-flag");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .without_status()
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo src/main.rs [..]-C link-arg=--this-is-a-bogus-flag[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_script_extra_link_arg_bin_single() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foobar"
+                version = "0.5.0"
+                authors = ["wycats@example.com"]
+
+                [[bin]]
+                name = "foo"
+                [[bin]]
+                name = "bar"
+            "#,
+        )
+        .file("src/bin/foo.rs", "fn main() {}")
+        .file("src/bin/bar.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rustc-link-arg-bins=-Lall-bins");
+                    println!("cargo:rustc-link-arg-bin=foo=-Lfoo-only");
+                    println!("cargo:rustc-link-arg-bin=bar=-Lbar-only");
+                }
+            "#,
+        )
+        .build();
+
+    // The arguments are harmless library search paths, so both binaries link
+    // and each command line can be checked regardless of the build order.
+    p.cargo("build -v")
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo [..]-C link-arg=-Lall-bins -C link-arg=-Lfoo-only[..]",
+        )
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name bar [..]-C link-arg=-Lall-bins -C link-arg=-Lbar-only[..]",
+        )
+        .with_stderr_does_not_contain("[..]-C link-arg=-Lbar-only[..]-C link-arg=-Lfoo-only[..]")
+        .with_stderr_does_not_contain("[..]-C link-arg=-Lfoo-only[..]-C link-arg=-Lbar-only[..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_extra_link_arg() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rustc-link-arg=--this-is-a-bogus-flag");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .without_status()
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name foo src/main.rs [..]-C link-arg=--this-is-a-bogus-flag[..]",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn link_arg_missing_target() {
+    // Errors when a given target doesn't exist.
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rustc-link-arg-bin=abc=-bogus"); }"#,
+        )
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+error: invalid instruction `cargo:rustc-link-arg-bin` from build script of `foo v0.0.1 ([ROOT]/foo)`
+The package has no bin target named `abc`
+",
+        )
+        .run();
+
+    p.change_file(
+        "build.rs",
+        r#"fn main() { println!("cargo:rustc-link-arg-bin=abc"); }"#,
+    );
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+error: invalid instruction `cargo:rustc-link-arg-bin=abc` from build script of `foo v0.0.1 ([ROOT]/foo)`
+The instruction should have the form cargo:rustc-link-arg-bin=BIN=ARG
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn link_arg_tests_only_for_tests() {
+    // `rustc-link-arg-tests` is only passed to integration tests.
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/main.rs", "fn main() {}")
+        .file("tests/t.rs", "#[test] fn t() {}")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rustc-link-arg-tests=--this-is-a-bogus-flag");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .with_stderr_does_not_contain("[..]--this-is-a-bogus-flag[..]")
+        .run();
+
+    p.cargo("test --no-run -v")
+        .without_status()
+        .with_stderr_contains(
+            "[RUNNING] `rustc --crate-name t tests/t.rs [..]-C link-arg=--this-is-a-bogus-flag[..]",
+        )
+        .run();
+}
@@ -1,6 +1,6 @@
 //! Tests for config settings.
 
-use cargo::core::profiles::Strip;
+use cargo::core::profiles::{DebugInfo, Strip, TomlDebugInfo};
 use cargo::core::{enable_nightly_features, Shell};
 use cargo::util::config::{self, Config, SslVersionConfig, StringList};
 use cargo::util::interning::InternedString;
@@ -403,7 +403,7 @@ lto = false
             opt_level: Some(toml::TomlOptLevel("s".to_string())),
             lto: Some(toml::StringOrBool::Bool(true)),
             codegen_units: Some(5),
-            debug: Some(toml::U32OrBool::Bool(true)),
+            debug: Some(TomlDebugInfo::Level(DebugInfo::Full)),
             debug_assertions: Some(true),
             rpath: Some(true),
             panic: Some("abort".to_string()),
@@ -446,7 +446,7 @@ fn profile_env_var_prefix() {
         .build();
     let p: toml::TomlProfile = config.get("profile.dev").unwrap();
     assert_eq!(p.debug_assertions, None);
-    assert_eq!(p.debug, Some(toml::U32OrBool::U32(1)));
+    assert_eq!(p.debug, Some(TomlDebugInfo::Level(DebugInfo::Limited)));
 
     let config = ConfigBuilder::new()
         .env("CARGO_PROFILE_DEV_DEBUG_ASSERTIONS", "false")
@@ -454,7 +454,7 @@ fn profile_env_var_prefix() {
         .build();
     let p: toml::TomlProfile = config.get("profile.dev").unwrap();
     assert_eq!(p.debug_assertions, Some(false));
-    assert_eq!(p.debug, Some(toml::U32OrBool::U32(1)));
+    assert_eq!(p.debug, Some(TomlDebugInfo::Level(DebugInfo::Limited)));
 }
 
 #[cargo_test]
@@ -1483,3 +1483,39 @@ Caused by:
   unknown variant `invalid`, expected one of `debuginfo`, `none`, `symbols`",
     );
 }
+
+#[cargo_test]
+fn build_target_string_is_not_split() {
+    write_config(
+        "\
+[build]
+target = 'specs dir/custom.json'
+",
+    );
+    let config = new_config();
+    let target = config.build_config().unwrap().target.clone().unwrap();
+    assert_eq!(
+        target.values(&config),
+        [paths::root()
+            .join("specs dir/custom.json")
+            .to_str()
+            .unwrap()]
+    );
+
+    let spec = paths::root().join("other specs/custom.json");
+    let config = ConfigBuilder::new()
+        .env("CARGO_BUILD_TARGET", spec.to_str().unwrap())
+        .build();
+    let target = config.build_config().unwrap().target.clone().unwrap();
+    assert_eq!(target.values(&config), [spec.to_str().unwrap()]);
+
+    write_config(
+        "\
+[build]
+target = ['a', 'b']
+",
+    );
+    let config = new_config();
+    let target = config.build_config().unwrap().target.clone().unwrap();
+    assert_eq!(target.values(&config), ["a", "b"]);
+}
@@ -2672,6 +2672,311 @@ fn git_fetch_cli_env_clean() {
         .run();
 }
 
+#[cargo_test]
+fn shallow_fetch() {
+    if disable_git_cli() {
+        return;
+    }
+    let (git_project, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", r#"pub fn f() { println!("one"); }"#)
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2018"
+
+                [dependencies]
+                dep1 = {{ git = "{}" }}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/main.rs", "fn main() { dep1::f(); }")
+        .file(
+            ".cargo/config",
+            "
+            [net]
+            git-shallow-fetch = true
+            ",
+        )
+        .build();
+    p.cargo("build").run();
+    p.rename_run("foo", "foo1").with_stdout("one").run();
+
+    // The shallow clone lives next to where a full clone would.
+    let db = paths::home().join(".cargo/git/db");
+    let shallow_db = t!(fs::read_dir(&db))
+        .map(|entry| t!(entry).path())
+        .find(|path| path.to_str().unwrap().ends_with("-shallow"))
+        .expect("shallow database");
+    assert!(shallow_db.join("shallow").exists());
+
+    // Push a new commit and start over with an empty cache. The locked
+    // revision is no longer the tip of the branch, but is still found.
+    git_project.change_file("src/lib.rs", r#"pub fn f() { println!("two"); }"#);
+    git::add(&repo);
+    git::commit(&repo);
+    paths::home().join(".cargo/git").rm_rf();
+    p.cargo("build").run();
+    p.rename_run("foo", "foo2").with_stdout("one").run();
+
+    p.cargo("update").run();
+    p.cargo("build").run();
+    p.rename_run("foo", "foo3").with_stdout("two").run();
+}
+
+#[cargo_test]
+fn shallow_fetch_falls_back_to_full_fetch() {
+    if disable_git_cli() {
+        return;
+    }
+    let (git_project, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", r#"pub fn f() { println!("one"); }"#)
+    });
+    let old_rev = repo.revparse_single("HEAD").unwrap().id().to_string();
+    git_project.change_file("src/lib.rs", r#"pub fn f() { println!("two"); }"#);
+    git::add(&repo);
+    git::commit(&repo);
+
+    // A short rev which is not the tip of a branch can't be fetched shallowly.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2018"
+
+                [dependencies]
+                dep1 = {{ git = "{}", rev = "{}" }}
+                "#,
+                git_project.url(),
+                &old_rev[..10]
+            ),
+        )
+        .file("src/main.rs", "fn main() { dep1::f(); }")
+        .file(
+            ".cargo/config",
+            "
+            [net]
+            git-shallow-fetch = true
+            ",
+        )
+        .build();
+    p.cargo("build -v")
+        .with_stderr_contains("[NOTE] shallow fetch of `[..]` did not find the revision, [..]")
+        .run();
+    p.rename_run("foo", "foo1").with_stdout("one").run();
+
+    // The full fetch went to the regular database.
+    let db = paths::home().join(".cargo/git/db");
+    let full_db = t!(fs::read_dir(&db))
+        .map(|entry| t!(entry).path())
+        .find(|path| !path.to_str().unwrap().ends_with("-shallow"))
+        .expect("full database");
+    assert!(!full_db.join("shallow").exists());
+
+    // The locked revision is found there from now on.
+    p.cargo("build -v")
+        .with_stderr_does_not_contain("[..]shallow fetch[..]")
+        .with_stderr_does_not_contain("[UPDATING] git repository [..]")
+        .run();
+}
+
+/// Returns the only checkout in the git cache.
+fn only_checkout() -> std::path::PathBuf {
+    let checkouts = paths::home().join(".cargo/git/checkouts");
+    let repo = t!(t!(fs::read_dir(&checkouts)).next().unwrap()).path();
+    t!(t!(fs::read_dir(&repo)).next().unwrap()).path()
+}
+
+fn monorepo() -> Project {
+    git::new("monorepo", |project| {
+        project
+            .file(
+                "crates/a/Cargo.toml",
+                r#"
+                    [package]
+                    name = "a"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    b = { path = "../b" }
+                "#,
+            )
+            .file("crates/a/src/lib.rs", "pub fn a() { b::b() }")
+            .file("crates/b/Cargo.toml", &basic_manifest("b", "0.1.0"))
+            .file("crates/b/src/lib.rs", "pub fn b() {}")
+            .file("crates/c/Cargo.toml", &basic_manifest("c", "0.1.0"))
+            .file("crates/c/src/lib.rs", "")
+            .file("assets/big.bin", "")
+    })
+}
+
+#[cargo_test]
+fn git_dep_checks_out_whole_repository() {
+    let repo = monorepo();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    a = {{ git = "{}" }}
+                "#,
+                repo.url()
+            ),
+        )
+        .file("src/lib.rs", "pub fn foo() { a::a() }")
+        .build();
+
+    p.cargo("build").run();
+
+    let checkout = only_checkout();
+    assert!(checkout.join("crates/c/src/lib.rs").exists());
+    assert!(checkout.join("assets/big.bin").exists());
+}
+
+#[cargo_test]
+fn sparse_checkout_only_checks_out_package_dirs() {
+    let repo = monorepo();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    a = {{ git = "{}" }}
+                "#,
+                repo.url()
+            ),
+        )
+        .file("src/lib.rs", "pub fn foo() { a::a() }")
+        .build();
+
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SPARSE_CHECKOUT", "true")
+        .run();
+
+    let checkout = only_checkout();
+    assert!(checkout.join("crates/a/src/lib.rs").exists());
+    assert!(checkout.join("crates/b/src/lib.rs").exists());
+    assert!(!checkout.join("crates/c").exists());
+    assert!(!checkout.join("assets").exists());
+}
+
+#[cargo_test]
+fn git_dep_with_subdirectory() {
+    let repo = monorepo();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    a = {{ git = "{0}", subdirectory = "crates/a" }}
+                    c = {{ git = "{0}", subdirectory = "./crates/c" }}
+                "#,
+                repo.url()
+            ),
+        )
+        .file("src/lib.rs", "pub fn foo() { a::a() }")
+        .build();
+
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SPARSE_CHECKOUT", "true")
+        .run();
+
+    let checkout = only_checkout();
+    assert!(checkout.join("crates/a/src/lib.rs").exists());
+    assert!(checkout.join("crates/b/src/lib.rs").exists());
+    assert!(checkout.join("crates/c/src/lib.rs").exists());
+    assert!(!checkout.join("assets").exists());
+
+    p.change_file(
+        "Cargo.toml",
+        &format!(
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                a = {{ git = "{}", subdirectory = "crates/nope" }}
+            "#,
+            repo.url()
+        ),
+    );
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SPARSE_CHECKOUT", "true")
+        .with_status(101)
+        .with_stderr_contains("[..]failed to load package in `crates/nope` of [..]monorepo")
+        .run();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[..]failed to load package in `crates/nope` of [..]monorepo")
+        .run();
+}
+
+#[cargo_test]
+fn sparse_checkout_invalid_root_manifest() {
+    let repo = git::new("dep1", |project| {
+        project
+            .file(
+                "Cargo.toml",
+                "[package]\nname = \"dep1\"\nversion = \"0.5.0\"\nbad\n",
+            )
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    dep1 = {{ git = "{}" }}
+                "#,
+                repo.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SPARSE_CHECKOUT", "true")
+        .with_status(101)
+        .with_stderr_contains("[..]failed to load package in the root of [..]dep1")
+        .with_stderr_contains("[..]could not parse input as TOML")
+        .run();
+}
+
 #[cargo_test]
 fn dirty_submodule() {
     // `cargo package` warns for dirty file in submodule.
@@ -3019,3 +3324,161 @@ warning: [..]
         )
         .run();
 }
+
+/// A git dependency with two submodules, the second of which can no longer be
+/// fetched.
+fn dep_with_submodules() -> Project {
+    let (dep, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", "")
+    });
+    let a = git::new("a", |project| project.no_manifest().file("lib.rs", ""));
+    let b = git::new("b", |project| project.no_manifest().file("lib.rs", ""));
+    git::add_submodule(&repo, &a.url().to_string(), Path::new("vendor/a"));
+    git::add_submodule(&repo, &b.url().to_string(), Path::new("vendor/b"));
+    git::commit(&repo);
+    b.root().rm_rf();
+    dep
+}
+
+#[cargo_test]
+fn git_dep_submodule_paths() {
+    let dep = dep_with_submodules();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.5.0"
+
+                    [dependencies]
+                    dep1 = {{ git = '{}', submodules = ["vendor/a"] }}
+                "#,
+                dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_stderr(&format!(
+            "\
+[UPDATING] git repository `{}`
+[UPDATING] git submodule `[..]a`
+[COMPILING] dep1 v0.5.0 ({}?submodules=vendor/a#[..])
+[COMPILING] foo v0.5.0 ([CWD])
+[FINISHED] [..]
+",
+            dep.url(),
+            dep.url()
+        ))
+        .run();
+
+    let checkout = only_checkout();
+    let name = checkout.file_name().unwrap().to_str().unwrap();
+    assert!(name.contains("-submodules-"), "{}", name);
+    assert!(checkout.join("vendor/a/lib.rs").is_file());
+    assert!(!checkout.join("vendor/b/lib.rs").exists());
+
+    let lock = p.read_lockfile();
+    assert!(lock.contains("?submodules=vendor/a#"), "{}", lock);
+}
+
+#[cargo_test]
+fn git_dep_without_submodules() {
+    let dep = dep_with_submodules();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.5.0"
+
+                    [dependencies]
+                    dep1 = {{ git = '{}' }}
+                "#,
+                dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[..]failed to update submodule `vendor/b`")
+        .run();
+
+    // The config default applies to dependencies without the key.
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SUBMODULES", "false")
+        .with_stderr_does_not_contain("[UPDATING] git submodule [..]")
+        .run();
+    // The failed checkout with every submodule is left next to this one.
+    let checkouts = only_checkout().parent().unwrap().to_path_buf();
+    let no_submodules = t!(fs::read_dir(&checkouts))
+        .map(|entry| t!(entry).path())
+        .find(|path| path.to_str().unwrap().ends_with("-no-submodules"))
+        .unwrap();
+    assert!(!no_submodules.join("vendor/a/lib.rs").exists());
+
+    p.change_file(
+        "Cargo.toml",
+        &format!(
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+
+                [dependencies]
+                dep1 = {{ git = '{}', submodules = false }}
+            "#,
+            dep.url()
+        ),
+    );
+    p.cargo("build")
+        .with_stderr_does_not_contain("[UPDATING] git submodule [..]")
+        .run();
+}
+
+#[cargo_test]
+fn git_dep_submodules_override_config() {
+    let (dep, repo) = git::new_repo("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", "")
+    });
+    let a = git::new("a", |project| project.no_manifest().file("lib.rs", ""));
+    git::add_submodule(&repo, &a.url().to_string(), Path::new("vendor/a"));
+    git::commit(&repo);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.5.0"
+
+                    [dependencies]
+                    dep1 = {{ git = '{}', submodules = true }}
+                "#,
+                dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    // An explicit `submodules = true` wins over the config.
+    p.cargo("build")
+        .env("CARGO_NET_GIT_SUBMODULES", "false")
+        .with_stderr_contains("[UPDATING] git submodule `[..]a`")
+        .run();
+    assert!(only_checkout().join("vendor/a/lib.rs").is_file());
+    let lock = p.read_lockfile();
+    assert!(lock.contains("?submodules=true#"), "{}", lock);
+}
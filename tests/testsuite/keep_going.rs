@@ -0,0 +1,82 @@
+//! Tests for --keep-going option.
+
+use cargo_test_support::{basic_manifest, project, Project};
+
+fn broken_deps_project() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                fails = { path = "fails" }
+                fails2 = { path = "fails2" }
+                ok = { path = "ok" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("fails/Cargo.toml", &basic_manifest("fails", "0.1.0"))
+        .file("fails/src/lib.rs", "compile_error!(\"fails\");")
+        .file("fails2/Cargo.toml", &basic_manifest("fails2", "0.1.0"))
+        .file("fails2/src/lib.rs", "compile_error!(\"fails2\");")
+        .file("ok/Cargo.toml", &basic_manifest("ok", "0.1.0"))
+        .file("ok/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("build --keep-going")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--keep-going` flag is unstable[..]
+See [..]
+See [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_keeps_going() {
+    let p = broken_deps_project();
+    p.cargo("build -j1 --keep-going -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[COMPILING] ok v0.1.0 ([..])")
+        .with_stderr_contains("[ERROR] could not compile `fails`[..]")
+        .with_stderr_contains("[ERROR] could not compile `fails2`[..]")
+        .with_stderr_contains("[NOTE] failed to build: fails, fails2")
+        .with_stderr_contains("[NOTE] skipped because a dependency failed to build: foo")
+        .with_stderr_contains("[ERROR] build failed")
+        .with_stderr_does_not_contain("[COMPILING] foo [..]")
+        .with_stderr_does_not_contain("[..]waiting for other jobs[..]")
+        .run();
+}
+
+#[cargo_test]
+fn check_keeps_going() {
+    let p = broken_deps_project();
+    p.cargo("check -j1 --keep-going -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[CHECKING] ok v0.1.0 ([..])")
+        .with_stderr_contains("[NOTE] failed to build: fails, fails2")
+        .with_stderr_contains("[NOTE] skipped because a dependency failed to build: foo")
+        .run();
+}
+
+#[cargo_test]
+fn without_keep_going_stops_early() {
+    let p = broken_deps_project();
+    p.cargo("build -j1")
+        .with_status(101)
+        .with_stderr_does_not_contain("[NOTE] failed to build[..]")
+        .with_stderr_does_not_contain("[COMPILING] foo [..]")
+        .run();
+}
@@ -0,0 +1,169 @@
+//! Tests for the `cargo licenses` command.
+
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+
+fn licensed(name: &str, license: &str) -> Package {
+    let mut pkg = Package::new(name, "0.1.0");
+    pkg.file(
+        "Cargo.toml",
+        &format!(
+            r#"
+                [package]
+                name = "{}"
+                version = "0.1.0"
+                license = "{}"
+            "#,
+            name, license
+        ),
+    )
+    .file("src/lib.rs", "");
+    pkg
+}
+
+fn foo_with_deps(deps: &[&str]) -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.1.0"
+
+                    [dependencies]
+                    {}
+                "#,
+                deps.join("\n")
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn grouped_by_license() {
+    licensed("bar", "MIT/Apache-2.0").publish();
+    licensed("baz", "MIT OR Apache-2.0").publish();
+    licensed("qux", "BSD-3-Clause").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#, r#"baz = "0.1""#, r#"qux = "0.1""#]);
+
+    p.cargo("licenses")
+        .with_stdout(
+            "\
+BSD-3-Clause (1)
+    qux v0.1.0
+MIT OR Apache-2.0 (2)
+    bar v0.1.0
+    baz v0.1.0
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_license() {
+    Package::new("bar", "0.1.0").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#]);
+
+    p.cargo("licenses")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `bar v0.1.0` does not specify a `license` or `license-file`",
+        )
+        .with_stderr_contains("[ERROR] 1 license problem found")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_expression() {
+    licensed("bar", "MIT, Apache-2.0").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#]);
+
+    p.cargo("licenses")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `bar v0.1.0` has an invalid license expression: \
+             invalid character `,` in license expression `MIT, Apache-2.0`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn allow_and_deny_from_config() {
+    licensed("bar", "MIT OR GPL-3.0").publish();
+    licensed("baz", "GPL-3.0").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#, r#"baz = "0.1""#]);
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [licenses]
+            allow = ["MIT", "Apache-2.0"]
+        "#,
+    );
+    p.cargo("licenses")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `baz v0.1.0` has license `GPL-3.0` which is not allowed",
+        )
+        .with_stderr_does_not_contain("[ERROR] package `bar v0.1.0`[..]")
+        .with_stderr_contains("[ERROR] 1 license problem found")
+        .run();
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [licenses]
+            deny = ["MIT", "GPL-3.0"]
+        "#,
+    );
+    p.cargo("licenses")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `bar v0.1.0` has license `MIT OR GPL-3.0` which is not allowed",
+        )
+        .with_stderr_contains("[ERROR] 2 license problems found")
+        .run();
+}
+
+#[cargo_test]
+fn denied_license_with_exception() {
+    licensed("bar", "GPL-3.0 WITH Classpath-exception-2.0").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#]);
+
+    p.change_file(
+        ".cargo/config.toml",
+        r#"
+            [licenses]
+            allow = ["GPL-3.0 WITH Classpath-exception-2.0"]
+            deny = ["GPL-3.0"]
+        "#,
+    );
+    p.cargo("licenses")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] package `bar v0.1.0` has license \
+             `GPL-3.0 WITH Classpath-exception-2.0` which is not allowed",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn notices_file() {
+    licensed("bar", "MIT")
+        .file("LICENSE-MIT", "bar MIT license text\n")
+        .publish();
+    licensed("baz", "MIT").publish();
+    let p = foo_with_deps(&[r#"bar = "0.1""#, r#"baz = "0.1""#]);
+
+    p.cargo("licenses --notices target/NOTICES")
+        .with_stderr_contains("[WARNING] no license text found for package `baz v0.1.0`")
+        .with_stderr_contains("[..]Writing [..]NOTICES")
+        .run();
+
+    let notices = p.read_file("target/NOTICES");
+    assert!(notices.starts_with("bar 0.1.0\nLicense: MIT\n"));
+    assert!(notices.contains("--- LICENSE-MIT ---\n\nbar MIT license text\n"));
+    assert!(!notices.contains("baz"));
+}
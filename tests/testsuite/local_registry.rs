@@ -488,3 +488,170 @@ fn crates_io_registry_url_is_optional() {
     p.cargo("build").with_stderr("[FINISHED] [..]").run();
     p.cargo("test").run();
 }
+
+#[cargo_test]
+fn create_local_registry() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.0.1")
+        .dep("baz", "0.1.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+    Package::new("quux", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+
+            [target.'cfg(target_os = "haiku")'.dependencies]
+            quux = "0.1.0"
+        "#,
+        )
+        .file(
+            "src/lib.rs",
+            "extern crate bar; pub fn foo() { bar::bar(); }",
+        )
+        .build();
+
+    p.cargo("local-registry --respect-source-config registry")
+        .with_stderr_contains("[..]Adding bar v0.0.1")
+        .with_stderr_contains("[..]Adding baz v0.1.0")
+        .with_stderr_contains("[..]Adding quux v0.1.0")
+        .with_stdout_contains("local-registry = \"registry\"")
+        .run();
+    assert!(p.root().join("registry/bar-0.0.1.crate").is_file());
+    assert!(p.root().join("registry/baz-0.1.0.crate").is_file());
+    // Only the index entry is added for packages not used on the host.
+    assert!(!p.root().join("registry/quux-0.1.0.crate").exists());
+    assert!(p.root().join("registry/index/qu/ux/quux").is_file());
+
+    p.change_file(
+        ".cargo/config",
+        r#"
+            [source.crates-io]
+            registry = 'https://wut'
+            replace-with = 'local'
+
+            [source.local]
+            local-registry = 'registry'
+        "#,
+    );
+    p.cargo("build")
+        .with_stderr_contains("[UNPACKING] bar v0.0.1 ([..])")
+        .with_stderr_contains("[UNPACKING] baz v0.1.0 ([..])")
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .run();
+
+    // Running it again against the local registry itself changes nothing.
+    p.cargo("local-registry --respect-source-config registry")
+        .with_stderr_does_not_contain("[..]Adding[..]")
+        .run();
+    assert_eq!(p.read_file("registry/index/3/b/bar").lines().count(), 1);
+}
+
+#[cargo_test]
+fn local_registry_all_platforms() {
+    Package::new("quux", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [target.'cfg(target_os = "haiku")'.dependencies]
+            quux = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("local-registry --respect-source-config --all-platforms registry")
+        .with_stderr_contains("[..]Adding quux v0.1.0")
+        .run();
+    assert!(p.root().join("registry/quux-0.1.0.crate").is_file());
+}
+
+#[cargo_test]
+fn add_to_local_registry() {
+    Package::new("bar", "0.0.1").publish();
+    Package::new("baz", "0.1.0").publish();
+
+    let registry = paths::root().join("registry");
+    // Both packages depend on `bar`, and `other` adds `baz`.
+    for (name, extra) in &[("foo", ""), ("other", r#"baz = "*""#)] {
+        let p = project()
+            .at(name)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [project]
+                    name = "{}"
+                    version = "0.0.1"
+                    authors = []
+
+                    [dependencies]
+                    bar = "0.0.1"
+                    {}
+                    "#,
+                    name, extra
+                ),
+            )
+            .file("src/lib.rs", "")
+            .build();
+        p.cargo("local-registry --respect-source-config")
+            .arg(&registry)
+            .run();
+    }
+
+    let index = t!(fs::read_to_string(registry.join("index/3/b/bar")));
+    assert_eq!(index.lines().count(), 1);
+    assert!(index.contains(r#""name":"bar","vers":"0.0.1""#));
+    assert!(registry.join("bar-0.0.1.crate").is_file());
+    assert!(registry.join("baz-0.1.0.crate").is_file());
+}
+
+#[cargo_test]
+fn local_registry_keeps_unlocked_requirements() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.0").dep("baz", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("local-registry --respect-source-config registry")
+        .with_stderr_contains("[..]Adding bar v0.1.0")
+        .run();
+    let index = p.read_file("registry/index/3/b/bar");
+    assert!(
+        index.contains(r#""name":"baz","req":"^0.1.0""#),
+        "{}",
+        index
+    );
+    assert!(!index.contains(r#""req":"=0.1.0""#), "{}", index);
+}
@@ -762,3 +762,112 @@ dependencies = [
 
     p.cargo("build --locked").run();
 }
+
+#[cargo_test]
+fn merge_conflict_prefers_versions_from_both_sides() {
+    let bar010 = Package::new("bar", "0.1.0").publish();
+    let bar011 = Package::new("bar", "0.1.1").publish();
+    Package::new("bar", "0.1.2").publish();
+    let baz010 = Package::new("baz", "0.1.0").publish();
+    let baz011 = Package::new("baz", "0.1.1").publish();
+    Package::new("baz", "0.1.2").publish();
+
+    let lockfile = format!(
+        r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+[[package]]
+name = "bar"
+<<<<<<< HEAD
+version = "0.1.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{}"
+||||||| merged common ancestors
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{}"
+=======
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{1}"
+>>>>>>> other-branch
+
+[[package]]
+name = "baz"
+<<<<<<< HEAD
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{}"
+=======
+version = "0.1.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "{}"
+>>>>>>> other-branch
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar",
+ "baz",
+]
+"#,
+        bar011, bar010, baz010, baz011,
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+                baz = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("Cargo.lock", &lockfile)
+        .build();
+
+    p.cargo("fetch")
+        .with_stderr_contains("[MERGING] conflicted lock file [..]Cargo.lock")
+        .with_stderr_contains("[SELECTED] bar v0.1.1 (ours: v0.1.1, theirs: v0.1.0)")
+        .with_stderr_contains("[SELECTED] baz v0.1.1 (ours: v0.1.0, theirs: v0.1.1)")
+        .run();
+
+    let lock = p.read_lockfile();
+    assert!(!lock.contains("<<<<<<<"));
+    assert!(lock.contains("name = \"bar\"\nversion = \"0.1.1\""));
+    assert!(lock.contains("name = \"baz\"\nversion = \"0.1.1\""));
+}
+
+#[cargo_test]
+fn merge_conflict_unterminated() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "Cargo.lock",
+            r#"[[package]]
+name = "foo"
+<<<<<<< HEAD
+version = "0.0.1"
+=======
+version = "0.0.2"
+"#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse lock file at: [..]Cargo.lock
+
+Caused by:
+  unterminated merge conflict, expected a `>>>>>>>` marker
+",
+        )
+        .run();
+}
@@ -13,6 +13,7 @@ extern crate cargo_test_macro;
 
 mod advanced_env;
 mod alt_registry;
+mod artifact_dep;
 mod bad_config;
 mod bad_manifest_path;
 mod bench;
@@ -20,6 +21,7 @@ mod build;
 mod build_plan;
 mod build_script;
 mod build_script_env;
+mod build_script_extra_link_arg;
 mod cache_messages;
 mod cargo_alias_config;
 mod cargo_command;
@@ -57,6 +59,8 @@ mod init;
 mod install;
 mod install_upgrade;
 mod jobserver;
+mod keep_going;
+mod licenses;
 mod list_targets;
 mod local_registry;
 mod locate_project;
@@ -93,6 +97,8 @@ mod publish;
 mod publish_lockfile;
 mod read_manifest;
 mod registry;
+mod registry_auth;
+mod registry_signatures;
 mod rename_deps;
 mod replace;
 mod required_features;
@@ -103,6 +109,7 @@ mod rustdoc;
 mod rustdoc_extern_html;
 mod rustdocflags;
 mod rustflags;
+mod sbom;
 mod search;
 mod shell_quoting;
 mod standard_lib;
@@ -114,6 +121,7 @@ mod tree_graph_features;
 mod unit_graph;
 mod update;
 mod vendor;
+mod verify_lock;
 mod verify_project;
 mod version;
 mod warn_on_failure;
@@ -2,19 +2,6 @@
 
 use cargo_test_support::{basic_manifest, cross_compile, project, rustc_host};
 
-#[cargo_test]
-fn double_target_rejected() {
-    let p = project()
-        .file("Cargo.toml", &basic_manifest("foo", "1.0.0"))
-        .file("src/main.rs", "fn main() {}")
-        .build();
-
-    p.cargo("build --target a --target b")
-        .with_stderr("error: specifying multiple `--target` flags requires `-Zmultitarget`")
-        .with_status(101)
-        .run();
-}
-
 #[cargo_test]
 fn simple_build() {
     if cross_compile::disabled() {
@@ -27,12 +14,11 @@ fn simple_build() {
         .file("src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("build -Z multitarget")
+    p.cargo("build")
         .arg("--target")
         .arg(&t1)
         .arg("--target")
         .arg(&t2)
-        .masquerade_as_nightly_cargo()
         .run();
 
     assert!(p.target_bin(t1, "foo").is_file());
@@ -51,12 +37,11 @@ fn simple_test() {
         .file("src/lib.rs", "fn main() {}")
         .build();
 
-    p.cargo("test -Z multitarget")
+    p.cargo("test")
         .arg("--target")
         .arg(&t1)
         .arg("--target")
         .arg(&t2)
-        .masquerade_as_nightly_cargo()
         .with_stderr_contains(&format!("[RUNNING] [..]{}[..]", t1))
         .with_stderr_contains(&format!("[RUNNING] [..]{}[..]", t2))
         .run();
@@ -69,10 +54,14 @@ fn simple_run() {
         .file("src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("run -Z multitarget --target a --target b")
-        .with_stderr("error: only one `--target` argument is supported")
+    p.cargo("run --target a --target b")
+        .with_stderr(
+            "\
+[ERROR] `cargo run` only supports a single target, but 2 targets were requested: a, b
+Pass a single `--target` flag to select one of them.
+",
+        )
         .with_status(101)
-        .masquerade_as_nightly_cargo()
         .run();
 }
 
@@ -88,12 +77,11 @@ fn simple_doc() {
         .file("src/lib.rs", "//! empty lib")
         .build();
 
-    p.cargo("doc -Z multitarget")
+    p.cargo("doc")
         .arg("--target")
         .arg(&t1)
         .arg("--target")
         .arg(&t2)
-        .masquerade_as_nightly_cargo()
         .run();
 
     assert!(p.build_dir().join(&t1).join("doc/foo/index.html").is_file());
@@ -112,12 +100,11 @@ fn simple_check() {
         .file("src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("check -Z multitarget")
+    p.cargo("check")
         .arg("--target")
         .arg(&t1)
         .arg("--target")
         .arg(&t2)
-        .masquerade_as_nightly_cargo()
         .run();
 }
 
@@ -132,13 +119,128 @@ fn same_value_twice() {
         .file("src/main.rs", "fn main() {}")
         .build();
 
-    p.cargo("build -Z multitarget")
+    p.cargo("build")
         .arg("--target")
         .arg(&t)
         .arg("--target")
         .arg(&t)
-        .masquerade_as_nightly_cargo()
         .run();
 
     assert!(p.target_bin(&t, "foo").is_file());
 }
+
+#[cargo_test]
+fn config_target_array() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let t1 = cross_compile::alternate();
+    let t2 = rustc_host();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "1.0.0"))
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [build]
+                    target = ["{}", "{}"]
+                "#,
+                t1, t2
+            ),
+        )
+        .build();
+
+    p.cargo("build").run();
+
+    assert!(p.target_bin(t1, "foo").is_file());
+    assert!(p.target_bin(&t2, "foo").is_file());
+
+    // A `--target` flag overrides the configured list.
+    p.cargo("run --target").arg(&t2).run();
+}
+
+#[cargo_test]
+fn doc_open_rejected() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "1.0.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("doc --open --target a --target b")
+        .with_stderr(
+            "\
+[ERROR] `cargo doc --open` only supports a single target, but 2 targets were requested: a, b
+Pass a single `--target` flag to select one of them.
+",
+        )
+        .with_status(101)
+        .run();
+}
+
+#[cargo_test]
+fn host_deps_built_once() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let t1 = cross_compile::alternate();
+    let t2 = rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "1.0.0"
+
+                [build-dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() { bar::bar(); }")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.0"))
+        .file("bar/src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    let output = p
+        .cargo("build -v")
+        .arg("--target")
+        .arg(&t1)
+        .arg("--target")
+        .arg(&t2)
+        .exec_with_output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    // The build dependency and the build script are compiled for the host
+    // once and shared by both targets.
+    let count = |needle: &str| stderr.lines().filter(|l| l.contains(needle)).count();
+    assert_eq!(count("--crate-name bar "), 1, "{}", stderr);
+    assert_eq!(count("--crate-name build_script_build "), 1, "{}", stderr);
+    assert_eq!(count("--crate-name foo "), 2, "{}", stderr);
+}
+
+#[cargo_test]
+fn out_dir_per_target() {
+    if cross_compile::disabled() {
+        return;
+    }
+    let t1 = cross_compile::alternate();
+    let t2 = rustc_host();
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "1.0.0"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Z unstable-options --out-dir out")
+        .arg("--target")
+        .arg(&t1)
+        .arg("--target")
+        .arg(&t2)
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let bin = format!("foo{}", std::env::consts::EXE_SUFFIX);
+    assert!(p.root().join("out").join(t1).join(&bin).is_file());
+    assert!(p.root().join("out").join(&t2).join(&bin).is_file());
+}
@@ -241,6 +241,8 @@ fn main(){
     p2.cargo("run --offline")
         .with_stderr(
             "\
+[NOTE] using the downloaded version v1.2.3 of `present_dep` \
+instead of v1.2.5, which is not downloaded
 [COMPILING] present_dep v1.2.3
 [COMPILING] foo v0.1.0 ([CWD])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
@@ -250,6 +252,215 @@ fn main(){
         .run();
 }
 
+#[cargo_test]
+fn prefer_offline() {
+    Package::new("present_dep", "1.2.3")
+        .file("Cargo.toml", &basic_manifest("present_dep", "1.2.3"))
+        .file(
+            "src/lib.rs",
+            r#"pub fn get_version()->&'static str {"1.2.3"}"#,
+        )
+        .publish();
+
+    // make package cached
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "=1.2.3"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build").run();
+
+    Package::new("present_dep", "1.2.5")
+        .file("Cargo.toml", &basic_manifest("present_dep", "1.2.5"))
+        .file(
+            "src/lib.rs",
+            r#"pub fn get_version()->&'static str {"1.2.5"}"#,
+        )
+        .publish();
+
+    let p2 = project()
+        .at("bar")
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "1.2"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "\
+extern crate present_dep;
+fn main(){
+    println!(\"{}\", present_dep::get_version());
+}",
+        )
+        .file(".cargo/config", "net.prefer-offline = true")
+        .build();
+
+    // The index is still updated, but the downloaded version wins.
+    p2.cargo("run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[NOTE] using the downloaded version v1.2.3 of `present_dep` \
+instead of v1.2.5, which is not downloaded
+[COMPILING] present_dep v1.2.3
+[COMPILING] bar v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+     Running `[..]`",
+        )
+        .with_stdout("1.2.3")
+        .run();
+
+    // Versions which aren't downloaded are still used if nothing else
+    // matches.
+    p2.change_file(
+        "Cargo.toml",
+        r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "1.2.4"
+        "#,
+    );
+    p2.cargo("run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] present_dep v1.2.5 ([..])
+[COMPILING] present_dep v1.2.5
+[COMPILING] bar v0.1.0 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+     Running `[..]`",
+        )
+        .with_stdout("1.2.5")
+        .run();
+}
+
+#[cargo_test]
+fn prefer_offline_falls_back_on_conflict() {
+    Package::new("present_dep", "1.2.3").publish();
+
+    // make package cached
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "=1.2.3"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build").run();
+
+    Package::new("present_dep", "1.2.5").publish();
+    Package::new("other", "1.0.0")
+        .dep("present_dep", "=1.2.5")
+        .publish();
+
+    // The downloaded version is tried first, but it conflicts with `other`,
+    // so the newer one is downloaded instead.
+    let p2 = project()
+        .at("bar")
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "1.2"
+            other = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(".cargo/config", "net.prefer-offline = true")
+        .build();
+
+    p2.cargo("build")
+        .with_stderr_contains("[DOWNLOADED] present_dep v1.2.5 ([..])")
+        .with_stderr_contains("[COMPILING] present_dep v1.2.5")
+        .with_stderr_does_not_contain("[NOTE] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn prefer_offline_no_note_for_unused_optional_dep() {
+    Package::new("present_dep", "1.2.3").publish();
+
+    // make package cached
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = "=1.2.3"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build").run();
+
+    Package::new("present_dep", "1.2.5").publish();
+
+    // Only packages which end up in the resolve are noted.
+    let p2 = project()
+        .at("bar")
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+
+            [dependencies]
+            present_dep = { version = "1.2", optional = true }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(".cargo/config", "net.prefer-offline = true")
+        .build();
+
+    p2.cargo("build")
+        .with_stderr_does_not_contain("[NOTE] [..]")
+        .run();
+    // The lock file from above already has the downloaded version.
+    fs::remove_file(p2.root().join("Cargo.lock")).unwrap();
+    p2.cargo("build --features present_dep")
+        .with_stderr_contains(
+            "[NOTE] using the downloaded version v1.2.3 of `present_dep` \
+             instead of v1.2.5, which is not downloaded",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn cargo_compile_forbird_git_httpsrepo_offline() {
     let p = project()
@@ -3,8 +3,9 @@
 use std::fs;
 
 use cargo_test_support::paths::CargoPathExt;
-use cargo_test_support::project;
-use cargo_test_support::registry::{self, api_path};
+use cargo_test_support::registry::{self, api_path, Package};
+use cargo_test_support::registry_server::RegistryServer;
+use cargo_test_support::{basic_manifest, project};
 
 fn setup(name: &str, content: Option<&str>) {
     let dir = api_path().join(format!("api/v1/crates/{}", name));
@@ -118,3 +119,34 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn owners_on_server() {
+    Package::new("bar", "0.0.1").publish();
+    let _server = RegistryServer::start();
+
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("owner -l bar").with_stdout("publisher\n").run();
+    p.cargo("owner -a octocat bar")
+        .with_stderr_contains("[..]user octocat has been invited to be an owner of crate bar")
+        .run();
+    p.cargo("owner -l bar")
+        .with_stdout("publisher\noctocat\n")
+        .run();
+
+    p.cargo("owner -r publisher bar").run();
+    p.cargo("owner -l bar").with_stdout("octocat\n").run();
+    p.cargo("owner -r octocat bar")
+        .with_status(101)
+        .with_stderr_contains("[..]cannot remove all individual owners of a crate")
+        .run();
+
+    p.cargo("owner -l baz")
+        .with_status(101)
+        .with_stderr_contains("[..]crate `baz` does not exist")
+        .run();
+}
@@ -2,7 +2,7 @@
 
 use std::env;
 
-use cargo_test_support::{is_nightly, project};
+use cargo_test_support::{basic_manifest, is_nightly, project};
 
 #[cargo_test]
 fn profile_overrides() {
@@ -578,3 +578,344 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn rustflags_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-rustflags"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.release]
+            rustflags = ["--cfg", "from_profile"]
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            #[cfg(not(from_profile))]
+            compile_error!("missing profile rustflags");
+            fn main() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("build --release -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[RUNNING] `rustc [..] --cfg from_profile [..]`
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rustflags_package_override() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["profile-rustflags"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [profile.dev.package.bar]
+            rustflags = ["--cfg", "from_override"]
+        "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+            #[cfg(from_override)]
+            compile_error!("override applied to the wrong package");
+            fn main() { bar::bar(); }
+        "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file(
+            "bar/src/lib.rs",
+            r#"
+            #[cfg(not(from_override))]
+            compile_error!("missing profile rustflags");
+            pub fn bar() {}
+        "#,
+        )
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..] --cfg from_override [..]`")
+        .with_stderr_line_without(&["[RUNNING] `rustc --crate-name foo"], &["from_override"])
+        .run();
+
+    // Changing the flags of the override only rebuilds the overridden package
+    // and the packages depending on it.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        cargo-features = ["profile-rustflags"]
+
+        [package]
+        name = "foo"
+        version = "0.1.0"
+
+        [dependencies]
+        bar = { path = "bar" }
+
+        [profile.dev.package.bar]
+        rustflags = ["--cfg", "from_override", "--cfg", "other"]
+        "#,
+    );
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] bar [..]
+[RUNNING] `rustc --crate-name bar [..] --cfg from_override --cfg other [..]`
+[COMPILING] foo [..]
+[RUNNING] `rustc --crate-name foo [..]`
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn rustflags_requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev.package."*"]
+            rustflags = ["-C", "target-cpu=native"]
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `profile-rustflags` is required
+
+  consider adding `cargo-features = [\"profile-rustflags\"]` to the manifest
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn split_debuginfo_and_debug_level() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            debug = "line-tables-only"
+            split-debuginfo = "packed"
+
+            [profile.release]
+            split-debuginfo = "packed"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --build-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains(
+            "[..]\"-C\",\"debuginfo=line-tables-only\",\"-C\",\"split-debuginfo=packed\"[..]",
+        )
+        .run();
+
+    // Without debuginfo there is nothing to split.
+    p.cargo("build --build-plan -Zunstable-options --release")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_does_not_contain("[..]split-debuginfo[..]")
+        .run();
+}
+
+#[cargo_test]
+fn split_debuginfo_rejects_invalid_option() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            split-debuginfo = "split"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  `split-debuginfo` setting of `split` is not a valid setting, must be `off`, `packed` or `unpacked`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+#[cfg(target_os = "linux")]
+fn clean_removes_split_debuginfo() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build").run();
+    // Stand-ins for the files produced with `split-debuginfo`.
+    let dwp = p.target_debug_dir().join("foo.dwp");
+    let hashed_dwp = p.target_debug_dir().join("deps/foo-0123456789abcdef.dwp");
+    let dwo = p
+        .target_debug_dir()
+        .join("deps/foo-0123456789abcdef.foo.1a2b3c4d-cgu.0.rcgu.dwo");
+    for path in &[&dwp, &hashed_dwp, &dwo] {
+        std::fs::write(path, "").unwrap();
+    }
+
+    p.cargo("clean -p foo").run();
+    assert!(!dwp.exists());
+    assert!(!hashed_dwp.exists());
+    assert!(!dwo.exists());
+}
+
+#[cargo_test]
+fn codegen_backend_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["codegen-backend"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [profile.dev.package.bar]
+            codegen-backend = "llvm"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build --build-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains(
+            "[..]\"--crate-name\",\"bar\",[..]\"-Z\",\"codegen-backend=llvm\"[..]\"--crate-name\",\"foo\"[..]",
+        )
+        .with_stdout_does_not_contain("[..]\"--crate-name\",\"foo\",[..]codegen-backend[..]")
+        .run();
+}
+
+#[cargo_test]
+fn codegen_backend_not_available() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["codegen-backend"]
+
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            codegen-backend = "nonexistent"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] codegen backend `nonexistent` selected by profile `dev` for package `foo` is not available
+available backends: llvm[..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn codegen_backend_requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [profile.dev]
+            codegen-backend = "cranelift"
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
+
+Caused by:
+  feature `codegen-backend` is required
+
+  consider adding `cargo-features = [\"codegen-backend\"]` to the manifest
+",
+        )
+        .run();
+}
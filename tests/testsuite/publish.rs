@@ -3,7 +3,8 @@
 use cargo_test_support::git::{self, repo};
 use cargo_test_support::paths;
 use cargo_test_support::registry::{self, registry_path, registry_url, Package};
-use cargo_test_support::{basic_manifest, no_such_file_err_msg, project, publish};
+use cargo_test_support::registry_server::RegistryServer;
+use cargo_test_support::{basic_manifest, cargo_process, no_such_file_err_msg, project, publish};
 use std::fs;
 
 const CLEAN_FOO_JSON: &str = r#"
@@ -1457,3 +1458,343 @@ Caused by:
         ))
         .run();
 }
+
+#[cargo_test]
+fn publish_to_server_then_depend() {
+    let _server = RegistryServer::start();
+
+    let bar = project()
+        .at("bar")
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "bar"
+        "#,
+        )
+        .file("src/lib.rs", "pub fn bar() {}")
+        .build();
+
+    bar.cargo("publish --no-verify")
+        .with_stderr_contains("[UPLOADING] bar v0.0.1 ([CWD])")
+        .run();
+    publish::validate_upload(
+        r#"
+        {
+          "authors": [],
+          "badges": {},
+          "categories": [],
+          "deps": [],
+          "description": "bar",
+          "documentation": null,
+          "features": {},
+          "homepage": null,
+          "keywords": [],
+          "license": "MIT",
+          "license_file": null,
+          "links": null,
+          "name": "bar",
+          "readme": null,
+          "readme_file": null,
+          "repository": null,
+          "vers": "0.0.1"
+          }
+        "#,
+        "bar-0.0.1.crate",
+        &["Cargo.toml", "Cargo.toml.orig", "src/lib.rs"],
+    );
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+        "#,
+        )
+        .file(
+            "src/lib.rs",
+            "extern crate bar; pub fn foo() { bar::bar(); }",
+        )
+        .build();
+
+    p.cargo("build")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.0.1 (registry `[..]`)
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    bar.cargo("publish --no-verify")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] api errors (status 200 OK): crate version `0.0.1` is already uploaded",
+        )
+        .run();
+}
+
+fn publish_workspace_project() -> cargo_test_support::Project {
+    let member = |name: &str, deps: &str| {
+        format!(
+            r#"
+            [project]
+            name = "{}"
+            version = "0.1.0"
+            authors = []
+            license = "MIT"
+            description = "{}"
+            homepage = "https://example.com"
+
+            [dependencies]
+            {}
+            "#,
+            name, name, deps
+        )
+    };
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["foo", "zoo", "private"]
+        "#,
+        )
+        .file(
+            "foo/Cargo.toml",
+            &member("foo", r#"zoo = { path = "../zoo", version = "0.1.0" }"#),
+        )
+        .file("foo/src/lib.rs", "pub fn foo() { zoo::zoo(); }")
+        .file("zoo/Cargo.toml", &member("zoo", ""))
+        .file("zoo/src/lib.rs", "pub fn zoo() {}")
+        .file("private/Cargo.toml", &basic_manifest("private", "0.1.0"))
+        .file("private/src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn publish_workspace() {
+    let _server = RegistryServer::start();
+
+    let p = publish_workspace_project();
+    p.change_file(
+        "private/Cargo.toml",
+        r#"
+            [project]
+            name = "private"
+            version = "0.1.0"
+            authors = []
+            publish = false
+        "#,
+    );
+
+    // `zoo` is packaged first, and `foo` is verified against the packaged
+    // `zoo`. `foo` is uploaded once `zoo` is in the index.
+    p.cargo("publish --workspace")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] using `registry.token` config value with source replacement is deprecated
+This may become a hard error in the future[..]
+Use the --token command-line flag to remove this warning.
+[PACKAGING] zoo v0.1.0 ([CWD]/zoo)
+[VERIFYING] zoo v0.1.0 ([CWD]/zoo)
+[COMPILING] zoo v0.1.0 ([CWD]/target/package/zoo-0.1.0)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[PACKAGING] foo v0.1.0 ([CWD]/foo)
+[VERIFYING] foo v0.1.0 ([CWD]/foo)
+[COMPILING] zoo v0.1.0 ([CWD]/target/package/zoo-0.1.0)
+[COMPILING] foo v0.1.0 ([CWD]/target/package/foo-0.1.0)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[UPLOADING] zoo v0.1.0 ([CWD]/zoo)
+[UPDATING] `[..]` index
+[UPLOADING] foo v0.1.0 ([CWD]/foo)
+",
+        )
+        .run();
+
+    let user = project()
+        .at("user")
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "user"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            foo = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "pub fn user() { foo::foo(); }")
+        .build();
+    user.cargo("build")
+        .with_stderr_contains("[DOWNLOADED] foo v0.1.0 (registry `[..]`)")
+        .with_stderr_contains("[DOWNLOADED] zoo v0.1.0 (registry `[..]`)")
+        .run();
+}
+
+#[cargo_test]
+fn publish_workspace_exclude() {
+    let _server = RegistryServer::start();
+
+    let p = publish_workspace_project();
+    p.cargo("publish --workspace --exclude private --exclude foo --no-verify")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] using `registry.token` config value with source replacement is deprecated
+This may become a hard error in the future[..]
+Use the --token command-line flag to remove this warning.
+[PACKAGING] zoo v0.1.0 ([CWD]/zoo)
+[UPLOADING] zoo v0.1.0 ([CWD]/zoo)
+",
+        )
+        .run();
+
+    p.cargo("publish -p nope --no-verify")
+        .with_status(101)
+        .with_stderr("[ERROR] package `nope` is not a member of the workspace")
+        .run();
+}
+
+#[cargo_test]
+fn publish_workspace_partial_failure() {
+    let _server = RegistryServer::start();
+    // `foo` is already published, so uploading it fails.
+    Package::new("foo", "0.1.0").publish();
+
+    let p = publish_workspace_project();
+    p.cargo("publish --workspace --exclude private --no-verify")
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] using `registry.token` config value with source replacement is deprecated
+This may become a hard error in the future[..]
+Use the --token command-line flag to remove this warning.
+[PACKAGING] zoo v0.1.0 ([CWD]/zoo)
+[PACKAGING] foo v0.1.0 ([CWD]/foo)
+[UPLOADING] zoo v0.1.0 ([CWD]/zoo)
+[UPDATING] `[..]` index
+[UPLOADING] foo v0.1.0 ([CWD]/foo)
+[NOTE] these packages were published: `zoo v0.1.0`
+[NOTE] these packages were not published: `foo v0.1.0`
+[ERROR] failed to publish `foo v0.1.0`
+
+Caused by:
+  api errors (status 200 OK): crate version `0.1.0` is already uploaded
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn publish_workspace_with_lockfile() {
+    let _server = RegistryServer::start();
+
+    let member = |name: &str, deps: &str| {
+        format!(
+            r#"
+            [project]
+            name = "{}"
+            version = "0.1.0"
+            authors = []
+            license = "MIT"
+            description = "{}"
+            homepage = "https://example.com"
+
+            [dependencies]
+            {}
+            "#,
+            name, name, deps
+        )
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["bar", "foo", "zoo"]
+        "#,
+        )
+        .file("bar/Cargo.toml", &member("bar", ""))
+        .file("bar/src/lib.rs", "")
+        .file(
+            "foo/Cargo.toml",
+            &member("foo", r#"zoo = { path = "../zoo", version = "0.1.0" }"#),
+        )
+        .file("foo/src/main.rs", "fn main() { zoo::zoo(); }")
+        .file("zoo/Cargo.toml", &member("zoo", ""))
+        .file("zoo/src/lib.rs", "pub fn zoo() {}")
+        .build();
+
+    // `foo` has a binary, so its `Cargo.lock` is packaged, but it is still
+    // verified against the packaged `zoo` before anything is uploaded.
+    p.cargo("publish --workspace --dry-run")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[PACKAGING] bar v0.1.0 ([CWD]/bar)
+[VERIFYING] bar v0.1.0 ([CWD]/bar)
+[COMPILING] bar v0.1.0 ([CWD]/target/package/bar-0.1.0)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[PACKAGING] zoo v0.1.0 ([CWD]/zoo)
+[VERIFYING] zoo v0.1.0 ([CWD]/zoo)
+[COMPILING] zoo v0.1.0 ([CWD]/target/package/zoo-0.1.0)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[PACKAGING] foo v0.1.0 ([CWD]/foo)
+[VERIFYING] foo v0.1.0 ([CWD]/foo)
+[COMPILING] zoo v0.1.0 ([CWD]/target/package/zoo-0.1.0)
+[COMPILING] foo v0.1.0 ([CWD]/target/package/foo-0.1.0)
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[UPLOADING] bar v0.1.0 ([CWD]/bar)
+[WARNING] aborting upload due to dry run
+[UPLOADING] zoo v0.1.0 ([CWD]/zoo)
+[WARNING] aborting upload due to dry run
+[UPLOADING] foo v0.1.0 ([CWD]/foo)
+[WARNING] aborting upload due to dry run
+",
+        )
+        .run();
+
+    // Only `foo` waits for the index, and its `Cargo.lock` is generated
+    // again against the registry.
+    p.cargo("publish --workspace --no-verify")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] using `registry.token` config value with source replacement is deprecated
+This may become a hard error in the future[..]
+Use the --token command-line flag to remove this warning.
+[PACKAGING] bar v0.1.0 ([CWD]/bar)
+[PACKAGING] zoo v0.1.0 ([CWD]/zoo)
+[PACKAGING] foo v0.1.0 ([CWD]/foo)
+[UPLOADING] bar v0.1.0 ([CWD]/bar)
+[UPLOADING] zoo v0.1.0 ([CWD]/zoo)
+[UPDATING] `[..]` index
+[PACKAGING] foo v0.1.0 ([CWD]/foo)
+[UPLOADING] foo v0.1.0 ([CWD]/foo)
+",
+        )
+        .run();
+
+    cargo_process("install foo --locked")
+        .with_stderr_contains("[INSTALLED] package `foo v0.1.0` (executable `foo[EXE]`)")
+        .run();
+}
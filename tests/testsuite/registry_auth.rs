@@ -0,0 +1,133 @@
+//! Tests for registries which require authentication for downloads.
+
+use std::fs;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::{git, paths, project, t, Project};
+
+/// Sets `auth-required` in the `config.json` of the alternative registry,
+/// with downloads from `dl`.
+fn require_auth(dl: &str) {
+    let repo = t!(git2::Repository::open(registry::alt_registry_path()));
+    t!(fs::write(
+        registry::alt_registry_path().join("config.json"),
+        format!(r#"{{"dl": "{}", "auth-required": true}}"#, dl),
+    ));
+    git::add(&repo);
+    git::commit(&repo);
+}
+
+/// Serves a single download of `body`, returning the request headers.
+fn serve_once(body: Vec<u8>) -> (String, JoinHandle<Vec<String>>) {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let t = thread::spawn(move || {
+        let mut conn = BufReader::new(server.accept().unwrap().0);
+        let headers: Vec<String> = conn
+            .by_ref()
+            .lines()
+            .map(|s| s.unwrap())
+            .take_while(|s| s.len() > 2)
+            .map(|s| s.trim().to_string())
+            .collect();
+        let conn = conn.get_mut();
+        write!(
+            conn,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        conn.write_all(&body).unwrap();
+        headers
+    });
+    (format!("http://{}", addr), t)
+}
+
+fn foo() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies.bar]
+                version = "0.0.1"
+                registry = "alternative"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn token_sent_with_downloads() {
+    let mut bar = Package::new("bar", "0.0.1");
+    bar.alternative(true).publish();
+    let (url, server) = serve_once(t!(fs::read(bar.archive_dst())));
+    require_auth(&url);
+
+    let p = foo();
+    p.cargo("build")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.0.1 (registry `[..]`)
+[COMPILING] bar v0.0.1 (registry `[..]`)
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    let headers = server.join().unwrap();
+    assert_eq!(headers[0], "GET /bar/0.0.1/download HTTP/1.1");
+    assert!(
+        headers.iter().any(|h| h == "Authorization: api-token"),
+        "{:?}",
+        headers
+    );
+}
+
+#[cargo_test]
+fn missing_token() {
+    Package::new("bar", "0.0.1").alternative(true).publish();
+    require_auth("http://127.0.0.1:1");
+    fs::remove_file(paths::home().join(".cargo/credentials")).unwrap();
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  no token found for registry `alternative`, which requires authentication",
+        )
+        .with_stderr_contains("  Run `cargo login --registry alternative` to add a token.")
+        .run();
+}
+
+#[cargo_test]
+fn token_from_config() {
+    let mut bar = Package::new("bar", "0.0.1");
+    bar.alternative(true).publish();
+    let (url, server) = serve_once(t!(fs::read(bar.archive_dst())));
+    require_auth(&url);
+    fs::remove_file(paths::home().join(".cargo/credentials")).unwrap();
+
+    let p = foo();
+    p.cargo("build")
+        .env("CARGO_REGISTRIES_ALTERNATIVE_TOKEN", "env-token")
+        .run();
+
+    let headers = server.join().unwrap();
+    assert!(
+        headers.iter().any(|h| h == "Authorization: env-token"),
+        "{:?}",
+        headers
+    );
+}
@@ -0,0 +1,204 @@
+//! Tests for registries which sign their index entries.
+
+use std::fs;
+
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::{git, paths, project, t, Project};
+
+/// Sets the `signing-key` in the `config.json` of the alternative registry.
+fn set_signing_key(key: Option<&str>) {
+    let repo = t!(git2::Repository::open(registry::alt_registry_path()));
+    let key = match key {
+        Some(key) => format!(r#", "signing-key": "{}""#, key),
+        None => String::new(),
+    };
+    t!(fs::write(
+        registry::alt_registry_path().join("config.json"),
+        format!(
+            r#"{{"dl": "{}", "api": "{}"{}}}"#,
+            registry::alt_dl_url(),
+            registry::alt_api_url(),
+            key
+        ),
+    ));
+    git::add(&repo);
+    git::commit(&repo);
+}
+
+fn require_signatures() {
+    let config = paths::home().join(".cargo/config");
+    let mut contents = t!(fs::read_to_string(&config));
+    contents.push_str("require-signatures = true\n");
+    t!(fs::write(&config, contents));
+}
+
+fn foo() -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies.bar]
+                version = "0.0.1"
+                registry = "alternative"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn signed_entries() {
+    Package::new("bar", "0.0.1")
+        .alternative(true)
+        .signed(true)
+        .publish();
+    set_signing_key(Some(&registry::signing_key()));
+
+    let p = foo();
+    p.cargo("build")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.0.1 (registry `[..]`)
+[COMPILING] bar v0.0.1 (registry `[..]`)
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn unsigned_entries_allowed_by_default() {
+    Package::new("bar", "0.0.1").alternative(true).publish();
+    set_signing_key(Some(&registry::signing_key()));
+
+    foo().cargo("build").run();
+}
+
+#[cargo_test]
+fn wrong_key() {
+    Package::new("bar", "0.0.1")
+        .alternative(true)
+        .signed(true)
+        .publish();
+    set_signing_key(Some(
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+    ));
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  invalid signature for package `bar v0.0.1 (registry `[..]`)` in `[..]` index",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_key() {
+    Package::new("bar", "0.0.1").alternative(true).publish();
+    set_signing_key(Some("not-hex"));
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("  invalid `signing-key` in the config.json of `[..]` index: [..]")
+        .run();
+}
+
+#[cargo_test]
+fn required_but_unsigned() {
+    Package::new("bar", "0.0.1").alternative(true).publish();
+    set_signing_key(Some(&registry::signing_key()));
+    require_signatures();
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  package `bar v0.0.1 (registry `[..]`)` is not signed, \
+             and registry `alternative` requires signatures",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn required_without_key() {
+    Package::new("bar", "0.0.1").alternative(true).publish();
+    require_signatures();
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  registry `alternative` requires signatures, \
+             but its config.json has no `signing-key`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn tampered_crate_file() {
+    Package::new("bar", "0.0.1")
+        .alternative(true)
+        .signed(true)
+        .publish();
+    set_signing_key(Some(&registry::signing_key()));
+
+    let p = foo();
+    p.cargo("build").run();
+
+    // Swap out the cached `.crate` file behind the index's back.
+    let registry = paths::home().join(".cargo/registry");
+    let cached = glob::glob(&format!("{}/cache/*/bar-0.0.1.crate", registry.display()))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    t!(fs::write(&cached, b"not the published crate"));
+
+    // The sources are already unpacked, but they still aren't used.
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[..]failed to unpack package `bar v0.0.1 (registry `[..]`)`")
+        .with_stderr_contains(
+            "  the checksum of `bar v0.0.1 (registry `[..]`)` \
+             does not match its signed entry in `[..]` index",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn tampered_deps() {
+    Package::new("baz", "0.0.1").alternative(true).publish();
+    Package::new("evil", "0.0.1").alternative(true).publish();
+    Package::new("bar", "0.0.1")
+        .alternative(true)
+        .registry_dep("baz", "0.0.1")
+        .signed(true)
+        .publish();
+    set_signing_key(Some(&registry::signing_key()));
+
+    // Redirect the dependency of the signed entry to another package.
+    let repo = t!(git2::Repository::open(registry::alt_registry_path()));
+    let index = registry::alt_registry_path().join("3/b/bar");
+    let entry = t!(fs::read_to_string(&index)).replace(r#""name":"baz""#, r#""name":"evil""#);
+    assert!(entry.contains("evil"));
+    t!(fs::write(&index, entry));
+    git::add(&repo);
+    git::commit(&repo);
+
+    let p = foo();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  invalid signature for package `bar v0.0.1 (registry `[..]`)` in `[..]` index",
+        )
+        .run();
+}
@@ -0,0 +1,171 @@
+//! Tests for the `cargo sbom` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project};
+use std::fs;
+
+#[cargo_test]
+fn cyclonedx_follows_unit_graph() {
+    let bar = Package::new("bar", "0.1.0")
+        .feature("extra", &[])
+        .file("src/lib.rs", "")
+        .publish();
+    let bb = Package::new("bb", "0.1.0").publish();
+    Package::new("winonly", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                license = "MIT OR Apache-2.0"
+
+                [dependencies]
+                bar = { version = "0.1", features = ["extra"] }
+
+                [build-dependencies]
+                bb = "0.1"
+
+                [target.'cfg(any())'.dependencies]
+                winonly = "0.1"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("sbom")
+        .with_json(&format!(
+            r#"
+            {{
+              "bomFormat": "CycloneDX",
+              "specVersion": "1.3",
+              "version": 1,
+              "metadata": {{
+                "timestamp": "[..]",
+                "tools": [{{"vendor": "rust-lang", "name": "cargo", "version": "[..]"}}],
+                "component": {{
+                  "type": "application",
+                  "bom-ref": "foo 0.1.0 (path+file://[..])",
+                  "name": "foo",
+                  "version": "0.1.0",
+                  "scope": "required",
+                  "licenses": [{{"expression": "MIT OR Apache-2.0"}}],
+                  "externalReferences": [{{"type": "distribution", "url": "path+file://[..]"}}]
+                }}
+              }},
+              "components": [
+                {{
+                  "type": "library",
+                  "bom-ref": "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                  "name": "bar",
+                  "version": "0.1.0",
+                  "scope": "required",
+                  "hashes": [{{"alg": "SHA-256", "content": "{}"}}],
+                  "purl": "pkg:cargo/bar@0.1.0",
+                  "externalReferences": [{{
+                    "type": "distribution",
+                    "url": "registry+https://github.com/rust-lang/crates.io-index"
+                  }}],
+                  "properties": [{{"name": "cargo:features", "value": "extra"}}]
+                }},
+                {{
+                  "type": "library",
+                  "bom-ref": "bb 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                  "name": "bb",
+                  "version": "0.1.0",
+                  "scope": "excluded",
+                  "hashes": [{{"alg": "SHA-256", "content": "{}"}}],
+                  "purl": "pkg:cargo/bb@0.1.0",
+                  "externalReferences": [{{
+                    "type": "distribution",
+                    "url": "registry+https://github.com/rust-lang/crates.io-index"
+                  }}]
+                }}
+              ],
+              "dependencies": [
+                {{
+                  "ref": "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                  "dependsOn": []
+                }},
+                {{
+                  "ref": "bb 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                  "dependsOn": []
+                }},
+                {{
+                  "ref": "foo 0.1.0 (path+file://[..])",
+                  "dependsOn": [
+                    "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                    "bb 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)"
+                  ]
+                }}
+              ]
+            }}
+            "#,
+            bar, bb
+        ))
+        .run();
+}
+
+#[cargo_test]
+fn spdx_out_dir() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("sbom --format spdx --out-dir sbom")
+        .with_stderr_contains("[..]Writing [..]sbom/foo-0.1.0-foo-bin.spdx.json")
+        .with_stderr_contains("[..]Writing [..]sbom/foo-0.1.0-foo-lib.spdx.json")
+        .run();
+
+    let read = |name: &str| -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(p.root().join("sbom").join(name)).unwrap())
+            .unwrap()
+    };
+    let doc = read("foo-0.1.0-foo-bin.spdx.json");
+    let lib_doc = read("foo-0.1.0-foo-lib.spdx.json");
+    assert_eq!(doc["spdxVersion"], "SPDX-2.2");
+    assert_eq!(doc["name"], "foo-0.1.0-foo-bin");
+    let namespace = doc["documentNamespace"].as_str().unwrap();
+    assert!(namespace.starts_with("https://spdx.org/spdxdocs/foo-0.1.0-foo-bin-"));
+    assert_ne!(doc["documentNamespace"], lib_doc["documentNamespace"]);
+    let packages = doc["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0]["name"], "bar");
+    assert_eq!(packages[0]["licenseDeclared"], "NOASSERTION");
+    assert_eq!(
+        packages[0]["externalRefs"][0]["referenceLocator"],
+        "pkg:cargo/bar@0.1.0"
+    );
+    assert_eq!(packages[1]["name"], "foo");
+}
+
+#[cargo_test]
+fn only_selected_targets() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "")
+        .file("src/bin/a.rs", "fn main() {}")
+        .file("src/bin/b.rs", "fn main() {}")
+        .build();
+
+    p.cargo("sbom --bin a --out-dir out")
+        .with_stderr("[..]Writing [..]out/foo-0.1.0-a-bin.cdx.json")
+        .run();
+    assert!(!p.root().join("out/foo-0.1.0-b-bin.cdx.json").exists());
+}
@@ -3,7 +3,8 @@
 use cargo_test_support::cargo_process;
 use cargo_test_support::git::repo;
 use cargo_test_support::paths;
-use cargo_test_support::registry::{api_path, registry_path, registry_url};
+use cargo_test_support::registry::{api_path, registry_path, registry_url, Package};
+use cargo_test_support::registry_server::RegistryServer;
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
@@ -244,3 +245,30 @@ fn multiple_query_params() {
         .with_stdout_contains(SEARCH_RESULTS)
         .run();
 }
+
+#[cargo_test]
+fn search_on_server() {
+    Package::new("bar", "0.0.1").publish();
+    Package::new("bar", "0.0.2").publish();
+    Package::new("bar", "0.0.3").yanked(true).publish();
+    Package::new("baz", "0.1.0").publish();
+    Package::new("quux", "0.1.0").publish();
+    let _server = RegistryServer::start();
+
+    cargo_process("search ba")
+        .with_stdout(
+            "\
+bar = \"0.0.2\"
+baz = \"0.1.0\"
+",
+        )
+        .run();
+    cargo_process("search ba --limit 1")
+        .with_stdout(
+            "\
+bar = \"0.0.2\"
+... and 1 crates more (use --limit N to see more)
+",
+        )
+        .run();
+}
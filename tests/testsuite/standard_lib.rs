@@ -655,3 +655,156 @@ fn different_features() {
         .target_host()
         .run();
 }
+
+#[cargo_test]
+fn build_std_config() {
+    let setup = match setup() {
+        Some(s) => s,
+        None => return,
+    };
+    let p = project()
+        .file("src/lib.rs", "#![no_std] pub fn foo() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-std = ["core"]
+            "#,
+        )
+        .build();
+    let mut build = p.cargo("build -v");
+    enable_build_std(&mut build, &setup);
+    build
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name core [..]`")
+        .with_stderr_does_not_contain("[..]libstd[..]")
+        .run();
+
+    let mut build = p.cargo("build");
+    enable_build_std(&mut build, &setup);
+    build
+        .with_status(101)
+        .with_stderr("[ERROR] build.build-std requires --target")
+        .run();
+}
+
+#[cargo_test]
+fn build_std_target_config() {
+    let setup = match setup() {
+        Some(s) => s,
+        None => return,
+    };
+    let p = project()
+        .file("src/lib.rs", "#![no_std] pub fn foo() {}")
+        .file(
+            ".cargo/config.toml",
+            &format!(
+                r#"
+                    [target.{}]
+                    build-std = ["core"]
+                "#,
+                rustc_host()
+            ),
+        )
+        .build();
+    let mut build = p.cargo("build -v");
+    enable_build_std(&mut build, &setup);
+    build
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name core [..]`")
+        .run();
+
+    // Only applies when the target is requested explicitly.
+    p.cargo("build -v")
+        .with_stderr_does_not_contain("[..]--crate-name core[..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_std_features_profile() {
+    let setup = match setup() {
+        Some(s) => s,
+        None => return,
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.dev]
+                build-std-features = ["feature1"]
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            "
+                pub fn foo() {
+                    std::conditional_function();
+                }
+            ",
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                build-std = ["std"]
+            "#,
+        )
+        .build();
+    let mut build = p.cargo("build");
+    enable_build_std(&mut build, &setup);
+    build.target_host().run();
+}
+
+#[cargo_test]
+fn panic_abort_and_lto() {
+    let setup = match setup() {
+        Some(s) => s,
+        None => return,
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [profile.dev]
+                panic = "abort"
+                lto = true
+            "#,
+        )
+        .file("src/main.rs", "fn main() { std::custom_api(); }")
+        .build();
+    p.cargo("build -v")
+        .build_std(&setup)
+        .target_host()
+        .with_stderr_contains("[RUNNING] `[..]--crate-name core [..]-C panic=abort[..]`")
+        .with_stderr_does_not_contain(
+            "[RUNNING] `[..]--crate-name core [..]-Clinker-plugin-lto[..]`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn offline() {
+    let setup = match setup() {
+        Some(s) => s,
+        None => return,
+    };
+    let p = project()
+        .file("src/lib.rs", "pub fn foo() { std::custom_api(); }")
+        .build();
+    // The first build downloads the registry dependencies of std.
+    p.cargo("build").build_std(&setup).target_host().run();
+    p.cargo("clean").run();
+    p.cargo("build --offline")
+        .build_std(&setup)
+        .target_host()
+        .with_stderr_does_not_contain("[UPDATING][..]")
+        .run();
+}
@@ -63,3 +63,100 @@ fn timings_works() {
 
     p.cargo("doc -Ztimings").masquerade_as_nightly_cargo().run();
 }
+
+#[cargo_test]
+fn json_report() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Ztimings=json-report")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 [..]
+      Timing report saved to [..]/foo/cargo-timing-[..].json
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    let report: serde_json::Value =
+        serde_json::from_str(&p.read_file("cargo-timing.json")).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["profile"], "dev");
+    assert_eq!(report["dirty_units"], 2);
+    let units = report["units"].as_array().unwrap();
+    assert_eq!(units.len(), 2);
+    assert!(units.iter().all(|u| u["name"] == "foo"));
+    assert!(units.iter().any(|u| u["target"] == " bin \"foo\""));
+    assert!(!report["concurrency"].as_array().unwrap().is_empty());
+    // The binary can only start once the library is done, so both are on the
+    // critical path.
+    let critical_path = report["critical_path"].as_array().unwrap();
+    assert_eq!(critical_path.len(), 2);
+    assert_eq!(
+        units[critical_path[1].as_u64().unwrap() as usize]["target"],
+        " bin \"foo\""
+    );
+}
+
+#[cargo_test]
+fn compare_with_previous_report() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(1)); }",
+        )
+        .file(
+            "previous.json",
+            r#"
+            {
+                "duration": 0.5,
+                "units": [
+                    {
+                        "name": "foo",
+                        "version": "0.0.1",
+                        "target": " custom-build (run)",
+                        "duration": 0.0
+                    },
+                    {
+                        "name": "foo",
+                        "version": "0.0.1",
+                        "target": "",
+                        "duration": 100.0
+                    }
+                ]
+            }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Ztimings-compare=previous.json")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[..]Regressed foo v0.0.1 custom-build (run) 0.0s -> [..]s")
+        .with_stderr_does_not_contain("[..]Regressed foo v0.0.1 100.0s[..]")
+        .with_stderr_contains(
+            "[..]Timing total [..]s, previously 0.5s, 1 unit(s) regressed \
+             compared to [..]previous.json",
+        )
+        .run();
+
+    // A bad report is an error before anything is built.
+    p.change_file("src/lib.rs", "pub fn f() {}");
+    p.cargo("build -Ztimings-compare=missing.json")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] failed to read previous timing report `[..]missing.json`")
+        .with_stderr_does_not_contain("[COMPILING][..]")
+        .run();
+    p.change_file("previous.json", "{");
+    p.cargo("build -Ztimings-compare=previous.json")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] failed to read previous timing report `[..]previous.json`")
+        .with_stderr_does_not_contain("[COMPILING][..]")
+        .run();
+}
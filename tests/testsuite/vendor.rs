@@ -50,6 +50,70 @@ fn add_vendor_config(p: &Project) {
     );
 }
 
+#[cargo_test]
+fn platform_filter() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                log = "0.3.5"
+
+                [target.'cfg(target_os = "haiku")'.dependencies]
+                haiku = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("log", "0.3.5")
+        .file("src/lib.rs", "pub fn log() {}")
+        .publish();
+    Package::new("haiku-sys", "0.1.0")
+        .file("src/lib.rs", "pub fn sys() {}")
+        .file("import/huge.a", "not really huge")
+        .publish();
+    Package::new("haiku", "0.1.0")
+        .dep("haiku-sys", "0.1.0")
+        .file("src/lib.rs", "pub fn haiku() {}")
+        .publish();
+
+    p.cargo("vendor --respect-source-config --versioned-dirs --platform x86_64-unknown-linux-gnu")
+        .with_stderr_contains("[..]Stubbing haiku v0.1.0 (not used on x86_64-unknown-linux-gnu)")
+        .with_stderr_contains(
+            "[..]Stubbing haiku-sys v0.1.0 (not used on x86_64-unknown-linux-gnu)",
+        )
+        .run();
+    assert_eq!(
+        p.read_file("vendor/log-0.3.5/src/lib.rs"),
+        "pub fn log() {}"
+    );
+    assert_eq!(p.read_file("vendor/haiku-0.1.0/src/lib.rs"), "");
+    assert!(p
+        .read_file("vendor/haiku-sys-0.1.0/Cargo.toml")
+        .contains("name = \"haiku-sys\""));
+    assert!(!p.root().join("vendor/haiku-sys-0.1.0/import").exists());
+    let cksum = p.read_file("vendor/haiku-sys-0.1.0/.cargo-checksum.json");
+    assert!(cksum.contains("\"stub\":true"));
+
+    add_vendor_config(&p);
+    p.cargo("build").run();
+
+    // Vendoring again for all platforms replaces the stubs.
+    p.change_file(".cargo/config", "");
+    p.cargo("vendor --respect-source-config --versioned-dirs")
+        .with_stderr_does_not_contain("[..]Stubbing[..]")
+        .run();
+    assert_eq!(
+        p.read_file("vendor/haiku-sys-0.1.0/import/huge.a"),
+        "not really huge"
+    );
+}
+
 #[cargo_test]
 fn two_versions() {
     let p = project()
@@ -514,6 +578,145 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn incremental_update() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bitflags = "=0.7.0"
+                log = "=0.3.5"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("bitflags", "0.7.0").publish();
+    Package::new("log", "0.3.5").publish();
+    Package::new("log", "0.3.6").publish();
+    Package::new("libc", "0.2.30").publish();
+
+    p.cargo("vendor --respect-source-config")
+        .with_stderr_contains("[..]Adding bitflags v0.7.0")
+        .with_stderr_contains("[..]Adding log v0.3.5")
+        .run();
+
+    // Other files in the vendor directory are left alone, local edits of the
+    // vendored files are not.
+    p.change_file("vendor/bitflags/src/lib.rs", "// patched");
+    p.change_file("vendor/patches/log.patch", "");
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            libc = "=0.2.30"
+            bitflags = "=0.7.0"
+            log = "=0.3.6"
+        "#,
+    );
+    p.cargo("vendor --respect-source-config")
+        .with_stderr_contains("[..]Updating log v0.3.5 -> v0.3.6")
+        .with_stderr_contains("[..]Adding libc v0.2.30")
+        .with_stderr_contains("[..]Vendoring log v0.3.6 [..]")
+        .with_stderr_contains("[..]Vendoring bitflags v0.7.0 [..]")
+        .with_stderr_does_not_contain("[..]Updating bitflags[..]")
+        .run();
+    assert_eq!(p.read_file("vendor/bitflags/src/lib.rs"), "");
+    assert!(p.root().join("vendor/patches/log.patch").exists());
+    assert!(p
+        .read_file("vendor/log/Cargo.toml")
+        .contains("version = \"0.3.6\""));
+}
+
+#[cargo_test]
+fn check() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bitflags = "=0.7.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    Package::new("bitflags", "0.7.0").publish();
+    Package::new("log", "0.3.5").publish();
+
+    p.cargo("vendor --respect-source-config --check")
+        .with_status(101)
+        .with_stderr_contains("    bitflags v0.7.0 is missing or out of date")
+        .with_stderr_does_not_contain("[..]Adding[..]")
+        .run();
+    assert!(!p.root().join("vendor").exists());
+    // The lock file isn't written either.
+    assert!(!p.root().join("Cargo.lock").exists());
+
+    p.cargo("vendor --respect-source-config").run();
+    p.cargo("vendor --respect-source-config --check")
+        .with_stdout("")
+        .run();
+
+    // Vendored files are checked against `.cargo-checksum.json`.
+    p.change_file("vendor/bitflags/src/lib.rs", "// edited");
+    p.cargo("vendor --respect-source-config --check")
+        .with_status(101)
+        .with_stderr_contains("    `src/lib.rs` of bitflags v0.7.0 was modified")
+        .run();
+    p.cargo("vendor --respect-source-config")
+        .with_stderr_contains("[..]Vendoring bitflags v0.7.0 [..]")
+        .run();
+    assert_eq!(p.read_file("vendor/bitflags/src/lib.rs"), "");
+    p.cargo("vendor --respect-source-config --check").run();
+    p.cargo("build").run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            log = "=0.3.5"
+        "#,
+    );
+    let lock = p.read_lockfile();
+    p.cargo("vendor --respect-source-config --check")
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]Removing[..]")
+        .with_stderr_does_not_contain("[..]Adding[..]")
+        .with_stderr_contains(
+            "\
+[ERROR] failed to sync
+
+Caused by:
+  the vendor directory `vendor` is not up to date:
+    `[..]vendor/bitflags` is no longer used
+    log v0.3.5 is missing or out of date
+  Run `cargo vendor` without `--check` to update it.",
+        )
+        .run();
+    assert!(p.root().join("vendor/bitflags").exists());
+    assert!(!p.root().join("vendor/log").exists());
+    assert_eq!(p.read_lockfile(), lock);
+}
+
 #[cargo_test]
 fn depend_on_vendor_dir_not_deleted() {
     let p = project()
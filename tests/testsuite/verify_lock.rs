@@ -0,0 +1,220 @@
+//! Tests for the `cargo verify-lock` command.
+
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_lib_manifest, basic_manifest, git, project};
+
+#[cargo_test]
+fn clean_lockfile_passes() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("verify-lock --deny-yanked --deny-git --require-checksums")
+        .with_stderr("[UPDATING] `[..]` index")
+        .run();
+}
+
+#[cargo_test]
+fn missing_lockfile() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("verify-lock")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] no Cargo.lock file found in `[CWD]`, \
+             run `cargo generate-lockfile` to create one",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn deny_yanked() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+    Package::new("bar", "0.1.0").yanked(true).publish();
+
+    // Nothing is checked unless asked for.
+    p.cargo("verify-lock").with_stderr("").run();
+
+    p.cargo("verify-lock --deny-yanked")
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ERROR] package `bar v0.1.0` is yanked in registry `crates.io`
+[ERROR] 1 lock file policy violation found
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn missing_checksum() {
+    Package::new("bar", "0.1.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "Cargo.lock",
+            r#"
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "foo"
+version = "0.0.1"
+dependencies = [
+ "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+]
+"#,
+        )
+        .build();
+
+    p.cargo("verify-lock --require-checksums --message-format json")
+        .with_status(101)
+        .with_json(
+            r#"
+            {
+              "success": false,
+              "violations": [
+                {
+                  "package_id": "bar 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                  "kind": "missing-checksum",
+                  "message": "package `bar v0.1.0` does not have a checksum"
+                }
+              ]
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn deny_git_from_config() {
+    let git_project = git::new("dep1", |project| {
+        project
+            .file("Cargo.toml", &basic_lib_manifest("dep1"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+
+                    [dependencies]
+                    dep1 = {{ git = '{}' }}
+                "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+                [lockfile]
+                deny-git = true
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("verify-lock --message-format json")
+        .with_status(101)
+        .with_json(
+            r#"
+            {
+              "success": false,
+              "violations": [
+                {
+                  "package_id": "dep1 0.5.0 (git+[..])",
+                  "kind": "git-source",
+                  "message": "package `dep1 v0.5.0 ([..])` comes from a git repository"
+                }
+              ]
+            }
+            "#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn allowed_registries() {
+    cargo_test_support::registry::init();
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "0.1.0").alternative(true).publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = "0.1"
+                baz = { version = "0.1", registry = "alternative" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.cargo("verify-lock --allow-registry crates-io --allow-registry alternative")
+        .run();
+
+    p.cargo("verify-lock --allow-registry crates-io")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `baz v0.1.0 (registry `[..]`)` comes from registry `[..]` which is not allowed
+[ERROR] 1 lock file policy violation found
+",
+        )
+        .run();
+}
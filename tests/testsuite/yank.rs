@@ -4,7 +4,8 @@ use std::fs;
 
 use cargo_test_support::paths::CargoPathExt;
 use cargo_test_support::project;
-use cargo_test_support::registry;
+use cargo_test_support::registry::{self, Package};
+use cargo_test_support::registry_server::RegistryServer;
 
 fn setup(name: &str, version: &str) {
     let dir = registry::api_path().join(format!("api/v1/crates/{}/{}", name, version));
@@ -46,3 +47,44 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn yank_on_server() {
+    Package::new("bar", "0.0.1").publish();
+    let _server = RegistryServer::start();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("yank --vers 0.0.1 bar")
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[WARNING] using `registry.token` config value with source replacement is deprecated
+This may become a hard error in the future[..]
+Use the --token command-line flag to remove this warning.
+        Yank bar:0.0.1
+",
+        )
+        .run();
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("error: no matching package named `bar` found")
+        .run();
+
+    p.cargo("yank --undo --vers 0.0.1 bar").run();
+    p.cargo("build").run();
+}